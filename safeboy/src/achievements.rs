@@ -0,0 +1,1158 @@
+use alloc::vec::Vec;
+
+/// Identifies an achievement added with [`AchievementSet::add`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct AchievementId(u32);
+
+/// Set of conditions that unlocks an achievement, parsed from an rcheevos condition string.
+///
+/// # Syntax
+///
+/// A trigger is a core group followed by any number of alternate groups, separated by `S`. It is
+/// true when the core group and at least one alternate group (if any) are true. Each group is a
+/// list of conditions separated by `_`, and is true when all of its conditions are true.
+///
+/// A condition is `[flag:]operand[operator operand][.hits.]`, e.g. `R:0xH1234<d0xH1234` or
+/// `0x 1234=300.10.`.
+///
+/// Operands are constants (`123`, `-1`, or `h7B`) or memory references (`0x` followed by a size
+/// and an address). Sizes are:
+///
+/// | Size          | Meaning                                   |
+/// |---------------|-------------------------------------------|
+/// | `H`           | 8-bit                                     |
+/// | ` ` or none   | 16-bit little endian                      |
+/// | `W`           | 24-bit little endian                      |
+/// | `X`           | 32-bit little endian                      |
+/// | `I`, `J`, `G` | 16-, 24-, and 32-bit big endian           |
+/// | `M`-`T`       | Bits 0-7                                  |
+/// | `L`, `U`      | Lower and upper nibble                    |
+/// | `K`           | Number of set bits in a byte              |
+///
+/// Memory references can be prefixed with `d` (value on the previous frame), `p` (the last value
+/// that differs from the current value), `b` (binary-coded decimal), or `~` (bitwise inverse).
+///
+/// Comparisons are `=`, `!=`, `<`, `<=`, `>`, and `>=`. Conditions with a hit count must be true
+/// for that many frames (not necessarily in a row) before they are considered true.
+///
+/// Flags are:
+///
+/// | Flag | Meaning                                                                        |
+/// |------|--------------------------------------------------------------------------------|
+/// | `R`  | ResetIf: if true, all hit counts are reset and the trigger is false            |
+/// | `P`  | PauseIf: if true, the group is false and its hit counts are frozen             |
+/// | `A`  | AddSource: add the value to the next condition's left operand                  |
+/// | `B`  | SubSource: subtract the value from the next condition's left operand           |
+/// | `C`  | AddHits: add the hit count to the next condition's hit count                   |
+/// | `D`  | SubHits: subtract the hit count from the next condition's hit count            |
+/// | `N`  | AndNext: the next condition is only true if this one is, too                   |
+/// | `O`  | OrNext: the next condition is true if this one is                              |
+/// | `I`  | AddAddress: add the value to the addresses in the next condition               |
+/// | `Z`  | ResetNextIf: if true, the next condition's hit count is reset                  |
+/// | `M`  | Measured: report progress (see [`AchievementSet::get_progress`])               |
+/// | `Q`  | MeasuredIf: progress is only reported while true                               |
+/// | `T`  | Trigger: treated as a normal condition                                         |
+///
+/// `A`, `B`, and `I` conditions can use `*`, `/`, `&`, `^`, `%`, `+`, or `-` instead of a
+/// comparison.
+///
+/// Addresses are in the CPU's address space (`$0000`-`$FFFF`); higher addresses read as 0.
+/// Floating point operands and `K:` (Remember) conditions are not supported.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AchievementTrigger {
+    groups: Vec<Vec<Condition>>
+}
+
+/// Describes an error from parsing an rcheevos condition string.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AchievementParseError {
+    /// Byte offset of the error in the string
+    pub position: usize,
+
+    /// What went wrong
+    pub kind: AchievementParseErrorKind
+}
+
+/// Describes what went wrong when parsing an rcheevos condition string.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AchievementParseErrorKind {
+    /// The string ended in the middle of a condition.
+    UnexpectedEnd,
+
+    /// An unexpected character was found.
+    UnexpectedCharacter(char),
+
+    /// A number is invalid or out of range.
+    InvalidNumber,
+
+    /// A condition that needs a comparison does not have one, or a modifier condition uses a
+    /// comparison where arithmetic is expected.
+    InvalidOperator,
+
+    /// The string uses syntax that is not supported (floating point operands or Remember
+    /// conditions).
+    Unsupported
+}
+
+/// State of an achievement.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AchievementState {
+    /// The trigger has not been false yet, so it can't unlock.
+    ///
+    /// This avoids unlocking achievements immediately when they are added mid-game.
+    Waiting,
+
+    /// The trigger is being evaluated.
+    Active,
+
+    /// The core group is paused by a PauseIf condition.
+    Paused,
+
+    /// The trigger was true, and the achievement is unlocked.
+    Triggered
+}
+
+/// Set of achievements, evaluated at the end of every frame.
+///
+/// Set it with [`set_achievement_set`](crate::RunnableInstanceFunctions::set_achievement_set).
+/// Memory is read without side effects, and every achievement that unlocks is passed to
+/// [`achievement_triggered`](crate::GameboyCallbacks::achievement_triggered).
+#[derive(Clone, Default)]
+pub struct AchievementSet {
+    achievements: Vec<Achievement>,
+    next_id: u32,
+    triggered: Vec<AchievementId>
+}
+
+#[derive(Clone)]
+struct Achievement {
+    id: AchievementId,
+    trigger: AchievementTrigger,
+    state: AchievementState,
+    progress: Option<(u32, u32)>
+}
+
+impl AchievementSet {
+    /// Instantiate an empty set.
+    pub fn new() -> AchievementSet {
+        AchievementSet::default()
+    }
+
+    /// Add an achievement in the [`Waiting`](AchievementState::Waiting) state.
+    pub fn add(&mut self, trigger: AchievementTrigger) -> AchievementId {
+        let id = AchievementId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.achievements.push(Achievement { id, trigger, state: AchievementState::Waiting, progress: None });
+        id
+    }
+
+    /// Remove an achievement.
+    ///
+    /// Returns `false` if there is no such achievement.
+    pub fn remove(&mut self, id: AchievementId) -> bool {
+        let Some(index) = self.achievements.iter().position(|a| a.id == id) else {
+            return false
+        };
+        self.achievements.remove(index);
+        true
+    }
+
+    /// Remove all achievements.
+    pub fn clear(&mut self) {
+        self.achievements.clear();
+        self.triggered.clear();
+    }
+
+    /// Get the number of achievements.
+    pub fn len(&self) -> usize {
+        self.achievements.len()
+    }
+
+    /// Returns true if there are no achievements.
+    pub fn is_empty(&self) -> bool {
+        self.achievements.is_empty()
+    }
+
+    /// Get the state of an achievement.
+    pub fn get_state(&self, id: AchievementId) -> Option<AchievementState> {
+        self.get(id).map(|a| a.state)
+    }
+
+    /// Get the progress of an achievement with a Measured condition as `(value, target)`.
+    pub fn get_progress(&self, id: AchievementId) -> Option<(u32, u32)> {
+        self.get(id).and_then(|a| a.progress)
+    }
+
+    /// Reset an achievement's hit counts and return it to the
+    /// [`Waiting`](AchievementState::Waiting) state.
+    ///
+    /// Returns `false` if there is no such achievement.
+    pub fn reset(&mut self, id: AchievementId) -> bool {
+        let Some(achievement) = self.achievements.iter_mut().find(|a| a.id == id) else {
+            return false
+        };
+        achievement.trigger.reset();
+        achievement.state = AchievementState::Waiting;
+        achievement.progress = None;
+        true
+    }
+
+    /// Get the achievements that unlocked at the end of the last frame.
+    pub fn get_triggered(&self) -> &[AchievementId] {
+        &self.triggered
+    }
+
+    fn get(&self, id: AchievementId) -> Option<&Achievement> {
+        self.achievements.iter().find(|a| a.id == id)
+    }
+
+    /// Evaluate all achievements, returning the number that unlocked.
+    ///
+    /// `read` reads a byte from an address.
+    pub(crate) fn update<R: Fn(u32) -> u8>(&mut self, read: R) -> usize {
+        self.triggered.clear();
+        for achievement in &mut self.achievements {
+            if achievement.state == AchievementState::Triggered {
+                continue
+            }
+
+            let result = achievement.trigger.test(&read);
+            achievement.progress = result.progress;
+            achievement.state = match achievement.state {
+                AchievementState::Waiting if result.triggered => {
+                    achievement.trigger.reset();
+                    AchievementState::Waiting
+                },
+                _ if result.triggered => {
+                    self.triggered.push(achievement.id);
+                    AchievementState::Triggered
+                },
+                _ if result.paused => AchievementState::Paused,
+                _ => AchievementState::Active
+            };
+        }
+        self.triggered.len()
+    }
+}
+
+impl AchievementTrigger {
+    /// Parse an rcheevos condition string.
+    pub fn parse(string: &str) -> Result<AchievementTrigger, AchievementParseError> {
+        let mut parser = Parser { bytes: string.as_bytes(), position: 0 };
+        let mut groups = Vec::new();
+        loop {
+            groups.push(parser.parse_group()?);
+            match parser.peek() {
+                Some(b'S') => parser.position += 1,
+                None => break,
+                Some(c) => return Err(parser.error(AchievementParseErrorKind::UnexpectedCharacter(c as char)))
+            }
+        }
+        Ok(AchievementTrigger { groups })
+    }
+
+    /// Reset all hit counts and remembered values.
+    pub fn reset(&mut self) {
+        for condition in self.groups.iter_mut().flatten() {
+            condition.hits = 0;
+            for operand in [&mut condition.left, &mut condition.right] {
+                if let Operand::Memory(memory) = operand {
+                    memory.current = 0;
+                    memory.delta = 0;
+                    memory.prior = 0;
+                }
+            }
+        }
+    }
+
+    fn test<R: Fn(u32) -> u8>(&mut self, read: &R) -> TriggerResult {
+        for group in &mut self.groups {
+            update_memory(group, read);
+        }
+
+        let results: Vec<GroupResult> = self.groups.iter_mut().map(|g| test_group(g)).collect();
+        let progress = results.iter().find_map(|r| r.progress);
+        if results.iter().any(|r| r.reset) {
+            for condition in self.groups.iter_mut().flatten() {
+                condition.hits = 0;
+            }
+            return TriggerResult { triggered: false, paused: false, progress }
+        }
+
+        let core = &results[0];
+        let alternates = &results[1..];
+        let alternate_valid = alternates.is_empty() || alternates.iter().any(|r| r.valid && !r.paused);
+        TriggerResult {
+            triggered: core.valid && !core.paused && alternate_valid,
+            paused: core.paused,
+            progress
+        }
+    }
+}
+
+struct TriggerResult {
+    triggered: bool,
+    paused: bool,
+    progress: Option<(u32, u32)>
+}
+
+struct GroupResult {
+    valid: bool,
+    paused: bool,
+    reset: bool,
+    progress: Option<(u32, u32)>
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Flag {
+    None,
+    ResetIf,
+    PauseIf,
+    AddSource,
+    SubSource,
+    AddHits,
+    SubHits,
+    AndNext,
+    OrNext,
+    AddAddress,
+    ResetNextIf,
+    Measured,
+    MeasuredIf,
+    Trigger
+}
+
+impl Flag {
+    /// Returns true if the condition only modifies the next condition's operand or address.
+    const fn is_modifier(self) -> bool {
+        matches!(self, Flag::AddSource | Flag::SubSource | Flag::AddAddress)
+    }
+
+    /// Returns true if the condition ends a chain of conditions.
+    const fn is_terminal(self) -> bool {
+        matches!(self, Flag::None | Flag::ResetIf | Flag::PauseIf | Flag::Measured | Flag::MeasuredIf | Flag::Trigger)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Size {
+    Bit(u8),
+    LowNibble,
+    HighNibble,
+    BitCount,
+    Bits8,
+    Bits16,
+    Bits24,
+    Bits32,
+    Bits16BigEndian,
+    Bits24BigEndian,
+    Bits32BigEndian
+}
+
+impl Size {
+    const fn get_mask(self) -> u32 {
+        match self {
+            Size::Bit(_) => 0x1,
+            Size::LowNibble | Size::HighNibble => 0xF,
+            Size::BitCount => 0xF,
+            Size::Bits8 => 0xFF,
+            Size::Bits16 | Size::Bits16BigEndian => 0xFFFF,
+            Size::Bits24 | Size::Bits24BigEndian => 0xFFFFFF,
+            Size::Bits32 | Size::Bits32BigEndian => 0xFFFFFFFF
+        }
+    }
+
+    fn read<R: Fn(u32) -> u8>(self, read: &R, address: u32) -> u32 {
+        let bytes = |count: u32, big_endian: bool| {
+            (0..count).fold(0u32, |value, i| {
+                let byte = read(address.wrapping_add(i)) as u32;
+                if big_endian { (value << 8) | byte } else { value | (byte << (i * 8)) }
+            })
+        };
+        match self {
+            Size::Bit(bit) => (read(address) as u32 >> bit) & 1,
+            Size::LowNibble => read(address) as u32 & 0xF,
+            Size::HighNibble => read(address) as u32 >> 4,
+            Size::BitCount => read(address).count_ones(),
+            Size::Bits8 => read(address) as u32,
+            Size::Bits16 => bytes(2, false),
+            Size::Bits24 => bytes(3, false),
+            Size::Bits32 => bytes(4, false),
+            Size::Bits16BigEndian => bytes(2, true),
+            Size::Bits24BigEndian => bytes(3, true),
+            Size::Bits32BigEndian => bytes(4, true)
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum MemoryKind {
+    Value,
+    Delta,
+    Prior,
+    Bcd,
+    Invert
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct MemoryOperand {
+    size: Size,
+    kind: MemoryKind,
+    address: u32,
+    current: u32,
+    delta: u32,
+    prior: u32
+}
+
+impl MemoryOperand {
+    const fn new(size: Size, kind: MemoryKind, address: u32) -> MemoryOperand {
+        MemoryOperand { size, kind, address, current: 0, delta: 0, prior: 0 }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Operand {
+    Constant(u32),
+    Memory(MemoryOperand)
+}
+
+impl Operand {
+    fn get_value(&self) -> u32 {
+        let memory = match self {
+            Operand::Constant(value) => return *value,
+            Operand::Memory(memory) => memory
+        };
+        match memory.kind {
+            MemoryKind::Value => memory.current,
+            MemoryKind::Delta => memory.delta,
+            MemoryKind::Prior => memory.prior,
+            MemoryKind::Bcd => decode_bcd(memory.current),
+            MemoryKind::Invert => !memory.current & memory.size.get_mask()
+        }
+    }
+}
+
+fn decode_bcd(value: u32) -> u32 {
+    (0..8).rev().fold(0, |result, digit| result * 10 + ((value >> (digit * 4)) & 0xF))
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Multiply,
+    Divide,
+    And,
+    Xor,
+    Modulo,
+    Add,
+    Subtract
+}
+
+impl Operator {
+    const fn is_comparison(self) -> bool {
+        matches!(self, Operator::Equal | Operator::NotEqual | Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual)
+    }
+
+    fn compare(self, left: u32, right: u32) -> bool {
+        match self {
+            Operator::Equal => left == right,
+            Operator::NotEqual => left != right,
+            Operator::Less => left < right,
+            Operator::LessEqual => left <= right,
+            Operator::Greater => left > right,
+            Operator::GreaterEqual => left >= right,
+            _ => false
+        }
+    }
+
+    fn apply(self, left: u32, right: u32) -> u32 {
+        match self {
+            Operator::Multiply => left.wrapping_mul(right),
+            Operator::Divide => left.checked_div(right).unwrap_or(0),
+            Operator::And => left & right,
+            Operator::Xor => left ^ right,
+            Operator::Modulo => left.checked_rem(right).unwrap_or(0),
+            Operator::Add => left.wrapping_add(right),
+            Operator::Subtract => left.wrapping_sub(right),
+            _ => left
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct Condition {
+    flag: Flag,
+    left: Operand,
+    operator: Option<Operator>,
+    right: Operand,
+    required_hits: u32,
+    hits: u32
+}
+
+impl Condition {
+    /// Get the value of a modifier condition.
+    fn get_modifier_value(&self) -> u32 {
+        let left = self.left.get_value();
+        match self.operator {
+            Some(operator) => operator.apply(left, self.right.get_value()),
+            None => left
+        }
+    }
+}
+
+/// Read the current value of every memory operand in `group`, applying AddAddress offsets.
+fn update_memory<R: Fn(u32) -> u8>(group: &mut [Condition], read: &R) {
+    let mut offset = 0u32;
+    for condition in group {
+        for operand in [&mut condition.left, &mut condition.right] {
+            let Operand::Memory(memory) = operand else {
+                continue
+            };
+            let address = memory.address.wrapping_add(offset);
+            let value = memory.size.read(read, address);
+            memory.delta = memory.current;
+            if value != memory.current {
+                memory.prior = memory.current;
+            }
+            memory.current = value;
+        }
+        offset = match condition.flag {
+            Flag::AddAddress => condition.get_modifier_value(),
+            _ => 0
+        };
+    }
+}
+
+fn test_group(group: &mut [Condition]) -> GroupResult {
+    let mut result = GroupResult { valid: true, paused: false, reset: false, progress: None };
+
+    // Pause conditions are evaluated first, so nothing else is updated while paused.
+    test_chains(group, true, &mut result);
+    if result.paused {
+        result.valid = false;
+        return result
+    }
+    test_chains(group, false, &mut result);
+    result
+}
+
+/// Evaluate every chain of conditions ending in a PauseIf condition (if `pause` is true) or any
+/// other condition (if `pause` is false).
+fn test_chains(group: &mut [Condition], pause: bool, result: &mut GroupResult) {
+    let mut measured_if = true;
+    let mut progress = None;
+
+    let mut start = 0;
+    while start < group.len() {
+        let end = group[start..].iter().position(|c| c.flag.is_terminal()).map_or(group.len(), |i| start + i + 1);
+        let chain = &mut group[start..end];
+        start = end;
+
+        let terminal_is_pause = chain.last().is_some_and(|c| c.flag == Flag::PauseIf);
+        if terminal_is_pause != pause {
+            continue
+        }
+
+        let mut add_value = 0u32;
+        let mut add_hits = 0i64;
+        let mut and_next = true;
+        let mut or_next = false;
+        let mut reset_next = false;
+
+        for condition in chain {
+            match condition.flag {
+                Flag::AddSource => {
+                    add_value = add_value.wrapping_add(condition.get_modifier_value());
+                    continue
+                },
+                Flag::SubSource => {
+                    add_value = add_value.wrapping_sub(condition.get_modifier_value());
+                    continue
+                },
+                Flag::AddAddress => continue,
+                _ => ()
+            }
+
+            let left = condition.left.get_value().wrapping_add(add_value);
+            let right = condition.right.get_value();
+            add_value = 0;
+
+            let operator = condition.operator.expect("comparison conditions always have an operator");
+            let mut valid = (operator.compare(left, right) && and_next) || or_next;
+            and_next = true;
+            or_next = false;
+
+            if reset_next {
+                condition.hits = 0;
+                valid = false;
+            }
+            else if valid {
+                if condition.required_hits == 0 {
+                    condition.hits = condition.hits.saturating_add(1);
+                }
+                else if condition.hits < condition.required_hits {
+                    condition.hits += 1;
+                    valid = condition.hits == condition.required_hits;
+                }
+            }
+            else if condition.required_hits != 0 && condition.hits >= condition.required_hits {
+                valid = true;
+            }
+
+            match condition.flag {
+                Flag::AddHits => {
+                    add_hits += condition.hits as i64;
+                    reset_next = false;
+                    continue
+                },
+                Flag::SubHits => {
+                    add_hits -= condition.hits as i64;
+                    continue
+                },
+                Flag::ResetNextIf => {
+                    reset_next = valid;
+                    continue
+                },
+                Flag::AndNext => {
+                    and_next = valid;
+                    continue
+                },
+                Flag::OrNext => {
+                    or_next = valid;
+                    continue
+                },
+                _ => ()
+            }
+            reset_next = false;
+
+            let mut total_hits = condition.hits as i64;
+            if add_hits != 0 {
+                total_hits += add_hits;
+                if condition.required_hits != 0 {
+                    valid = total_hits >= condition.required_hits as i64;
+                }
+                add_hits = 0;
+            }
+
+            match condition.flag {
+                Flag::PauseIf => result.paused |= valid,
+                Flag::ResetIf => result.reset |= valid,
+                Flag::MeasuredIf => measured_if &= valid,
+                _ => {
+                    if condition.flag == Flag::Measured {
+                        progress = Some(if condition.required_hits != 0 {
+                            (total_hits.clamp(0, condition.required_hits as i64) as u32, condition.required_hits)
+                        }
+                        else {
+                            (left.min(right), right)
+                        });
+                    }
+                    result.valid &= valid;
+                }
+            }
+        }
+    }
+
+    if let Some((value, target)) = progress {
+        result.progress = Some((if measured_if { value } else { 0 }, target));
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Result<u8, AchievementParseError> {
+        let byte = self.peek().ok_or(self.error(AchievementParseErrorKind::UnexpectedEnd))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn error(&self, kind: AchievementParseErrorKind) -> AchievementParseError {
+        AchievementParseError { position: self.position, kind }
+    }
+
+    fn parse_group(&mut self) -> Result<Vec<Condition>, AchievementParseError> {
+        let mut conditions = Vec::new();
+        if matches!(self.peek(), None | Some(b'S')) {
+            return Ok(conditions)
+        }
+        loop {
+            conditions.push(self.parse_condition()?);
+            if self.peek() != Some(b'_') {
+                break
+            }
+            self.position += 1;
+        }
+        Ok(conditions)
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition, AchievementParseError> {
+        let flag = if self.bytes.get(self.position + 1) == Some(&b':') {
+            let flag = match self.next()?.to_ascii_uppercase() {
+                b'R' => Flag::ResetIf,
+                b'P' => Flag::PauseIf,
+                b'A' => Flag::AddSource,
+                b'B' => Flag::SubSource,
+                b'C' => Flag::AddHits,
+                b'D' => Flag::SubHits,
+                b'N' => Flag::AndNext,
+                b'O' => Flag::OrNext,
+                b'I' => Flag::AddAddress,
+                b'Z' => Flag::ResetNextIf,
+                b'M' => Flag::Measured,
+                b'Q' => Flag::MeasuredIf,
+                b'T' => Flag::Trigger,
+                b'K' => {
+                    self.position -= 1;
+                    return Err(self.error(AchievementParseErrorKind::Unsupported))
+                },
+                c => {
+                    self.position -= 1;
+                    return Err(self.error(AchievementParseErrorKind::UnexpectedCharacter(c as char)))
+                }
+            };
+            self.position += 1;
+            flag
+        }
+        else {
+            Flag::None
+        };
+
+        let left = self.parse_operand()?;
+        let operator_position = self.position;
+        let mut operator = self.parse_operator()?;
+        let right = match operator {
+            Some(_) => self.parse_operand()?,
+            None => Operand::Constant(0)
+        };
+
+        let is_comparison = operator.is_some_and(Operator::is_comparison);
+        if flag.is_modifier() {
+            // Modifiers used to be written with a comparison, which is ignored.
+            if is_comparison {
+                operator = None;
+            }
+        }
+        else if !is_comparison {
+            return Err(AchievementParseError { position: operator_position, kind: AchievementParseErrorKind::InvalidOperator })
+        }
+
+        let required_hits = match self.peek() {
+            Some(b'.') => {
+                self.position += 1;
+                let hits = self.parse_decimal()?;
+                self.expect(b'.')?;
+                hits
+            },
+            Some(b'(') => {
+                self.position += 1;
+                let hits = self.parse_decimal()?;
+                self.expect(b')')?;
+                hits
+            },
+            _ => 0
+        };
+
+        Ok(Condition { flag, left, operator, right, required_hits, hits: 0 })
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), AchievementParseError> {
+        match self.next()? {
+            c if c == expected => Ok(()),
+            c => {
+                self.position -= 1;
+                Err(self.error(AchievementParseErrorKind::UnexpectedCharacter(c as char)))
+            }
+        }
+    }
+
+    fn parse_operator(&mut self) -> Result<Option<Operator>, AchievementParseError> {
+        let Some(c) = self.peek() else {
+            return Ok(None)
+        };
+        let followed_by_equal = self.bytes.get(self.position + 1) == Some(&b'=');
+        let (operator, length) = match c {
+            b'=' if followed_by_equal => (Operator::Equal, 2),
+            b'=' => (Operator::Equal, 1),
+            b'!' if followed_by_equal => (Operator::NotEqual, 2),
+            b'<' if followed_by_equal => (Operator::LessEqual, 2),
+            b'<' => (Operator::Less, 1),
+            b'>' if followed_by_equal => (Operator::GreaterEqual, 2),
+            b'>' => (Operator::Greater, 1),
+            b'*' => (Operator::Multiply, 1),
+            b'/' => (Operator::Divide, 1),
+            b'&' => (Operator::And, 1),
+            b'^' => (Operator::Xor, 1),
+            b'%' => (Operator::Modulo, 1),
+            b'+' => (Operator::Add, 1),
+            b'-' => (Operator::Subtract, 1),
+            _ => return Ok(None)
+        };
+        self.position += length;
+        Ok(Some(operator))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, AchievementParseError> {
+        let kind = match self.peek().map(|c| c.to_ascii_lowercase()) {
+            Some(b'd') => Some(MemoryKind::Delta),
+            Some(b'p') => Some(MemoryKind::Prior),
+            Some(b'b') => Some(MemoryKind::Bcd),
+            Some(b'~') => Some(MemoryKind::Invert),
+            _ => None
+        };
+        if kind.is_some() {
+            self.position += 1;
+        }
+
+        let is_memory = self.peek() == Some(b'0') && matches!(self.bytes.get(self.position + 1), Some(b'x' | b'X'));
+        if !is_memory {
+            if kind.is_some() {
+                return match self.peek() {
+                    Some(c) => Err(self.error(AchievementParseErrorKind::UnexpectedCharacter(c as char))),
+                    None => Err(self.error(AchievementParseErrorKind::UnexpectedEnd))
+                }
+            }
+            return self.parse_constant()
+        }
+        self.position += 2;
+
+        // The size is optional for 16-bit values.
+        let size = match self.peek().map(|c| c.to_ascii_uppercase()) {
+            Some(c) if c.is_ascii_hexdigit() => {
+                let address = self.parse_hex()?;
+                return Ok(Operand::Memory(MemoryOperand::new(Size::Bits16, kind.unwrap_or(MemoryKind::Value), address)))
+            },
+            Some(b' ') => Size::Bits16,
+            Some(b'H') => Size::Bits8,
+            Some(b'W') => Size::Bits24,
+            Some(b'X') => Size::Bits32,
+            Some(b'I') => Size::Bits16BigEndian,
+            Some(b'J') => Size::Bits24BigEndian,
+            Some(b'G') => Size::Bits32BigEndian,
+            Some(c @ b'M'..=b'T') => Size::Bit(c - b'M'),
+            Some(b'L') => Size::LowNibble,
+            Some(b'U') => Size::HighNibble,
+            Some(b'K') => Size::BitCount,
+            Some(c) => return Err(self.error(AchievementParseErrorKind::UnexpectedCharacter(c as char))),
+            None => return Err(self.error(AchievementParseErrorKind::UnexpectedEnd))
+        };
+        self.position += 1;
+
+        let address = self.parse_hex()?;
+        Ok(Operand::Memory(MemoryOperand::new(size, kind.unwrap_or(MemoryKind::Value), address)))
+    }
+
+    fn parse_constant(&mut self) -> Result<Operand, AchievementParseError> {
+        match self.peek() {
+            Some(b'h' | b'H') => {
+                self.position += 1;
+                Ok(Operand::Constant(self.parse_hex()?))
+            },
+            Some(b'f' | b'F' | b'{') => Err(self.error(AchievementParseErrorKind::Unsupported)),
+            Some(b'-') => {
+                self.position += 1;
+                Ok(Operand::Constant(self.parse_decimal()?.wrapping_neg()))
+            },
+            Some(b'+') => {
+                self.position += 1;
+                Ok(Operand::Constant(self.parse_decimal()?))
+            },
+            Some(c) if c.is_ascii_digit() => Ok(Operand::Constant(self.parse_decimal()?)),
+            Some(c) => Err(self.error(AchievementParseErrorKind::UnexpectedCharacter(c as char))),
+            None => Err(self.error(AchievementParseErrorKind::UnexpectedEnd))
+        }
+    }
+
+    fn parse_digits(&mut self, radix: u32) -> Result<u32, AchievementParseError> {
+        let start = self.position;
+        let mut value = 0u32;
+        while let Some(digit) = self.peek().and_then(|c| (c as char).to_digit(radix)) {
+            value = value
+                .checked_mul(radix)
+                .and_then(|v| v.checked_add(digit))
+                .ok_or(AchievementParseError { position: start, kind: AchievementParseErrorKind::InvalidNumber })?;
+            self.position += 1;
+        }
+        if self.position == start {
+            return Err(self.error(match self.peek() {
+                Some(c) => AchievementParseErrorKind::UnexpectedCharacter(c as char),
+                None => AchievementParseErrorKind::UnexpectedEnd
+            }))
+        }
+        Ok(value)
+    }
+
+    fn parse_decimal(&mut self) -> Result<u32, AchievementParseError> {
+        self.parse_digits(10)
+    }
+
+    fn parse_hex(&mut self) -> Result<u32, AchievementParseError> {
+        self.parse_digits(16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(string: &str) -> AchievementTrigger {
+        AchievementTrigger::parse(string).unwrap()
+    }
+
+    fn parse_error(string: &str) -> (usize, AchievementParseErrorKind) {
+        let error = AchievementTrigger::parse(string).unwrap_err();
+        (error.position, error.kind)
+    }
+
+    /// Evaluate a trigger for one frame.
+    fn test(trigger: &mut AchievementTrigger, memory: &[u8]) -> TriggerResult {
+        trigger.test(&|address| memory.get(address as usize).copied().unwrap_or(0))
+    }
+
+    /// Evaluate a trigger over several frames, returning whether it was true on each.
+    fn test_frames(string: &str, frames: &[&[u8]]) -> Vec<bool> {
+        let mut trigger = parse(string);
+        frames.iter().map(|memory| test(&mut trigger, memory).triggered).collect()
+    }
+
+    fn update(set: &mut AchievementSet, memory: &[u8]) -> usize {
+        set.update(|address| memory.get(address as usize).copied().unwrap_or(0))
+    }
+
+    #[test]
+    fn parse_conditions() {
+        let trigger = parse("R:0xH1234<d0xH1234_0x 1234=300.10.");
+        assert_eq!(trigger.groups.len(), 1);
+        let [reset, hits] = trigger.groups[0].as_slice() else { panic!("expected two conditions") };
+
+        assert_eq!(reset.flag, Flag::ResetIf);
+        assert_eq!(reset.left, Operand::Memory(MemoryOperand::new(Size::Bits8, MemoryKind::Value, 0x1234)));
+        assert_eq!(reset.operator, Some(Operator::Less));
+        assert_eq!(reset.right, Operand::Memory(MemoryOperand::new(Size::Bits8, MemoryKind::Delta, 0x1234)));
+        assert_eq!(reset.required_hits, 0);
+
+        assert_eq!(hits.flag, Flag::None);
+        assert_eq!(hits.left, Operand::Memory(MemoryOperand::new(Size::Bits16, MemoryKind::Value, 0x1234)));
+        assert_eq!(hits.operator, Some(Operator::Equal));
+        assert_eq!(hits.right, Operand::Constant(300));
+        assert_eq!(hits.required_hits, 10);
+    }
+
+    #[test]
+    fn parse_operands() {
+        let left = |string: &str| parse(string).groups[0][0].left;
+        let right = |string: &str| parse(string).groups[0][0].right;
+
+        assert_eq!(left("0x1234=1"), Operand::Memory(MemoryOperand::new(Size::Bits16, MemoryKind::Value, 0x1234)));
+        assert_eq!(left("0xM0010=1"), Operand::Memory(MemoryOperand::new(Size::Bit(0), MemoryKind::Value, 0x10)));
+        assert_eq!(left("0xT0010=1"), Operand::Memory(MemoryOperand::new(Size::Bit(7), MemoryKind::Value, 0x10)));
+        assert_eq!(left("b0xG0010=1"), Operand::Memory(MemoryOperand::new(Size::Bits32BigEndian, MemoryKind::Bcd, 0x10)));
+        assert_eq!(left("p0xU10=1"), Operand::Memory(MemoryOperand::new(Size::HighNibble, MemoryKind::Prior, 0x10)));
+        assert_eq!(right("0xH10=h7B"), Operand::Constant(0x7B));
+        assert_eq!(right("0xH10=-1"), Operand::Constant(u32::MAX));
+        assert_eq!(parse("0xH10=1(5)").groups[0][0].required_hits, 5);
+
+        // Modifiers ignore a comparison, but keep arithmetic.
+        let trigger = parse("A:0xH10=0_B:0xH11*2_0xH12=3");
+        assert_eq!(trigger.groups[0][0].operator, None);
+        assert_eq!(trigger.groups[0][1].operator, Some(Operator::Multiply));
+    }
+
+    #[test]
+    fn parse_groups() {
+        assert_eq!(parse("0xH00=1S0xH01=1S0xH02=1").groups.len(), 3);
+
+        let trigger = parse("S0xH01=1S0xH02=1");
+        assert_eq!(trigger.groups.len(), 3);
+        assert!(trigger.groups[0].is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse_error("0xH0000"), (7, AchievementParseErrorKind::InvalidOperator));
+        assert_eq!(parse_error("0xH0000*2"), (7, AchievementParseErrorKind::InvalidOperator));
+        assert_eq!(parse_error("0xH0000="), (8, AchievementParseErrorKind::UnexpectedEnd));
+        assert_eq!(parse_error("0xH0000=1.5"), (11, AchievementParseErrorKind::UnexpectedEnd));
+        assert_eq!(parse_error("0xH0000=1(5."), (11, AchievementParseErrorKind::UnexpectedCharacter('.')));
+        assert_eq!(parse_error("0xZ0000=1"), (2, AchievementParseErrorKind::UnexpectedCharacter('Z')));
+        assert_eq!(parse_error("X:0xH0000=1"), (0, AchievementParseErrorKind::UnexpectedCharacter('X')));
+        assert_eq!(parse_error("0xH0000=1|"), (9, AchievementParseErrorKind::UnexpectedCharacter('|')));
+        assert_eq!(parse_error("d5=1"), (1, AchievementParseErrorKind::UnexpectedCharacter('5')));
+        assert_eq!(parse_error("0xH0000=99999999999"), (8, AchievementParseErrorKind::InvalidNumber));
+        assert_eq!(parse_error("K:0xH0000"), (0, AchievementParseErrorKind::Unsupported));
+        assert_eq!(parse_error("0xH0000=f1.5"), (8, AchievementParseErrorKind::Unsupported));
+    }
+
+    #[test]
+    fn memory_sizes() {
+        let memory = [0x34, 0x12, 0x56, 0x78];
+        let true_for = |string: &str| test(&mut parse(string), &memory).triggered;
+
+        assert!(true_for("0x0000=h1234"));
+        assert!(true_for("0xW0000=h561234"));
+        assert!(true_for("0xX0000=h78561234"));
+        assert!(true_for("0xI0000=h3412"));
+        assert!(true_for("0xJ0000=h341256"));
+        assert!(true_for("0xG0000=h34125678"));
+        assert!(true_for("0xN0001=1"));
+        assert!(true_for("0xO0001=0"));
+        assert!(true_for("0xL0000=4"));
+        assert!(true_for("0xU0000=3"));
+        assert!(true_for("0xK0003=4"));
+        assert!(true_for("b0xH0000=34"));
+        assert!(true_for("~0xH0000=h0CB"));
+        assert!(true_for("0xH0004=0"));
+        assert!(true_for("0xH10000=0"));
+    }
+
+    #[test]
+    fn delta_and_prior() {
+        assert_eq!(test_frames("0xH00>d0xH00", &[&[1], &[2], &[2], &[1]]), [true, true, false, false]);
+
+        // The prior value is the last value that differs from the current one.
+        let mut trigger = parse("p0xH00=1");
+        let priors: Vec<bool> = [1, 2, 2, 3].iter().map(|v| test(&mut trigger, &[*v]).triggered).collect();
+        assert_eq!(priors, [false, true, true, false]);
+    }
+
+    #[test]
+    fn hit_counts() {
+        let frames: [&[u8]; 5] = [&[1], &[0], &[1], &[1], &[0]];
+        assert_eq!(test_frames("0xH00=1.3.", &frames), [false, false, false, true, true]);
+
+        // AddHits adds to the hits of the next condition.
+        assert_eq!(test_frames("C:0xH00=1_0xH01=1.3.", &[&[1, 0], &[0, 1], &[1, 1]]), [false, false, true]);
+
+        // ResetNextIf only resets the next condition.
+        assert_eq!(test_frames("Z:0xH01=1_0xH00=1.2.", &[&[1, 0], &[1, 1], &[1, 0], &[1, 0]]), [false, false, false, true]);
+    }
+
+    #[test]
+    fn and_next_and_or_next() {
+        let frames: [&[u8]; 4] = [&[0, 0], &[1, 0], &[0, 1], &[1, 1]];
+        assert_eq!(test_frames("N:0xH00=1_0xH01=1", &frames), [false, false, false, true]);
+        assert_eq!(test_frames("O:0xH00=1_0xH01=1", &frames), [false, true, true, true]);
+
+        // Chains are evaluated left to right: (a || b) && c.
+        let frames: [&[u8]; 3] = [&[1, 0, 0], &[1, 0, 1], &[0, 1, 1]];
+        assert_eq!(test_frames("O:0xH00=1_N:0xH01=1_0xH02=1", &frames), [false, true, true]);
+
+        // Hits on the end of a chain only count when the whole chain is true.
+        let frames: [&[u8]; 3] = [&[1, 0], &[1, 1], &[1, 1]];
+        assert_eq!(test_frames("N:0xH00=1_0xH01=1.2.", &frames), [false, false, true]);
+    }
+
+    #[test]
+    fn add_source_and_add_address() {
+        assert_eq!(test_frames("A:0xH00_0xH01=5", &[&[2, 3], &[2, 2]]), [true, false]);
+        assert_eq!(test_frames("B:0xH00_0xH01=1", &[&[2, 3], &[2, 2]]), [true, false]);
+        assert_eq!(test_frames("A:0xH00*2_A:0xH01_0xH02=7", &[&[2, 1, 2], &[2, 1, 1]]), [true, false]);
+
+        // AddAddress offsets the address of the next condition by the pointer at $00.
+        let mut memory = [0u8; 0x20];
+        memory[0] = 2;
+        memory[0x12] = 7;
+        let mut trigger = parse("I:0xH00_0xH10=7");
+        assert!(test(&mut trigger, &memory).triggered);
+        memory[0] = 3;
+        assert!(!test(&mut trigger, &memory).triggered);
+
+        // The offset only applies to the next condition.
+        let mut trigger = parse("I:0xH00_0xH10=7_0xH10=0");
+        memory[0] = 2;
+        assert!(test(&mut trigger, &memory).triggered);
+    }
+
+    #[test]
+    fn alternate_groups() {
+        let frames: [&[u8]; 4] = [&[1, 0, 0], &[1, 1, 0], &[1, 0, 1], &[0, 1, 1]];
+        assert_eq!(test_frames("0xH00=1S0xH01=1S0xH02=1", &frames), [false, true, true, false]);
+        assert_eq!(test_frames("S0xH01=1S0xH02=1", &frames), [false, true, true, true]);
+    }
+
+    #[test]
+    fn pause_if_and_reset_if() {
+        // Hits are frozen while paused.
+        let mut trigger = parse("0xH00=1.3._P:0xH01=1");
+        let results: Vec<(bool, bool)> = [[1, 0], [1, 1], [1, 1], [1, 0], [1, 0]].iter().map(|memory| {
+            let result = test(&mut trigger, memory);
+            (result.triggered, result.paused)
+        }).collect();
+        assert_eq!(results, [(false, false), (false, true), (false, true), (false, false), (true, false)]);
+
+        // Resets clear the hits of every group.
+        let frames: [&[u8]; 5] = [&[1, 0], &[1, 1], &[1, 0], &[1, 0], &[1, 0]];
+        assert_eq!(test_frames("0xH00=1.2.S0xH00=1_R:0xH01=1", &frames), [false, false, false, true, true]);
+
+        // A paused group can't reset.
+        assert_eq!(test_frames("0xH00=1.2._R:0xH01=1_P:0xH02=1", &[&[1, 0, 0], &[1, 1, 1], &[1, 0, 0]]), [false, false, true]);
+    }
+
+    #[test]
+    fn states() {
+        let mut set = AchievementSet::new();
+        let id = set.add(parse("0xH00=1_P:0xH01=1"));
+        assert_eq!(set.get_state(id), Some(AchievementState::Waiting));
+
+        // Achievements that are true when added don't unlock until they were false.
+        assert_eq!(update(&mut set, &[1, 0]), 0);
+        assert_eq!(set.get_state(id), Some(AchievementState::Waiting));
+        assert_eq!(update(&mut set, &[0, 0]), 0);
+        assert_eq!(set.get_state(id), Some(AchievementState::Active));
+        assert_eq!(update(&mut set, &[1, 1]), 0);
+        assert_eq!(set.get_state(id), Some(AchievementState::Paused));
+
+        assert_eq!(update(&mut set, &[1, 0]), 1);
+        assert_eq!(set.get_triggered(), [id]);
+        assert_eq!(set.get_state(id), Some(AchievementState::Triggered));
+        assert_eq!(update(&mut set, &[1, 0]), 0);
+        assert!(set.get_triggered().is_empty());
+
+        assert!(set.reset(id));
+        assert_eq!(set.get_state(id), Some(AchievementState::Waiting));
+        assert!(set.remove(id));
+        assert!(!set.reset(id));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn measured_progress() {
+        let mut set = AchievementSet::new();
+        let value = set.add(parse("M:0xH00>=10"));
+        let hits = set.add(parse("M:0xH00=4.5."));
+        let measured_if = set.add(parse("M:0xH00>=10_Q:0xH01=1"));
+
+        update(&mut set, &[4, 0]);
+        assert_eq!(set.get_progress(value), Some((4, 10)));
+        assert_eq!(set.get_progress(hits), Some((1, 5)));
+        assert_eq!(set.get_progress(measured_if), Some((0, 10)));
+
+        update(&mut set, &[4, 1]);
+        assert_eq!(set.get_progress(hits), Some((2, 5)));
+        assert_eq!(set.get_progress(measured_if), Some((4, 10)));
+
+        update(&mut set, &[12, 1]);
+        assert_eq!(set.get_progress(value), Some((10, 10)));
+        assert_eq!(set.get_progress(hits), Some((2, 5)));
+    }
+
+    #[test]
+    fn reset_trigger() {
+        let mut trigger = parse("0xH00=1.2.");
+        test(&mut trigger, &[1]);
+        trigger.reset();
+        assert!(!test(&mut trigger, &[1]).triggered);
+        assert!(test(&mut trigger, &[1]).triggered);
+    }
+}
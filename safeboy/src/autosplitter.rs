@@ -0,0 +1,516 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use crate::{RamWatchType, RamWatchValue};
+
+/// Condition on a value in memory.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SplitCondition {
+    /// Address of the value (the first byte, for multi-byte values)
+    pub address: u16,
+
+    /// Only met if this bank is mapped to `address`
+    pub bank: Option<u16>,
+
+    /// How the value is stored
+    pub value_type: RamWatchType,
+
+    /// What the value is compared with
+    pub comparison: SplitComparison
+}
+
+impl SplitCondition {
+    /// Instantiate a condition with no bank.
+    pub fn new(address: u16, value_type: RamWatchType, comparison: SplitComparison) -> SplitCondition {
+        SplitCondition { address, bank: None, value_type, comparison }
+    }
+
+    /// Only meet the condition if `bank` is mapped to the address.
+    pub fn with_bank(mut self, bank: u16) -> SplitCondition {
+        self.bank = Some(bank);
+        self
+    }
+}
+
+/// Describes what a [`SplitCondition`] compares its value with.
+///
+/// Bits are compared as 0 or 1. Comparisons with the previous frame's value are never met on the
+/// first frame or if the bank was not mapped on the previous frame.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SplitComparison {
+    /// The value equals the given value.
+    Equal(i32),
+
+    /// The value does not equal the given value.
+    NotEqual(i32),
+
+    /// The value is less than the given value.
+    Less(i32),
+
+    /// The value is less than or equal to the given value.
+    LessOrEqual(i32),
+
+    /// The value is greater than the given value.
+    Greater(i32),
+
+    /// The value is greater than or equal to the given value.
+    GreaterOrEqual(i32),
+
+    /// The value is different from the previous frame.
+    Changed,
+
+    /// The value changed to the given value since the previous frame.
+    ChangedTo(i32),
+
+    /// The value is greater than on the previous frame.
+    Increased,
+
+    /// The value is less than on the previous frame.
+    Decreased
+}
+
+impl SplitComparison {
+    fn test(self, value: i32, previous: Option<i32>) -> bool {
+        match self {
+            SplitComparison::Equal(v) => value == v,
+            SplitComparison::NotEqual(v) => value != v,
+            SplitComparison::Less(v) => value < v,
+            SplitComparison::LessOrEqual(v) => value <= v,
+            SplitComparison::Greater(v) => value > v,
+            SplitComparison::GreaterOrEqual(v) => value >= v,
+            SplitComparison::Changed => previous.is_some_and(|p| value != p),
+            SplitComparison::ChangedTo(v) => value == v && previous.is_some_and(|p| p != v),
+            SplitComparison::Increased => previous.is_some_and(|p| value > p),
+            SplitComparison::Decreased => previous.is_some_and(|p| value < p)
+        }
+    }
+}
+
+/// State of an [`Autosplitter`]'s run.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum AutosplitterState {
+    /// Waiting for the start conditions to be met.
+    #[default]
+    NotRunning,
+
+    /// The timer is running.
+    Running,
+
+    /// The timer is paused by the pause conditions (e.g. during loading).
+    Paused,
+
+    /// Every split was reached, and the timer is stopped until the reset conditions are met.
+    Ended
+}
+
+/// Describes something that happened to an [`Autosplitter`]'s run.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AutosplitterEventKind {
+    /// The run started.
+    Start,
+
+    /// The split with the given index was reached.
+    Split(usize),
+
+    /// The run was reset.
+    Reset,
+
+    /// The timer was paused.
+    Pause,
+
+    /// The timer was resumed.
+    Resume
+}
+
+/// Event emitted by an [`Autosplitter`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AutosplitterEvent {
+    /// What happened
+    pub kind: AutosplitterEventKind,
+
+    /// Game time of the run when this happened, in ticks (see [`Autosplitter::get_game_ticks`])
+    pub ticks: u64,
+
+    /// Game time of the run when this happened
+    pub time: Duration
+}
+
+#[derive(Clone, Default)]
+struct ConditionList {
+    conditions: Vec<(SplitCondition, Option<i32>)>
+}
+
+impl ConditionList {
+    fn new(conditions: Vec<SplitCondition>) -> ConditionList {
+        ConditionList { conditions: conditions.into_iter().map(|c| (c, None)).collect() }
+    }
+
+    /// Evaluate all conditions, returning true if there is at least one and all are met.
+    fn update<R: Fn(u16, &mut [u8]), B: Fn(u16) -> Option<u16>>(&mut self, read: &R, get_bank: &B) -> bool {
+        let mut met = !self.conditions.is_empty();
+        for (condition, previous) in &mut self.conditions {
+            let value = if condition.bank.is_none_or(|bank| get_bank(condition.address) == Some(bank)) {
+                let mut bytes = [0u8; 4];
+                read(condition.address, &mut bytes[..condition.value_type.get_size()]);
+                Some(match condition.value_type.decode(&bytes) {
+                    RamWatchValue::Integer(i) => i,
+                    RamWatchValue::Bool(b) => b as i32
+                })
+            }
+            else {
+                None
+            };
+
+            met &= value.is_some_and(|v| condition.comparison.test(v, *previous));
+            *previous = value;
+        }
+        met
+    }
+}
+
+/// Times runs by evaluating conditions on memory at the end of every frame.
+///
+/// Set it with [`set_autosplitter`](crate::RunnableInstanceFunctions::set_autosplitter). Each
+/// event is passed to [`autosplitter_event`](crate::GameboyCallbacks::autosplitter_event).
+///
+/// A list of conditions is met when all of its conditions are met; an empty list is never met.
+/// The run starts when the start conditions are met, then each split is reached in order when its
+/// conditions are met. The timer is paused while the pause conditions are met, and the run is
+/// reset when the reset conditions are met.
+///
+/// Time is measured in emulated time, so it is not affected by the emulation speed or by frames
+/// that take too long to emulate. It is converted with the clock rate at the end of each frame (see
+/// [`get_clock_rate`](crate::RunnableInstanceFunctions::get_clock_rate)), so it follows the SGB's
+/// faster clock and the clock multiplier.
+///
+/// Splits and the pause conditions are also evaluated on the frame the run starts.
+#[derive(Clone, Default)]
+pub struct Autosplitter {
+    start: ConditionList,
+    reset: ConditionList,
+    pause: ConditionList,
+    splits: Vec<(String, ConditionList)>,
+    state: AutosplitterState,
+    current_split: usize,
+    game_ticks: u64,
+    game_time: Duration,
+    last_ticks: Option<u64>,
+    events: Vec<AutosplitterEvent>
+}
+
+impl Autosplitter {
+    /// Instantiate an autosplitter with no conditions or splits.
+    pub fn new() -> Autosplitter {
+        Autosplitter::default()
+    }
+
+    /// Set the conditions that start the run.
+    pub fn set_start_conditions(&mut self, conditions: Vec<SplitCondition>) {
+        self.start = ConditionList::new(conditions);
+    }
+
+    /// Set the conditions that reset the run.
+    pub fn set_reset_conditions(&mut self, conditions: Vec<SplitCondition>) {
+        self.reset = ConditionList::new(conditions);
+    }
+
+    /// Set the conditions that pause the timer.
+    pub fn set_pause_conditions(&mut self, conditions: Vec<SplitCondition>) {
+        self.pause = ConditionList::new(conditions);
+    }
+
+    /// Add a split to the end of the run, returning its index.
+    pub fn add_split(&mut self, name: &str, conditions: Vec<SplitCondition>) -> usize {
+        self.splits.push((String::from(name), ConditionList::new(conditions)));
+        self.splits.len() - 1
+    }
+
+    /// Remove all splits and reset the run.
+    pub fn clear_splits(&mut self) {
+        self.splits.clear();
+        self.reset();
+    }
+
+    /// Get the number of splits.
+    pub fn get_split_count(&self) -> usize {
+        self.splits.len()
+    }
+
+    /// Get the name of a split.
+    pub fn get_split_name(&self, index: usize) -> Option<&str> {
+        self.splits.get(index).map(|(name, _)| name.as_str())
+    }
+
+    /// Get the state of the run.
+    pub fn get_state(&self) -> AutosplitterState {
+        self.state
+    }
+
+    /// Get the index of the next split to be reached, or `None` if the run is not in progress.
+    pub fn get_current_split(&self) -> Option<usize> {
+        matches!(self.state, AutosplitterState::Running | AutosplitterState::Paused).then_some(self.current_split)
+    }
+
+    /// Get the game time of the run in ticks (see
+    /// [`get_elapsed_ticks`](crate::RunnableInstanceFunctions::get_elapsed_ticks)), excluding time
+    /// paused.
+    pub fn get_game_ticks(&self) -> u64 {
+        self.game_ticks
+    }
+
+    /// Get the game time of the run, excluding time paused.
+    pub fn get_game_time(&self) -> Duration {
+        self.game_time
+    }
+
+    /// Get the events from the end of the last frame.
+    pub fn get_events(&self) -> &[AutosplitterEvent] {
+        &self.events
+    }
+
+    /// Stop the run without emitting an event, waiting for the start conditions again.
+    pub fn reset(&mut self) {
+        self.state = AutosplitterState::NotRunning;
+        self.current_split = 0;
+        self.game_ticks = 0;
+        self.game_time = Duration::ZERO;
+    }
+
+    /// Evaluate all conditions, returning the number of events.
+    ///
+    /// `read` fills a buffer with bytes starting at an address, `get_bank` returns the bank
+    /// mapped to an address, `ticks` is the number of ticks elapsed since the instance was
+    /// created, and `clock_rate` is the current clock rate (ticks are at twice this rate).
+    pub(crate) fn update<R: Fn(u16, &mut [u8]), B: Fn(u16) -> Option<u16>>(&mut self, read: R, get_bank: B, ticks: u64, clock_rate: u32) -> usize {
+        self.events.clear();
+        let elapsed = self.last_ticks.map_or(0, |last| ticks.saturating_sub(last));
+        self.last_ticks = Some(ticks);
+
+        // Every list is evaluated so comparisons with the previous frame stay up to date.
+        let start = self.start.update(&read, &get_bank);
+        let reset = self.reset.update(&read, &get_bank);
+        let pause = self.pause.update(&read, &get_bank);
+        let mut split = false;
+        for (index, (_, conditions)) in self.splits.iter_mut().enumerate() {
+            let met = conditions.update(&read, &get_bank);
+            if index == self.current_split {
+                split = met;
+            }
+        }
+
+        match self.state {
+            AutosplitterState::NotRunning => {
+                if !start {
+                    return self.events.len()
+                }
+                self.reset();
+                self.state = AutosplitterState::Running;
+                self.push_event(AutosplitterEventKind::Start);
+            }
+            state => {
+                if state == AutosplitterState::Running {
+                    self.game_ticks += elapsed;
+                    self.game_time += Duration::from_nanos((elapsed as u128 * 1_000_000_000 / (clock_rate as u128 * 2)) as u64);
+                }
+
+                if reset {
+                    self.push_event(AutosplitterEventKind::Reset);
+                    self.reset();
+                    return self.events.len()
+                }
+
+                if state == AutosplitterState::Ended {
+                    return self.events.len()
+                }
+            }
+        }
+
+        if pause && self.state == AutosplitterState::Running {
+            self.state = AutosplitterState::Paused;
+            self.push_event(AutosplitterEventKind::Pause);
+        }
+        else if !pause && self.state == AutosplitterState::Paused {
+            self.state = AutosplitterState::Running;
+            self.push_event(AutosplitterEventKind::Resume);
+        }
+
+        if split {
+            self.push_event(AutosplitterEventKind::Split(self.current_split));
+            self.current_split += 1;
+            if self.current_split == self.splits.len() {
+                self.state = AutosplitterState::Ended;
+            }
+        }
+
+        self.events.len()
+    }
+
+    fn push_event(&mut self, kind: AutosplitterEventKind) {
+        self.events.push(AutosplitterEvent { kind, ticks: self.game_ticks, time: self.game_time });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// DMG clock rate, with ticks at 8 MiHz
+    const CLOCK_RATE: u32 = 4194304;
+    const SECOND: u64 = CLOCK_RATE as u64 * 2;
+
+    fn equal(address: u16, value: i32) -> SplitCondition {
+        SplitCondition::new(address, RamWatchType::U8, SplitComparison::Equal(value))
+    }
+
+    fn update(autosplitter: &mut Autosplitter, memory: &[u8], ticks: u64) -> Vec<AutosplitterEventKind> {
+        update_with_rate(autosplitter, memory, ticks, CLOCK_RATE)
+    }
+
+    fn update_with_rate(autosplitter: &mut Autosplitter, memory: &[u8], ticks: u64, clock_rate: u32) -> Vec<AutosplitterEventKind> {
+        let read = |address: u16, buffer: &mut [u8]| {
+            buffer.copy_from_slice(&memory[address as usize..address as usize + buffer.len()])
+        };
+        autosplitter.update(read, |_| None, ticks, clock_rate);
+        autosplitter.get_events().iter().map(|e| e.kind).collect()
+    }
+
+    /// Starts when `$C000` is 1, resets when `$C000` is 2, pauses while `$C001` is 1, and splits
+    /// when `$C002` changes to 1 and then to 2.
+    fn test_autosplitter() -> Autosplitter {
+        let mut autosplitter = Autosplitter::new();
+        autosplitter.set_start_conditions(vec![equal(0xC000, 1)]);
+        autosplitter.set_reset_conditions(vec![equal(0xC000, 2)]);
+        autosplitter.set_pause_conditions(vec![equal(0xC001, 1)]);
+        autosplitter.add_split("first", vec![SplitCondition::new(0xC002, RamWatchType::U8, SplitComparison::ChangedTo(1))]);
+        autosplitter.add_split("second", vec![equal(0xC002, 2)]);
+        autosplitter
+    }
+
+    #[test]
+    fn run() {
+        let mut autosplitter = test_autosplitter();
+        let mut memory = [0u8; 0x10000];
+        assert_eq!(update(&mut autosplitter, &memory, 0), []);
+        assert_eq!(autosplitter.get_state(), AutosplitterState::NotRunning);
+        assert_eq!(autosplitter.get_current_split(), None);
+
+        memory[0xC000] = 1;
+        assert_eq!(update(&mut autosplitter, &memory, 1000), [AutosplitterEventKind::Start]);
+        assert_eq!(autosplitter.get_events()[0].ticks, 0);
+        assert_eq!(autosplitter.get_current_split(), Some(0));
+
+        memory[0xC002] = 1;
+        assert_eq!(update(&mut autosplitter, &memory, 1000 + SECOND), [AutosplitterEventKind::Split(0)]);
+        assert_eq!(autosplitter.get_events()[0].ticks, SECOND);
+        assert_eq!(autosplitter.get_events()[0].time, Duration::from_secs(1));
+
+        memory[0xC002] = 2;
+        assert_eq!(update(&mut autosplitter, &memory, 1000 + SECOND * 2), [AutosplitterEventKind::Split(1)]);
+        assert_eq!(autosplitter.get_state(), AutosplitterState::Ended);
+        assert_eq!(autosplitter.get_current_split(), None);
+        assert_eq!(autosplitter.get_game_time(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn split_on_start_frame() {
+        let mut autosplitter = test_autosplitter();
+        let mut memory = [0u8; 0x10000];
+        update(&mut autosplitter, &memory, 0);
+
+        memory[0xC000] = 1;
+        memory[0xC002] = 1;
+        assert_eq!(update(&mut autosplitter, &memory, 1000), [AutosplitterEventKind::Start, AutosplitterEventKind::Split(0)]);
+        assert!(autosplitter.get_events().iter().all(|e| e.ticks == 0));
+        assert_eq!(autosplitter.get_current_split(), Some(1));
+
+        // The edge-triggered split was consumed, so it is not reached again.
+        assert_eq!(update(&mut autosplitter, &memory, 2000), []);
+    }
+
+    #[test]
+    fn reset_from_ended() {
+        let mut autosplitter = test_autosplitter();
+        let mut memory = [0u8; 0x10000];
+        update(&mut autosplitter, &memory, 0);
+        memory[0xC000] = 1;
+        update(&mut autosplitter, &memory, 0);
+        memory[0xC002] = 1;
+        update(&mut autosplitter, &memory, 100);
+        memory[0xC002] = 2;
+        update(&mut autosplitter, &memory, 200);
+        assert_eq!(autosplitter.get_state(), AutosplitterState::Ended);
+
+        // The timer is stopped, and the start conditions are ignored until a reset.
+        assert_eq!(update(&mut autosplitter, &memory, 1000), []);
+        assert_eq!(autosplitter.get_game_ticks(), 200);
+
+        memory[0xC000] = 2;
+        assert_eq!(update(&mut autosplitter, &memory, 1100), [AutosplitterEventKind::Reset]);
+        assert_eq!(autosplitter.get_events()[0].ticks, 200);
+        assert_eq!(autosplitter.get_state(), AutosplitterState::NotRunning);
+        assert_eq!(autosplitter.get_game_ticks(), 0);
+        assert_eq!(autosplitter.get_game_time(), Duration::ZERO);
+
+        memory[0xC000] = 1;
+        memory[0xC002] = 0;
+        assert_eq!(update(&mut autosplitter, &memory, 1200), [AutosplitterEventKind::Start]);
+        assert_eq!(autosplitter.get_current_split(), Some(0));
+    }
+
+    #[test]
+    fn paused_time_excluded() {
+        let mut autosplitter = test_autosplitter();
+        let mut memory = [0u8; 0x10000];
+        memory[0xC000] = 1;
+        update(&mut autosplitter, &memory, 0);
+        update(&mut autosplitter, &memory, 1000);
+
+        // Time until the pause conditions are met counts.
+        memory[0xC001] = 1;
+        assert_eq!(update(&mut autosplitter, &memory, 2000), [AutosplitterEventKind::Pause]);
+        assert_eq!(autosplitter.get_state(), AutosplitterState::Paused);
+        assert_eq!(autosplitter.get_events()[0].ticks, 2000);
+
+        assert_eq!(update(&mut autosplitter, &memory, 5000), []);
+        assert_eq!(autosplitter.get_game_ticks(), 2000);
+
+        memory[0xC001] = 0;
+        assert_eq!(update(&mut autosplitter, &memory, 6000), [AutosplitterEventKind::Resume]);
+        assert_eq!(autosplitter.get_events()[0].ticks, 2000);
+
+        update(&mut autosplitter, &memory, 7000);
+        assert_eq!(autosplitter.get_game_ticks(), 3000);
+
+        // Splits can still be reached while paused.
+        memory[0xC001] = 1;
+        memory[0xC002] = 1;
+        assert_eq!(update(&mut autosplitter, &memory, 8000), [AutosplitterEventKind::Pause, AutosplitterEventKind::Split(0)]);
+    }
+
+    #[test]
+    fn clock_rate() {
+        let mut autosplitter = test_autosplitter();
+        let mut memory = [0u8; 0x10000];
+        memory[0xC000] = 1;
+        update(&mut autosplitter, &memory, 0);
+
+        // SGB
+        let sgb_rate = 21477272 / 5;
+        update_with_rate(&mut autosplitter, &memory, sgb_rate as u64 * 2, sgb_rate);
+        assert_eq!(autosplitter.get_game_time(), Duration::from_secs(1));
+
+        // Double the clock rate with a clock multiplier
+        update_with_rate(&mut autosplitter, &memory, sgb_rate as u64 * 2 + SECOND * 2, CLOCK_RATE * 2);
+        assert_eq!(autosplitter.get_game_time(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn empty_conditions() {
+        let mut autosplitter = Autosplitter::new();
+        let memory = [0u8; 0x10000];
+        assert_eq!(update(&mut autosplitter, &memory, 0), []);
+        assert_eq!(update(&mut autosplitter, &memory, 1000), []);
+        assert_eq!(autosplitter.get_state(), AutosplitterState::NotRunning);
+    }
+}
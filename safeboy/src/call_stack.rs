@@ -0,0 +1,308 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::{Instruction, Interrupt, Mnemonic};
+
+/// Describes how a function was entered.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CallKind {
+    /// `CALL` instruction
+    Call,
+
+    /// `RST` instruction
+    Rst,
+
+    /// Interrupt dispatch
+    Interrupt(Interrupt)
+}
+
+/// Function call that has not returned yet.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CallFrame {
+    /// How the function was entered
+    pub kind: CallKind,
+
+    /// Address of the `CALL` or `RST` instruction, or of the instruction that was interrupted
+    pub call_address: u16,
+
+    /// Bank mapped to `call_address` at the time of the call, if it is banked
+    pub call_bank: Option<u16>,
+
+    /// Address of the function
+    pub target: u16,
+
+    /// Bank mapped to `target` at the time of the call, if it is banked
+    pub target_bank: Option<u16>,
+
+    /// Address execution returns to
+    pub return_address: u16,
+
+    /// Value of SP after the return address was pushed
+    pub sp: u16
+}
+
+/// Entry in a backtrace, from [`get_backtrace`](crate::RunnableInstanceFunctions::get_backtrace).
+#[derive(Clone, PartialEq, Debug)]
+pub struct BacktraceFrame {
+    /// Address being executed (for the innermost frame), or the address of the call
+    pub address: u16,
+
+    /// Bank mapped to `address`, if it is banked
+    pub bank: Option<u16>,
+
+    /// Description of `address` from the symbol table (see [`SymbolTable::describe`](crate::SymbolTable::describe))
+    pub name: Option<String>
+}
+
+/// Tracks function calls and interrupts by following `CALL`, `RST`, and interrupt dispatch.
+///
+/// Attach this with [`set_call_stack`](crate::RunnableInstanceFunctions::set_call_stack).
+///
+/// Frames are removed once SP moves above their return address, so returns (`RET`, `RETI`, or
+/// popping the return address manually) and stack resets are handled the same way.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CallStack {
+    frames: Vec<CallFrame>,
+    previous: Option<PreviousInstruction>,
+    pushed: usize
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct PreviousInstruction {
+    instruction: Instruction,
+    bank: Option<u16>,
+    sp: u16
+}
+
+impl CallStack {
+    /// Instantiate an empty call stack.
+    pub const fn new() -> CallStack {
+        CallStack { frames: Vec::new(), previous: None, pushed: 0 }
+    }
+
+    /// Get all frames, outermost first.
+    pub fn get_frames(&self) -> &[CallFrame] {
+        self.frames.as_slice()
+    }
+
+    /// Get the number of frames.
+    pub fn get_depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Remove all frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.previous = None;
+        self.pushed = 0;
+    }
+
+    /// Get the frames that were added by the last call to `step`.
+    pub(crate) fn get_new_frames(&self) -> &[CallFrame] {
+        &self.frames[self.frames.len() - self.pushed..]
+    }
+
+    /// Get the frames as they will be once the instruction at PC is executed.
+    ///
+    /// Calls and interrupts are only detected once the next instruction executes, so this includes
+    /// any that just happened.
+    pub(crate) fn preview(&self, instruction: Instruction, bank: Option<u16>, sp: u16, stack_top: u16, stack_top_bank: Option<u16>) -> Vec<CallFrame> {
+        let mut stack = self.clone();
+        if self.previous.is_some_and(|p| p.instruction.address != instruction.address) {
+            stack.step(instruction, bank, sp, stack_top, stack_top_bank);
+        }
+        stack.frames
+    }
+
+    /// Update the stack for the instruction about to be executed.
+    ///
+    /// `stack_top` is the word at SP, and `stack_top_bank` is the bank mapped to it. Returns the
+    /// interrupt that was just dispatched, if any.
+    pub(crate) fn step(&mut self, instruction: Instruction, bank: Option<u16>, sp: u16, stack_top: u16, stack_top_bank: Option<u16>) -> Option<Interrupt> {
+        let pc = instruction.address;
+        self.pushed = 0;
+        let interrupt = self.previous.and_then(|p| was_dispatched(&p.instruction, p.sp, pc, sp));
+
+        // If an interrupt was dispatched, the previous instruction finished where it returns to.
+        let (resolved_pc, resolved_bank, resolved_sp) = match interrupt {
+            Some(_) => (stack_top, stack_top_bank, sp.wrapping_add(2)),
+            None => (pc, bank, sp)
+        };
+
+        while self.frames.last().is_some_and(|f| f.sp < resolved_sp) {
+            self.frames.pop();
+        }
+
+        if let Some(previous) = self.previous {
+            let p = &previous.instruction;
+            let kind = match p.mnemonic {
+                Mnemonic::Call => Some(CallKind::Call),
+                Mnemonic::Rst => Some(CallKind::Rst),
+                _ => None
+            };
+            if let Some(kind) = kind
+                && p.branch_target == Some(resolved_pc)
+                && resolved_sp == previous.sp.wrapping_sub(2) {
+                self.frames.push(CallFrame {
+                    kind,
+                    call_address: p.address,
+                    call_bank: previous.bank,
+                    target: resolved_pc,
+                    target_bank: resolved_bank,
+                    return_address: p.next_address(),
+                    sp: resolved_sp
+                });
+                self.pushed += 1;
+            }
+        }
+
+        if let Some(interrupt) = interrupt {
+            self.frames.push(CallFrame {
+                kind: CallKind::Interrupt(interrupt),
+                call_address: stack_top,
+                call_bank: stack_top_bank,
+                target: pc,
+                target_bank: bank,
+                return_address: stack_top,
+                sp
+            });
+            self.pushed += 1;
+        }
+
+        self.previous = Some(PreviousInstruction { instruction, bank, sp });
+        interrupt
+    }
+}
+
+/// Get the interrupt that was dispatched between `previous` (executed with `previous_sp`) and
+/// executing `pc` with `sp`, if any.
+fn was_dispatched(previous: &Instruction, previous_sp: u16, pc: u16, sp: u16) -> Option<Interrupt> {
+    let interrupt = Interrupt::from_vector(pc)?;
+    if previous.next_address() == pc || previous.branch_target == Some(pc) {
+        return None
+    }
+
+    // Returns and JP HL could legitimately go anywhere, so use SP to tell if the return address was
+    // also pushed by an interrupt (e.g. a pending interrupt being serviced right after RETI).
+    let branch_sp = match previous.mnemonic {
+        Mnemonic::Ret | Mnemonic::Reti => previous_sp.wrapping_add(2),
+        Mnemonic::Jp if previous.branch_target.is_none() => previous_sp,
+        _ => return Some(interrupt)
+    };
+    (sp != branch_sp).then_some(interrupt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_instruction;
+
+    /// Step `stack` as if the instruction in `bytes` is about to be executed at `address`.
+    fn step(stack: &mut CallStack, bytes: &[u8], address: u16, sp: u16, stack_top: u16) -> Option<Interrupt> {
+        let instruction = decode_instruction(bytes, address).unwrap();
+        stack.step(instruction, None, sp, stack_top, None)
+    }
+
+    fn frame(kind: CallKind, call_address: u16, target: u16, return_address: u16, sp: u16) -> CallFrame {
+        CallFrame { kind, call_address, call_bank: None, target, target_bank: None, return_address, sp }
+    }
+
+    #[test]
+    fn call_and_ret() {
+        let mut stack = CallStack::new();
+        assert_eq!(step(&mut stack, &[0xCD, 0x00, 0x20], 0x0150, 0xFFFE, 0), None);
+        assert_eq!(stack.get_depth(), 0);
+
+        // RET at the target
+        assert_eq!(step(&mut stack, &[0xC9], 0x2000, 0xFFFC, 0x0153), None);
+        let call = frame(CallKind::Call, 0x0150, 0x2000, 0x0153, 0xFFFC);
+        assert_eq!(stack.get_frames(), [call]);
+        assert_eq!(stack.get_new_frames(), [call]);
+
+        assert_eq!(step(&mut stack, &[0x00], 0x0153, 0xFFFE, 0), None);
+        assert_eq!(stack.get_depth(), 0);
+        assert!(stack.get_new_frames().is_empty());
+    }
+
+    #[test]
+    fn conditional_call_not_taken() {
+        let mut stack = CallStack::new();
+        step(&mut stack, &[0xC4, 0x00, 0x20], 0x0150, 0xFFFE, 0);
+        step(&mut stack, &[0x00], 0x0153, 0xFFFE, 0);
+        assert_eq!(stack.get_depth(), 0);
+    }
+
+    #[test]
+    fn rst() {
+        let mut stack = CallStack::new();
+        step(&mut stack, &[0xFF], 0x0200, 0xFFFE, 0);
+        step(&mut stack, &[0xC9], 0x0038, 0xFFFC, 0x0201);
+        assert_eq!(stack.get_frames(), [frame(CallKind::Rst, 0x0200, 0x0038, 0x0201, 0xFFFC)]);
+
+        step(&mut stack, &[0x00], 0x0201, 0xFFFE, 0);
+        assert_eq!(stack.get_depth(), 0);
+    }
+
+    #[test]
+    fn interrupt() {
+        let mut stack = CallStack::new();
+        step(&mut stack, &[0x00], 0x0150, 0xFFFE, 0);
+        assert_eq!(step(&mut stack, &[0xD9], 0x0050, 0xFFFC, 0x0150), Some(Interrupt::Timer));
+        assert_eq!(stack.get_frames(), [frame(CallKind::Interrupt(Interrupt::Timer), 0x0150, 0x0050, 0x0150, 0xFFFC)]);
+
+        assert_eq!(step(&mut stack, &[0x00], 0x0150, 0xFFFE, 0), None);
+        assert_eq!(stack.get_depth(), 0);
+    }
+
+    #[test]
+    fn interrupt_after_call() {
+        let mut stack = CallStack::new();
+        step(&mut stack, &[0xCD, 0x00, 0x20], 0x0150, 0xFFFE, 0);
+
+        // The interrupt is dispatched before the first instruction of the function.
+        assert_eq!(step(&mut stack, &[0xD9], 0x0040, 0xFFFA, 0x2000), Some(Interrupt::VBlank));
+        assert_eq!(stack.get_frames(), [
+            frame(CallKind::Call, 0x0150, 0x2000, 0x0153, 0xFFFC),
+            frame(CallKind::Interrupt(Interrupt::VBlank), 0x2000, 0x0040, 0x2000, 0xFFFA)
+        ]);
+        assert_eq!(stack.get_new_frames().len(), 2);
+
+        step(&mut stack, &[0xC9], 0x2000, 0xFFFC, 0x0153);
+        assert_eq!(stack.get_frames(), [frame(CallKind::Call, 0x0150, 0x2000, 0x0153, 0xFFFC)]);
+    }
+
+    #[test]
+    fn interrupt_after_reti() {
+        let mut stack = CallStack::new();
+        step(&mut stack, &[0x00], 0x0150, 0xFFFE, 0);
+        step(&mut stack, &[0xD9], 0x0040, 0xFFFC, 0x0150);
+
+        // STAT was pending, so it is serviced as soon as the VBlank handler returns.
+        assert_eq!(step(&mut stack, &[0xD9], 0x0048, 0xFFFC, 0x0150), Some(Interrupt::Stat));
+        assert_eq!(stack.get_frames(), [frame(CallKind::Interrupt(Interrupt::Stat), 0x0150, 0x0048, 0x0150, 0xFFFC)]);
+        assert_eq!(stack.get_new_frames().len(), 1);
+
+        assert_eq!(step(&mut stack, &[0x00], 0x0150, 0xFFFE, 0), None);
+        assert_eq!(stack.get_depth(), 0);
+    }
+
+    #[test]
+    fn return_to_vector() {
+        // A plain return that happens to land on an interrupt vector
+        let mut stack = CallStack::new();
+        step(&mut stack, &[0xCD, 0x00, 0x20], 0x004D, 0xFFFE, 0);
+        step(&mut stack, &[0xC9], 0x2000, 0xFFFC, 0x0050);
+        assert_eq!(step(&mut stack, &[0x00], 0x0050, 0xFFFE, 0), None);
+        assert_eq!(stack.get_depth(), 0);
+    }
+
+    #[test]
+    fn jp_hl() {
+        let mut stack = CallStack::new();
+        step(&mut stack, &[0xE9], 0x0150, 0xFFFE, 0);
+        assert_eq!(step(&mut stack, &[0x00], 0x0060, 0xFFFE, 0), None);
+
+        step(&mut stack, &[0xE9], 0x0150, 0xFFFE, 0);
+        assert_eq!(step(&mut stack, &[0x00], 0x0060, 0xFFFC, 0x3000), Some(Interrupt::Joypad));
+        assert_eq!(stack.get_frames(), [frame(CallKind::Interrupt(Interrupt::Joypad), 0x3000, 0x0060, 0x3000, 0xFFFC)]);
+    }
+}
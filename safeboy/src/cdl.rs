@@ -0,0 +1,148 @@
+use alloc::vec::Vec;
+use core::ops::{BitOr, BitOrAssign};
+
+/// Set of flags logged for a ROM byte.
+///
+/// Flags can be combined with `|`, for example `CdlFlags::CODE | CdlFlags::DATA`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct CdlFlags(u8);
+
+impl CdlFlags {
+    /// The byte was never accessed.
+    pub const NONE: CdlFlags = CdlFlags(0);
+
+    /// The byte was executed as part of an instruction (opcode or operand).
+    pub const CODE: CdlFlags = CdlFlags(1 << 0);
+
+    /// The byte was read as data.
+    pub const DATA: CdlFlags = CdlFlags(1 << 1);
+
+    /// Returns true if all flags in `other` are also in `self`.
+    pub const fn contains(self, other: CdlFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Returns true if there are no flags.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Get the raw bits.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl BitOr for CdlFlags {
+    type Output = CdlFlags;
+
+    fn bitor(self, rhs: CdlFlags) -> Self::Output {
+        CdlFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for CdlFlags {
+    fn bitor_assign(&mut self, rhs: CdlFlags) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Records which bytes of the ROM were executed as code or read as data.
+///
+/// Attach this with [`set_code_data_logger`](crate::RunnableInstanceFunctions::set_code_data_logger).
+///
+/// Bytes are indexed by their offset in the ROM file (`bank * 0x4000 + (address & 0x3FFF)`), so
+/// the log does not depend on which bank was mapped where. Accesses to the boot ROM are not logged.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CodeDataLogger {
+    flags: Vec<u8>
+}
+
+impl CodeDataLogger {
+    /// Instantiate an empty log.
+    ///
+    /// The log is resized to the size of the ROM when it is attached.
+    pub const fn new() -> CodeDataLogger {
+        CodeDataLogger { flags: Vec::new() }
+    }
+
+    /// Load a log previously saved with [`CodeDataLogger::as_bytes`].
+    ///
+    /// The file contains one byte per ROM byte, where bit 0 is set if the byte was executed as
+    /// code, and bit 1 is set if it was read as data. This is the same layout as the CDL files
+    /// used by other emulators, so it can be passed to ROM disassemblers that accept them.
+    pub fn from_bytes(data: &[u8]) -> CodeDataLogger {
+        CodeDataLogger { flags: data.to_vec() }
+    }
+
+    /// Get the log in the format described in [`CodeDataLogger::from_bytes`].
+    pub fn as_bytes(&self) -> &[u8] {
+        self.flags.as_slice()
+    }
+
+    /// Get the number of ROM bytes covered by the log.
+    pub fn len(&self) -> usize {
+        self.flags.len()
+    }
+
+    /// Returns true if the log does not cover any bytes.
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    /// Unset all flags.
+    pub fn clear(&mut self) {
+        self.flags.fill(0);
+    }
+
+    /// Get the flags for the byte at `offset` in the ROM.
+    pub fn get_flags(&self, offset: usize) -> CdlFlags {
+        self.flags.get(offset).map(|f| CdlFlags(*f)).unwrap_or_default()
+    }
+
+    /// Get the flags for the byte at `address` in `bank`.
+    pub fn get_flags_for_address(&self, bank: u16, address: u16) -> CdlFlags {
+        self.get_flags(rom_offset(bank, address))
+    }
+
+    /// Get the number of bytes in `bank` (`0x4000` bytes) that have all of `flags` set.
+    ///
+    /// If `flags` is [`CdlFlags::NONE`], this is the number of bytes that were never accessed.
+    pub fn count_in_bank(&self, bank: u16, flags: CdlFlags) -> usize {
+        let start = rom_offset(bank, 0);
+        let end = (start + 0x4000).min(self.flags.len());
+        self.flags.get(start..end).unwrap_or_default().iter().filter(|f| matches(**f, flags)).count()
+    }
+
+    /// Get the number of bytes in the ROM that have all of `flags` set.
+    ///
+    /// If `flags` is [`CdlFlags::NONE`], this is the number of bytes that were never accessed.
+    pub fn count(&self, flags: CdlFlags) -> usize {
+        self.flags.iter().filter(|f| matches(**f, flags)).count()
+    }
+
+    /// Make sure the log covers a ROM of `size` bytes.
+    pub(crate) fn resize(&mut self, size: usize) {
+        if self.flags.len() < size {
+            self.flags.resize(size, 0);
+        }
+    }
+
+    pub(crate) fn log(&mut self, offset: usize, flags: CdlFlags) {
+        if let Some(f) = self.flags.get_mut(offset) {
+            *f |= flags.0;
+        }
+    }
+}
+
+/// Get the offset in the ROM file of `address` in `bank`.
+const fn rom_offset(bank: u16, address: u16) -> usize {
+    bank as usize * 0x4000 + (address & 0x3FFF) as usize
+}
+
+fn matches(flags: u8, wanted: CdlFlags) -> bool {
+    match wanted.is_empty() {
+        true => flags == 0,
+        false => CdlFlags(flags).contains(wanted)
+    }
+}
@@ -0,0 +1,330 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const CHEAT_LIST_MAGIC_LE: &[u8; 4] = b"hCBS";
+const CHEAT_LIST_MAGIC_BE: &[u8; 4] = b"SBCh";
+const CHEAT_LIST_HEADER_SIZE: usize = 8;
+const CHEAT_SIZE: usize = 136;
+const CHEAT_DESCRIPTION_SIZE: usize = 128;
+
+/// Describes a cheat that overrides a value read from memory.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cheat {
+    /// Description of the cheat
+    ///
+    /// This is truncated to 127 bytes when added.
+    pub description: String,
+
+    /// Address to override
+    pub address: u16,
+
+    /// Bank that must be mapped to `address` for the cheat to apply, or `None` for any bank
+    pub bank: Option<u16>,
+
+    /// Value to read instead
+    pub value: u8,
+
+    /// If set, the cheat only applies when the original value is this value
+    pub old_value: Option<u8>,
+
+    /// The cheat is applied
+    pub enabled: bool
+}
+
+/// Describes an error from parsing a cheat code.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CheatCodeError {
+    /// The code is not the length of a Game Genie (`ABC-DEF` or `ABC-DEF-GHI`) or GameShark
+    /// (`BBVVAAAA`) code.
+    InvalidLength,
+
+    /// The code contains a character that is not a hexadecimal digit (or a dash in a Game Genie
+    /// code).
+    InvalidCharacter(char),
+
+    /// The Game Genie code decodes to an address outside of ROM (`$0000`-`$7FFF`).
+    AddressOutOfRange(u16)
+}
+
+impl Cheat {
+    /// Parse a Game Genie or GameShark code.
+    ///
+    /// Eight digit codes without dashes are GameShark codes, and all other codes are Game Genie
+    /// codes.
+    pub fn from_code(code: &str, description: &str) -> Result<Cheat, CheatCodeError> {
+        let code = code.trim();
+        if code.len() == 8 && !code.contains('-') {
+            Cheat::from_gameshark(code, description)
+        }
+        else {
+            Cheat::from_game_genie(code, description)
+        }
+    }
+
+    /// Parse a Game Genie code (`ABC-DEF` or `ABC-DEF-GHI`; dashes are optional).
+    ///
+    /// Game Genie codes patch ROM in any bank. Nine digit codes only apply when the original value
+    /// matches.
+    pub fn from_game_genie(code: &str, description: &str) -> Result<Cheat, CheatCodeError> {
+        let mut digits = [0u8; 9];
+        let mut length = 0;
+        for c in code.trim().chars().filter(|c| *c != '-') {
+            let digit = parse_digit(c)?;
+            *digits.get_mut(length).ok_or(CheatCodeError::InvalidLength)? = digit;
+            length += 1;
+        }
+        if length != 6 && length != 9 {
+            return Err(CheatCodeError::InvalidLength)
+        }
+
+        let [a, b, c, d, e, f, g, _, i] = digits;
+        let value = (a << 4) | b;
+
+        // The address is FCDE, with F inverted.
+        let address = (((f ^ 0xF) as u16) << 12) | ((c as u16) << 8) | ((d as u16) << 4) | e as u16;
+        if address > 0x7FFF {
+            return Err(CheatCodeError::AddressOutOfRange(address))
+        }
+
+        // H is a checksum, and is ignored.
+        let old_value = (length == 9).then(|| ((g << 4) | i).rotate_right(2) ^ 0xBA);
+
+        Ok(Cheat { description: String::from(description), address, bank: None, value, old_value, enabled: true })
+    }
+
+    /// Parse a GameShark code (`BBVVAAAA`).
+    ///
+    /// `BB` is the RAM bank (`01` for any bank), `VV` is the value, and `AAAA` is the address in
+    /// little endian.
+    pub fn from_gameshark(code: &str, description: &str) -> Result<Cheat, CheatCodeError> {
+        let code = code.trim();
+        if code.chars().count() != 8 {
+            return Err(CheatCodeError::InvalidLength)
+        }
+
+        let mut digits = [0u8; 8];
+        for (digit, c) in digits.iter_mut().zip(code.chars()) {
+            *digit = parse_digit(c)?;
+        }
+        let byte = |i: usize| (digits[i] << 4) | digits[i + 1];
+
+        let bank = byte(0);
+        Ok(Cheat {
+            description: String::from(description),
+            address: u16::from_le_bytes([byte(4), byte(6)]),
+            bank: (bank != 1).then_some((bank & 0xF) as u16),
+            value: byte(2),
+            old_value: None,
+            enabled: true
+        })
+    }
+}
+
+/// Truncate a description to fit in SameBoy's cheat structure (including the null terminator)
+/// without splitting a character.
+pub(crate) fn truncate_description(description: &str) -> &str {
+    let mut length = description.len().min(CHEAT_DESCRIPTION_SIZE - 1);
+    while !description.is_char_boundary(length) {
+        length -= 1;
+    }
+    &description[..length]
+}
+
+fn parse_digit(c: char) -> Result<u8, CheatCodeError> {
+    c.to_digit(16).map(|d| d as u8).ok_or(CheatCodeError::InvalidCharacter(c))
+}
+
+/// List of cheats in SameBoy's cheat database format (`.cht`).
+///
+/// # Format
+///
+/// This is the format read and written by SameBoy's `GB_load_cheats` and `GB_save_cheats`.
+///
+/// | Offset | Size  | Description                                                       |
+/// |--------|-------|-------------------------------------------------------------------|
+/// | 0x00   | 4     | Magic (`hCBS` if little endian, `SBCh` if big endian)             |
+/// | 0x04   | 4     | Size of each cheat (`136`)                                        |
+/// | 0x08   | 136×N | Cheats                                                            |
+///
+/// Each cheat is laid out as follows, with integers in the endianness given by the magic.
+///
+/// | Offset | Size | Description                                                        |
+/// |--------|------|--------------------------------------------------------------------|
+/// | 0x00   | 2    | Address                                                            |
+/// | 0x02   | 2    | Bank, or `0xFFFF` for any bank                                     |
+/// | 0x04   | 1    | Value                                                              |
+/// | 0x05   | 1    | Old value                                                          |
+/// | 0x06   | 1    | `1` if the old value is used                                       |
+/// | 0x07   | 1    | `1` if the cheat is enabled                                        |
+/// | 0x08   | 128  | Description (UTF-8, null-terminated)                               |
+///
+/// Lists are always written in little endian.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CheatList {
+    /// Cheats, in order
+    pub cheats: Vec<Cheat>
+}
+
+impl CheatList {
+    /// Read a cheat list.
+    pub fn from_bytes(data: &[u8]) -> Result<CheatList, ReadCheatListError> {
+        let header = data.get(..CHEAT_LIST_HEADER_SIZE).ok_or(ReadCheatListError::Truncated)?;
+        let big_endian = match &header[0..4] {
+            m if m == CHEAT_LIST_MAGIC_LE => false,
+            m if m == CHEAT_LIST_MAGIC_BE => true,
+            _ => return Err(ReadCheatListError::BadMagic)
+        };
+        let u16_from = |bytes: [u8; 2]| if big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) };
+
+        let size_bytes: [u8; 4] = header[4..8].try_into().unwrap();
+        let cheat_size = if big_endian { u32::from_be_bytes(size_bytes) } else { u32::from_le_bytes(size_bytes) };
+        if cheat_size as usize != CHEAT_SIZE {
+            return Err(ReadCheatListError::UnsupportedCheatSize(cheat_size))
+        }
+
+        let data = &data[CHEAT_LIST_HEADER_SIZE..];
+        if !data.len().is_multiple_of(CHEAT_SIZE) {
+            return Err(ReadCheatListError::Truncated)
+        }
+
+        let cheats = data
+            .chunks_exact(CHEAT_SIZE)
+            .map(|cheat| {
+                let bank = u16_from([cheat[2], cheat[3]]);
+                let description = &cheat[8..];
+                let length = description.iter().position(|b| *b == 0).unwrap_or(description.len());
+                Cheat {
+                    description: String::from_utf8_lossy(&description[..length]).into_owned(),
+                    address: u16_from([cheat[0], cheat[1]]),
+                    bank: (bank != 0xFFFF).then_some(bank),
+                    value: cheat[4],
+                    old_value: (cheat[6] != 0).then_some(cheat[5]),
+                    enabled: cheat[7] != 0
+                }
+            })
+            .collect();
+
+        Ok(CheatList { cheats })
+    }
+
+    /// Write the cheat list.
+    ///
+    /// Descriptions are truncated to 127 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(CHEAT_LIST_HEADER_SIZE + self.cheats.len() * CHEAT_SIZE);
+        data.extend_from_slice(CHEAT_LIST_MAGIC_LE);
+        data.extend_from_slice(&(CHEAT_SIZE as u32).to_le_bytes());
+        for cheat in &self.cheats {
+            data.extend_from_slice(&cheat.address.to_le_bytes());
+            data.extend_from_slice(&cheat.bank.unwrap_or(0xFFFF).to_le_bytes());
+            data.push(cheat.value);
+            data.push(cheat.old_value.unwrap_or(0));
+            data.push(cheat.old_value.is_some() as u8);
+            data.push(cheat.enabled as u8);
+
+            let mut description = [0u8; CHEAT_DESCRIPTION_SIZE];
+            let bytes = truncate_description(&cheat.description).as_bytes();
+            description[..bytes.len()].copy_from_slice(bytes);
+            data.extend_from_slice(&description);
+        }
+        data
+    }
+}
+
+/// Describes an error from reading a cheat list.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReadCheatListError {
+    /// The data is not a cheat list.
+    BadMagic,
+
+    /// The cheat list was created with a version of SameBoy with a different cheat layout.
+    UnsupportedCheatSize(u32),
+
+    /// The cheat list ended unexpectedly.
+    Truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn decode(code: &str) -> Result<(u16, Option<u16>, u8, Option<u8>), CheatCodeError> {
+        Cheat::from_code(code, "").map(|c| (c.address, c.bank, c.value, c.old_value))
+    }
+
+    #[test]
+    fn game_genie_codes() {
+        assert_eq!(decode("00A-17B"), Ok((0x4A17, None, 0x00, None)));
+        assert_eq!(decode("00a17b"), Ok((0x4A17, None, 0x00, None)));
+        assert_eq!(decode("123-45A-B9C"), Ok((0x5345, None, 0x12, Some(0x95))));
+        assert_eq!(decode("FA0-1CF-E6A"), Ok((0x001C, None, 0xFA, Some(0x00))));
+
+        assert_eq!(decode("000-000"), Err(CheatCodeError::AddressOutOfRange(0xF000)));
+        assert_eq!(decode("00A-17"), Err(CheatCodeError::InvalidLength));
+        assert_eq!(decode("00A-17B-C4"), Err(CheatCodeError::InvalidLength));
+        assert_eq!(decode("00A-17B-C49-0"), Err(CheatCodeError::InvalidLength));
+        assert_eq!(decode("00A-17G"), Err(CheatCodeError::InvalidCharacter('G')));
+    }
+
+    #[test]
+    fn gameshark_codes() {
+        assert_eq!(decode("01098AC1"), Ok((0xC18A, None, 0x09, None)));
+        assert_eq!(decode("92FF34D2"), Ok((0xD234, Some(2), 0xFF, None)));
+        assert_eq!(Cheat::from_gameshark("01FF34D", ""), Err(CheatCodeError::InvalidLength));
+        assert_eq!(Cheat::from_gameshark("01FF34DX", ""), Err(CheatCodeError::InvalidCharacter('X')));
+    }
+
+    fn test_list() -> CheatList {
+        CheatList {
+            cheats: vec![
+                Cheat { description: String::from("Infinite lives"), address: 0xC134, bank: None, value: 0x09, old_value: None, enabled: true },
+                Cheat { description: String::new(), address: 0x4567, bank: Some(3), value: 0x00, old_value: Some(0xC9), enabled: false }
+            ]
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let list = test_list();
+        let data = list.to_bytes();
+        assert_eq!(data.len(), CHEAT_LIST_HEADER_SIZE + 2 * CHEAT_SIZE);
+        assert_eq!(&data[..8], b"hCBS\x88\x00\x00\x00");
+        assert_eq!(&data[8..16], [0x34, 0xC1, 0xFF, 0xFF, 0x09, 0x00, 0, 1]);
+        assert_eq!(CheatList::from_bytes(&data), Ok(list));
+        assert_eq!(CheatList::from_bytes(&CheatList::default().to_bytes()), Ok(CheatList::default()));
+    }
+
+    #[test]
+    fn big_endian() {
+        let mut data = Vec::from(*b"SBCh\x00\x00\x00\x88");
+        let mut cheat = [0u8; CHEAT_SIZE];
+        cheat[..8].copy_from_slice(&[0x45, 0x67, 0x00, 0x03, 0x00, 0xC9, 1, 0]);
+        data.extend_from_slice(&cheat);
+        assert_eq!(CheatList::from_bytes(&data), Ok(CheatList { cheats: vec![test_list().cheats[1].clone()] }));
+    }
+
+    #[test]
+    fn long_descriptions() {
+        // 126 ASCII bytes followed by a two byte character, which doesn't fit with the terminator.
+        let description = "x".repeat(126) + "é";
+        let list = CheatList { cheats: vec![Cheat { description, ..test_list().cheats[0].clone() }] };
+        let read = CheatList::from_bytes(&list.to_bytes()).unwrap();
+        assert_eq!(read.cheats[0].description, "x".repeat(126));
+    }
+
+    #[test]
+    fn read_errors() {
+        let data = test_list().to_bytes();
+        assert_eq!(CheatList::from_bytes(&data[..7]), Err(ReadCheatListError::Truncated));
+        assert_eq!(CheatList::from_bytes(&data[..data.len() - 1]), Err(ReadCheatListError::Truncated));
+
+        let mut bad_magic = data.clone();
+        bad_magic[..4].copy_from_slice(b"SBMV");
+        assert_eq!(CheatList::from_bytes(&bad_magic), Err(ReadCheatListError::BadMagic));
+
+        let mut bad_size = data.clone();
+        bad_size[4] = 0x80;
+        assert_eq!(CheatList::from_bytes(&bad_size), Err(ReadCheatListError::UnsupportedCheatSize(0x80)));
+    }
+}
@@ -0,0 +1,791 @@
+use core::fmt::{Display, Formatter};
+
+/// Decoded SM83 instruction.
+///
+/// Cycles are measured in T-cycles (4 MiHz ticks at single speed).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Instruction {
+    /// Address of the first byte of the instruction
+    pub address: u16,
+
+    /// Mnemonic
+    pub mnemonic: Mnemonic,
+
+    /// Operands, in the order they are written (destination first)
+    pub operands: [Option<Operand>; 2],
+
+    /// Length of the instruction in bytes
+    pub length: u8,
+
+    /// Cycles taken if the instruction does not branch (or always, for unconditional instructions)
+    pub cycles: u8,
+
+    /// Cycles taken if a conditional branch is taken
+    ///
+    /// This is `None` for instructions that are not conditional.
+    pub cycles_taken: Option<u8>,
+
+    /// Address execution continues at if the instruction branches, if known
+    ///
+    /// This is set for `JR`, `JP a16`, `CALL`, and `RST`, but not for `JP HL` or returns.
+    pub branch_target: Option<u16>
+}
+
+impl Instruction {
+    /// Get the address of the instruction following this one.
+    pub const fn next_address(&self) -> u16 {
+        self.address.wrapping_add(self.length as u16)
+    }
+
+    /// Returns true if this instruction only branches if a condition is met.
+    pub const fn is_conditional(&self) -> bool {
+        self.cycles_taken.is_some()
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.mnemonic == Mnemonic::Illegal {
+            let Some(Operand::Immediate8(opcode)) = self.operands[0] else {
+                unreachable!("illegal instructions always have their opcode as an operand")
+            };
+            return write!(f, ".BYTE ${opcode:02x}")
+        }
+
+        f.write_str(self.mnemonic.as_str())?;
+        let mut separator = " ";
+        for operand in self.operands.iter().flatten() {
+            write!(f, "{separator}{operand}")?;
+            separator = ", ";
+        }
+        Ok(())
+    }
+}
+
+/// Instruction mnemonic.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[allow(missing_docs)]
+pub enum Mnemonic {
+    Nop, Stop, Halt, Di, Ei,
+    Ld, Ldh, Push, Pop,
+    Inc, Dec, Add, Adc, Sub, Sbc, And, Xor, Or, Cp,
+    Rlca, Rrca, Rla, Rra, Daa, Cpl, Scf, Ccf,
+    Jr, Jp, Call, Ret, Reti, Rst,
+    Rlc, Rrc, Rl, Rr, Sla, Sra, Swap, Srl, Bit, Res, Set,
+
+    /// Opcode that does not correspond to an instruction (this locks up the CPU)
+    Illegal
+}
+
+impl Mnemonic {
+    /// Get the mnemonic as an uppercase string.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Mnemonic::Nop => "NOP",
+            Mnemonic::Stop => "STOP",
+            Mnemonic::Halt => "HALT",
+            Mnemonic::Di => "DI",
+            Mnemonic::Ei => "EI",
+            Mnemonic::Ld => "LD",
+            Mnemonic::Ldh => "LDH",
+            Mnemonic::Push => "PUSH",
+            Mnemonic::Pop => "POP",
+            Mnemonic::Inc => "INC",
+            Mnemonic::Dec => "DEC",
+            Mnemonic::Add => "ADD",
+            Mnemonic::Adc => "ADC",
+            Mnemonic::Sub => "SUB",
+            Mnemonic::Sbc => "SBC",
+            Mnemonic::And => "AND",
+            Mnemonic::Xor => "XOR",
+            Mnemonic::Or => "OR",
+            Mnemonic::Cp => "CP",
+            Mnemonic::Rlca => "RLCA",
+            Mnemonic::Rrca => "RRCA",
+            Mnemonic::Rla => "RLA",
+            Mnemonic::Rra => "RRA",
+            Mnemonic::Daa => "DAA",
+            Mnemonic::Cpl => "CPL",
+            Mnemonic::Scf => "SCF",
+            Mnemonic::Ccf => "CCF",
+            Mnemonic::Jr => "JR",
+            Mnemonic::Jp => "JP",
+            Mnemonic::Call => "CALL",
+            Mnemonic::Ret => "RET",
+            Mnemonic::Reti => "RETI",
+            Mnemonic::Rst => "RST",
+            Mnemonic::Rlc => "RLC",
+            Mnemonic::Rrc => "RRC",
+            Mnemonic::Rl => "RL",
+            Mnemonic::Rr => "RR",
+            Mnemonic::Sla => "SLA",
+            Mnemonic::Sra => "SRA",
+            Mnemonic::Swap => "SWAP",
+            Mnemonic::Srl => "SRL",
+            Mnemonic::Bit => "BIT",
+            Mnemonic::Res => "RES",
+            Mnemonic::Set => "SET",
+            Mnemonic::Illegal => ".BYTE"
+        }
+    }
+}
+
+impl Display for Mnemonic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 8-bit register operand.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[allow(missing_docs)]
+pub enum Register8 {
+    A, B, C, D, E, H, L
+}
+
+/// 16-bit register operand.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[allow(missing_docs)]
+pub enum Register16 {
+    AF, BC, DE, HL, SP
+}
+
+/// Branch condition.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Condition {
+    /// Zero flag is clear
+    NotZero,
+
+    /// Zero flag is set
+    Zero,
+
+    /// Carry flag is clear
+    NotCarry,
+
+    /// Carry flag is set
+    Carry
+}
+
+/// Instruction operand.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Operand {
+    /// 8-bit register
+    Register8(Register8),
+
+    /// 16-bit register
+    Register16(Register16),
+
+    /// Byte pointed to by a 16-bit register (e.g. `[hl]`)
+    Indirect(Register16),
+
+    /// Byte pointed to by HL, incrementing HL afterwards (`[hl+]`)
+    IndirectIncrement,
+
+    /// Byte pointed to by HL, decrementing HL afterwards (`[hl-]`)
+    IndirectDecrement,
+
+    /// Byte at an absolute address (e.g. `[$c000]`)
+    IndirectAddress(u16),
+
+    /// Byte at `$FF00` plus an immediate offset (`LDH`)
+    HighAddress(u8),
+
+    /// Byte at `$FF00` plus C (`[$ff00+c]`)
+    HighC,
+
+    /// 8-bit immediate value
+    Immediate8(u8),
+
+    /// 16-bit immediate value
+    Immediate16(u16),
+
+    /// Signed immediate offset (`ADD SP, e8`)
+    SignedImmediate(i8),
+
+    /// SP plus a signed immediate offset (`LD HL, SP+e8`)
+    StackOffset(i8),
+
+    /// Branch target address of `JR`, `JP`, or `CALL`
+    Address(u16),
+
+    /// Branch condition
+    Condition(Condition),
+
+    /// Bit index of `BIT`, `RES`, or `SET`
+    Bit(u8),
+
+    /// Target address of `RST`
+    RstVector(u8)
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            Operand::Register8(r) => f.write_str(match r {
+                Register8::A => "a",
+                Register8::B => "b",
+                Register8::C => "c",
+                Register8::D => "d",
+                Register8::E => "e",
+                Register8::H => "h",
+                Register8::L => "l"
+            }),
+            Operand::Register16(r) => f.write_str(register16_name(r)),
+            Operand::Indirect(r) => write!(f, "[{}]", register16_name(r)),
+            Operand::IndirectIncrement => f.write_str("[hl+]"),
+            Operand::IndirectDecrement => f.write_str("[hl-]"),
+            Operand::IndirectAddress(a) => write!(f, "[${a:04x}]"),
+            Operand::HighAddress(a) => write!(f, "[$ff{a:02x}]"),
+            Operand::HighC => f.write_str("[$ff00+c]"),
+            Operand::Immediate8(v) => write!(f, "${v:02x}"),
+            Operand::Immediate16(v) => write!(f, "${v:04x}"),
+            Operand::SignedImmediate(v) => write!(f, "{v}"),
+            Operand::StackOffset(v) => write!(f, "sp{v:+}"),
+            Operand::Address(a) => write!(f, "${a:04x}"),
+            Operand::Condition(c) => f.write_str(match c {
+                Condition::NotZero => "nz",
+                Condition::Zero => "z",
+                Condition::NotCarry => "nc",
+                Condition::Carry => "c"
+            }),
+            Operand::Bit(b) => write!(f, "{b}"),
+            Operand::RstVector(v) => write!(f, "${v:02x}")
+        }
+    }
+}
+
+const fn register16_name(register: Register16) -> &'static str {
+    match register {
+        Register16::AF => "af",
+        Register16::BC => "bc",
+        Register16::DE => "de",
+        Register16::HL => "hl",
+        Register16::SP => "sp"
+    }
+}
+
+/// Decode the instruction at the start of `bytes`, which is located at `address`.
+///
+/// Returns `None` if `bytes` is too short to contain the whole instruction.
+pub fn decode_instruction(bytes: &[u8], address: u16) -> Option<Instruction> {
+    let opcode = *bytes.first()?;
+    let imm8 = || bytes.get(1).copied();
+    let imm16 = || Some(u16::from_le_bytes([*bytes.get(1)?, *bytes.get(2)?]));
+
+    let mut instruction = Instruction {
+        address,
+        mnemonic: Mnemonic::Nop,
+        operands: [None, None],
+        length: 1,
+        cycles: 4,
+        cycles_taken: None,
+        branch_target: None
+    };
+
+    let x = opcode >> 6;
+    let y = (opcode >> 3) & 7;
+    let z = opcode & 7;
+
+    // Sets the mnemonic and operands, and optionally the length and cycles.
+    macro_rules! set {
+        ($mnemonic:ident) => {{
+            instruction.mnemonic = Mnemonic::$mnemonic;
+        }};
+        ($mnemonic:ident, $a:expr) => {{
+            instruction.mnemonic = Mnemonic::$mnemonic;
+            instruction.operands = [Some($a), None];
+        }};
+        ($mnemonic:ident, $a:expr, $b:expr) => {{
+            instruction.mnemonic = Mnemonic::$mnemonic;
+            instruction.operands = [Some($a), Some($b)];
+        }};
+    }
+
+    match (x, z) {
+        (0, 0) => match y {
+            0 => set!(Nop),
+            1 => {
+                set!(Ld, Operand::IndirectAddress(imm16()?), Operand::Register16(Register16::SP));
+                instruction.length = 3;
+                instruction.cycles = 20;
+            }
+            2 => {
+                // The second byte is ignored, but still part of the instruction.
+                imm8()?;
+                set!(Stop);
+                instruction.length = 2;
+            }
+            _ => {
+                let target = address.wrapping_add(2).wrapping_add_signed(imm8()? as i8 as i16);
+                instruction.length = 2;
+                instruction.branch_target = Some(target);
+                if y == 3 {
+                    set!(Jr, Operand::Address(target));
+                    instruction.cycles = 12;
+                }
+                else {
+                    set!(Jr, Operand::Condition(condition(y - 4)), Operand::Address(target));
+                    instruction.cycles = 8;
+                    instruction.cycles_taken = Some(12);
+                }
+            }
+        },
+        (0, 1) => {
+            let register = register16_sp(y >> 1);
+            if y & 1 == 0 {
+                set!(Ld, Operand::Register16(register), Operand::Immediate16(imm16()?));
+                instruction.length = 3;
+                instruction.cycles = 12;
+            }
+            else {
+                set!(Add, Operand::Register16(Register16::HL), Operand::Register16(register));
+                instruction.cycles = 8;
+            }
+        }
+        (0, 2) => {
+            let memory = match y >> 1 {
+                0 => Operand::Indirect(Register16::BC),
+                1 => Operand::Indirect(Register16::DE),
+                2 => Operand::IndirectIncrement,
+                _ => Operand::IndirectDecrement
+            };
+            let a = Operand::Register8(Register8::A);
+            if y & 1 == 0 {
+                set!(Ld, memory, a);
+            }
+            else {
+                set!(Ld, a, memory);
+            }
+            instruction.cycles = 8;
+        }
+        (0, 3) => {
+            let register = Operand::Register16(register16_sp(y >> 1));
+            if y & 1 == 0 {
+                set!(Inc, register);
+            }
+            else {
+                set!(Dec, register);
+            }
+            instruction.cycles = 8;
+        }
+        (0, 4) | (0, 5) => {
+            if z == 4 {
+                set!(Inc, operand8(y));
+            }
+            else {
+                set!(Dec, operand8(y));
+            }
+            instruction.cycles = if y == 6 { 12 } else { 4 };
+        }
+        (0, 6) => {
+            set!(Ld, operand8(y), Operand::Immediate8(imm8()?));
+            instruction.length = 2;
+            instruction.cycles = if y == 6 { 12 } else { 8 };
+        }
+        (0, _) => match y {
+            0 => set!(Rlca),
+            1 => set!(Rrca),
+            2 => set!(Rla),
+            3 => set!(Rra),
+            4 => set!(Daa),
+            5 => set!(Cpl),
+            6 => set!(Scf),
+            _ => set!(Ccf)
+        },
+        (1, _) => {
+            if y == 6 && z == 6 {
+                set!(Halt);
+            }
+            else {
+                set!(Ld, operand8(y), operand8(z));
+                instruction.cycles = if y == 6 || z == 6 { 8 } else { 4 };
+            }
+        }
+        (2, _) => {
+            set_alu(&mut instruction, y, operand8(z));
+            instruction.cycles = if z == 6 { 8 } else { 4 };
+        }
+        (_, 0) => match y {
+            0..=3 => {
+                set!(Ret, Operand::Condition(condition(y)));
+                instruction.cycles = 8;
+                instruction.cycles_taken = Some(20);
+            }
+            4 => {
+                set!(Ldh, Operand::HighAddress(imm8()?), Operand::Register8(Register8::A));
+                instruction.length = 2;
+                instruction.cycles = 12;
+            }
+            5 => {
+                set!(Add, Operand::Register16(Register16::SP), Operand::SignedImmediate(imm8()? as i8));
+                instruction.length = 2;
+                instruction.cycles = 16;
+            }
+            6 => {
+                set!(Ldh, Operand::Register8(Register8::A), Operand::HighAddress(imm8()?));
+                instruction.length = 2;
+                instruction.cycles = 12;
+            }
+            _ => {
+                set!(Ld, Operand::Register16(Register16::HL), Operand::StackOffset(imm8()? as i8));
+                instruction.length = 2;
+                instruction.cycles = 12;
+            }
+        },
+        (_, 1) => match y {
+            0 | 2 | 4 | 6 => {
+                set!(Pop, Operand::Register16(register16_af(y >> 1)));
+                instruction.cycles = 12;
+            }
+            1 => {
+                set!(Ret);
+                instruction.cycles = 16;
+            }
+            3 => {
+                set!(Reti);
+                instruction.cycles = 16;
+            }
+            5 => set!(Jp, Operand::Register16(Register16::HL)),
+            _ => {
+                set!(Ld, Operand::Register16(Register16::SP), Operand::Register16(Register16::HL));
+                instruction.cycles = 8;
+            }
+        },
+        (_, 2) => match y {
+            0..=3 => {
+                let target = imm16()?;
+                set!(Jp, Operand::Condition(condition(y)), Operand::Address(target));
+                instruction.length = 3;
+                instruction.cycles = 12;
+                instruction.cycles_taken = Some(16);
+                instruction.branch_target = Some(target);
+            }
+            4 => {
+                set!(Ldh, Operand::HighC, Operand::Register8(Register8::A));
+                instruction.cycles = 8;
+            }
+            5 => {
+                set!(Ld, Operand::IndirectAddress(imm16()?), Operand::Register8(Register8::A));
+                instruction.length = 3;
+                instruction.cycles = 16;
+            }
+            6 => {
+                set!(Ldh, Operand::Register8(Register8::A), Operand::HighC);
+                instruction.cycles = 8;
+            }
+            _ => {
+                set!(Ld, Operand::Register8(Register8::A), Operand::IndirectAddress(imm16()?));
+                instruction.length = 3;
+                instruction.cycles = 16;
+            }
+        },
+        (_, 3) => match y {
+            0 => {
+                let target = imm16()?;
+                set!(Jp, Operand::Address(target));
+                instruction.length = 3;
+                instruction.cycles = 16;
+                instruction.branch_target = Some(target);
+            }
+            1 => return decode_cb(imm8()?, address),
+            6 => set!(Di),
+            7 => set!(Ei),
+            _ => set!(Illegal, Operand::Immediate8(opcode))
+        },
+        (_, 4) => match y {
+            0..=3 => {
+                let target = imm16()?;
+                set!(Call, Operand::Condition(condition(y)), Operand::Address(target));
+                instruction.length = 3;
+                instruction.cycles = 12;
+                instruction.cycles_taken = Some(24);
+                instruction.branch_target = Some(target);
+            }
+            _ => set!(Illegal, Operand::Immediate8(opcode))
+        },
+        (_, 5) => match y {
+            0 | 2 | 4 | 6 => {
+                set!(Push, Operand::Register16(register16_af(y >> 1)));
+                instruction.cycles = 16;
+            }
+            1 => {
+                let target = imm16()?;
+                set!(Call, Operand::Address(target));
+                instruction.length = 3;
+                instruction.cycles = 24;
+                instruction.branch_target = Some(target);
+            }
+            _ => set!(Illegal, Operand::Immediate8(opcode))
+        },
+        (_, 6) => {
+            set_alu(&mut instruction, y, Operand::Immediate8(imm8()?));
+            instruction.length = 2;
+            instruction.cycles = 8;
+        }
+        _ => {
+            set!(Rst, Operand::RstVector(y * 8));
+            instruction.cycles = 16;
+            instruction.branch_target = Some(y as u16 * 8);
+        }
+    }
+
+    Some(instruction)
+}
+
+/// Decode all instructions in `bytes`, which starts at `address`.
+///
+/// Decoding stops once there are not enough bytes left for a whole instruction.
+pub fn disassemble(bytes: &[u8], address: u16) -> impl Iterator<Item = Instruction> + '_ {
+    let mut offset = 0usize;
+    core::iter::from_fn(move || {
+        let instruction = decode_instruction(bytes.get(offset..)?, address.wrapping_add(offset as u16))?;
+        offset += instruction.length as usize;
+        Some(instruction)
+    })
+}
+
+fn decode_cb(opcode: u8, address: u16) -> Option<Instruction> {
+    let y = (opcode >> 3) & 7;
+    let z = opcode & 7;
+    let target = operand8(z);
+    let memory = z == 6;
+
+    let (mnemonic, operands, cycles) = match opcode >> 6 {
+        0 => {
+            let mnemonic = match y {
+                0 => Mnemonic::Rlc,
+                1 => Mnemonic::Rrc,
+                2 => Mnemonic::Rl,
+                3 => Mnemonic::Rr,
+                4 => Mnemonic::Sla,
+                5 => Mnemonic::Sra,
+                6 => Mnemonic::Swap,
+                _ => Mnemonic::Srl
+            };
+            (mnemonic, [Some(target), None], if memory { 16 } else { 8 })
+        }
+        1 => (Mnemonic::Bit, [Some(Operand::Bit(y)), Some(target)], if memory { 12 } else { 8 }),
+        2 => (Mnemonic::Res, [Some(Operand::Bit(y)), Some(target)], if memory { 16 } else { 8 }),
+        _ => (Mnemonic::Set, [Some(Operand::Bit(y)), Some(target)], if memory { 16 } else { 8 })
+    };
+
+    Some(Instruction { address, mnemonic, operands, length: 2, cycles, cycles_taken: None, branch_target: None })
+}
+
+fn set_alu(instruction: &mut Instruction, operation: u8, operand: Operand) {
+    let a = Some(Operand::Register8(Register8::A));
+    let (mnemonic, operands) = match operation {
+        0 => (Mnemonic::Add, [a, Some(operand)]),
+        1 => (Mnemonic::Adc, [a, Some(operand)]),
+        2 => (Mnemonic::Sub, [Some(operand), None]),
+        3 => (Mnemonic::Sbc, [a, Some(operand)]),
+        4 => (Mnemonic::And, [Some(operand), None]),
+        5 => (Mnemonic::Xor, [Some(operand), None]),
+        6 => (Mnemonic::Or, [Some(operand), None]),
+        _ => (Mnemonic::Cp, [Some(operand), None])
+    };
+    instruction.mnemonic = mnemonic;
+    instruction.operands = operands;
+}
+
+const fn operand8(index: u8) -> Operand {
+    match index {
+        0 => Operand::Register8(Register8::B),
+        1 => Operand::Register8(Register8::C),
+        2 => Operand::Register8(Register8::D),
+        3 => Operand::Register8(Register8::E),
+        4 => Operand::Register8(Register8::H),
+        5 => Operand::Register8(Register8::L),
+        6 => Operand::Indirect(Register16::HL),
+        _ => Operand::Register8(Register8::A)
+    }
+}
+
+const fn condition(index: u8) -> Condition {
+    match index {
+        0 => Condition::NotZero,
+        1 => Condition::Zero,
+        2 => Condition::NotCarry,
+        _ => Condition::Carry
+    }
+}
+
+const fn register16_sp(index: u8) -> Register16 {
+    match index {
+        0 => Register16::BC,
+        1 => Register16::DE,
+        2 => Register16::HL,
+        _ => Register16::SP
+    }
+}
+
+const fn register16_af(index: u8) -> Register16 {
+    match index {
+        0 => Register16::BC,
+        1 => Register16::DE,
+        2 => Register16::HL,
+        _ => Register16::AF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    const ILLEGAL: [u8; 11] = [0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD];
+
+    const LENGTHS: [u8; 256] = [
+        1, 3, 1, 1, 1, 1, 2, 1, 3, 1, 1, 1, 1, 1, 2, 1,
+        2, 3, 1, 1, 1, 1, 2, 1, 2, 1, 1, 1, 1, 1, 2, 1,
+        2, 3, 1, 1, 1, 1, 2, 1, 2, 1, 1, 1, 1, 1, 2, 1,
+        2, 3, 1, 1, 1, 1, 2, 1, 2, 1, 1, 1, 1, 1, 2, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 3, 3, 3, 1, 2, 1, 1, 1, 3, 2, 3, 3, 2, 1,
+        1, 1, 3, 1, 3, 1, 2, 1, 1, 1, 3, 1, 3, 1, 2, 1,
+        2, 1, 1, 1, 1, 1, 2, 1, 2, 1, 3, 1, 1, 1, 2, 1,
+        2, 1, 1, 1, 1, 1, 2, 1, 2, 1, 3, 1, 1, 1, 2, 1
+    ];
+
+    // Cycles if not taken (CB is the cycles of `RLC B`, and illegal opcodes are 4).
+    const CYCLES: [u8; 256] = [
+         4, 12,  8,  8,  4,  4,  8,  4, 20,  8,  8,  8,  4,  4,  8,  4,
+         4, 12,  8,  8,  4,  4,  8,  4, 12,  8,  8,  8,  4,  4,  8,  4,
+         8, 12,  8,  8,  4,  4,  8,  4,  8,  8,  8,  8,  4,  4,  8,  4,
+         8, 12,  8,  8, 12, 12, 12,  4,  8,  8,  8,  8,  4,  4,  8,  4,
+         4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,
+         4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,
+         4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,
+         8,  8,  8,  8,  8,  8,  4,  8,  4,  4,  4,  4,  4,  4,  8,  4,
+         4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,
+         4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,
+         4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,
+         4,  4,  4,  4,  4,  4,  8,  4,  4,  4,  4,  4,  4,  4,  8,  4,
+         8, 12, 12, 16, 12, 16,  8, 16,  8, 16, 12,  8, 12, 24,  8, 16,
+         8, 12, 12,  4, 12, 16,  8, 16,  8, 16, 12,  4, 12,  4,  8, 16,
+        12, 12,  8,  4,  4, 16,  8, 16, 16,  4, 16,  4,  4,  4,  8, 16,
+        12, 12,  8,  4,  4, 16,  8, 16, 12,  8, 16,  4,  4,  4,  8, 16
+    ];
+
+    fn decode(bytes: &[u8]) -> Instruction {
+        decode_instruction(bytes, 0x0150).unwrap()
+    }
+
+    #[test]
+    fn base_opcodes() {
+        for opcode in 0..=255u8 {
+            let instruction = decode(&[opcode, 0, 0]);
+            assert_eq!(instruction.length, LENGTHS[opcode as usize], "length of {opcode:02x}");
+            assert_eq!(instruction.cycles, CYCLES[opcode as usize], "cycles of {opcode:02x}");
+            assert_eq!(instruction.mnemonic == Mnemonic::Illegal, ILLEGAL.contains(&opcode), "{opcode:02x}");
+        }
+    }
+
+    #[test]
+    fn cb_opcodes() {
+        for opcode in 0..=255u8 {
+            let instruction = decode(&[0xCB, opcode]);
+            let expected = match (opcode >> 6, opcode & 7) {
+                (1, 6) => 12,
+                (_, 6) => 16,
+                _ => 8
+            };
+            assert_eq!(instruction.length, 2, "length of cb {opcode:02x}");
+            assert_eq!(instruction.cycles, expected, "cycles of cb {opcode:02x}");
+            assert_eq!(instruction.cycles_taken, None);
+        }
+
+        assert_eq!(decode(&[0xCB, 0x37]).to_string(), "SWAP a");
+        assert_eq!(decode(&[0xCB, 0x7E]).to_string(), "BIT 7, [hl]");
+        assert_eq!(decode(&[0xCB, 0xC1]).to_string(), "SET 0, c");
+    }
+
+    #[test]
+    fn conditional_cycles() {
+        // (opcode, not taken, taken)
+        for (opcode, cycles, cycles_taken) in [(0x20, 8, 12), (0x38, 8, 12), (0xC0, 8, 20), (0xD8, 8, 20), (0xC2, 12, 16), (0xDA, 12, 16), (0xC4, 12, 24), (0xDC, 12, 24)] {
+            let instruction = decode(&[opcode, 0x00, 0x40]);
+            assert_eq!(instruction.cycles, cycles, "{opcode:02x}");
+            assert_eq!(instruction.cycles_taken, Some(cycles_taken), "{opcode:02x}");
+            assert!(instruction.is_conditional());
+        }
+
+        for opcode in [0x18, 0xC3, 0xC9, 0xCD, 0xD9, 0xE9, 0xFF] {
+            assert!(!decode(&[opcode, 0x00, 0x40]).is_conditional(), "{opcode:02x}");
+        }
+    }
+
+    #[test]
+    fn branch_targets() {
+        assert_eq!(decode(&[0x18, 0xFE]).branch_target, Some(0x0150));
+        assert_eq!(decode(&[0x18, 0x7F]).branch_target, Some(0x01D1));
+        assert_eq!(decode(&[0x20, 0x80]).branch_target, Some(0x00D2));
+        assert_eq!(decode_instruction(&[0x18, 0xFD], 0x0000).unwrap().branch_target, Some(0xFFFF));
+        assert_eq!(decode_instruction(&[0x18, 0x01], 0xFFFE).unwrap().branch_target, Some(0x0001));
+
+        assert_eq!(decode(&[0xC3, 0x34, 0x12]).branch_target, Some(0x1234));
+        assert_eq!(decode(&[0xCC, 0x34, 0x12]).branch_target, Some(0x1234));
+        assert_eq!(decode(&[0xEF]).branch_target, Some(0x0028));
+
+        assert_eq!(decode(&[0xE9]).branch_target, None);
+        assert_eq!(decode(&[0xC9]).branch_target, None);
+        assert_eq!(decode(&[0xD9]).branch_target, None);
+
+        assert_eq!(decode(&[0x20, 0xFE]).to_string(), "JR nz, $0150");
+        assert_eq!(decode(&[0xCD, 0x34, 0x12]).next_address(), 0x0153);
+    }
+
+    #[test]
+    fn illegal_opcodes() {
+        for opcode in ILLEGAL {
+            let instruction = decode(&[opcode]);
+            assert_eq!(instruction.mnemonic, Mnemonic::Illegal);
+            assert_eq!(instruction.operands, [Some(Operand::Immediate8(opcode)), None]);
+            assert_eq!(instruction.branch_target, None);
+            assert_eq!(instruction.to_string(), alloc::format!(".BYTE ${opcode:02x}"));
+        }
+    }
+
+    #[test]
+    fn short_input() {
+        assert_eq!(decode_instruction(&[], 0x0150), None);
+        assert_eq!(decode_instruction(&[0xCB], 0x0150), None);
+        for opcode in 0..=255u8 {
+            let length = LENGTHS[opcode as usize] as usize;
+            let bytes = [opcode, 0, 0];
+            assert_eq!(decode_instruction(&bytes[..length - 1], 0x0150), None, "{opcode:02x}");
+            assert!(decode_instruction(&bytes[..length], 0x0150).is_some(), "{opcode:02x}");
+        }
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(decode(&[0x08, 0x00, 0xC0]).to_string(), "LD [$c000], sp");
+        assert_eq!(decode(&[0x22]).to_string(), "LD [hl+], a");
+        assert_eq!(decode(&[0x3A]).to_string(), "LD a, [hl-]");
+        assert_eq!(decode(&[0xE0, 0x40]).to_string(), "LDH [$ff40], a");
+        assert_eq!(decode(&[0xF2]).to_string(), "LDH a, [$ff00+c]");
+        assert_eq!(decode(&[0xE8, 0xFE]).to_string(), "ADD sp, -2");
+        assert_eq!(decode(&[0xF8, 0x05]).to_string(), "LD hl, sp+5");
+        assert_eq!(decode(&[0xF5]).to_string(), "PUSH af");
+        assert_eq!(decode(&[0x76]).to_string(), "HALT");
+        assert_eq!(decode(&[0x96]).to_string(), "SUB [hl]");
+        assert_eq!(decode(&[0xCE, 0x12]).to_string(), "ADC a, $12");
+    }
+
+    #[test]
+    fn disassemble_stops_at_partial_instruction() {
+        let instructions: Vec<Instruction> = disassemble(&[0x00, 0x3E, 0x42, 0xCB, 0x11, 0xC3, 0x50], 0x0100).collect();
+        let addresses: Vec<u16> = instructions.iter().map(|i| i.address).collect();
+        assert_eq!(addresses, [0x0100, 0x0101, 0x0103]);
+        assert_eq!(instructions[2].mnemonic, Mnemonic::Rl);
+    }
+}
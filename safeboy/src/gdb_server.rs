@@ -0,0 +1,621 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use crate::{Gameboy, Registers, RunnableInstanceFunctions, StopReason, Watch, WatchId};
+
+/// Target description sent to the client.
+///
+/// Registers are sent as register pairs in this order, little endian.
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.gnu.gdb.sm83.cpu">
+    <reg name="af" bitsize="16" type="int" regnum="0"/>
+    <reg name="bc" bitsize="16" type="int"/>
+    <reg name="de" bitsize="16" type="int"/>
+    <reg name="hl" bitsize="16" type="int"/>
+    <reg name="sp" bitsize="16" type="data_ptr"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+  </feature>
+</target>
+"#;
+
+const REGISTER_COUNT: usize = 6;
+
+/// How often to check for an interrupt request from the client while running (about one frame).
+const INTERRUPT_CHECK_TICKS: u64 = 70224 * 2;
+
+/// Serves a [`Gameboy`] to GDB (or any client that speaks the GDB remote serial protocol) over
+/// TCP.
+///
+/// The registers `af`, `bc`, `de`, `hl`, `sp`, and `pc` are provided, and memory is accessed
+/// through the CPU's address space. Software and hardware breakpoints stop before the instruction
+/// at the address is executed, and watchpoints stop after the instruction that accessed memory.
+///
+/// For example, after calling [`GdbServer::serve`], connect with `target remote localhost:<port>`.
+pub struct GdbServer {
+    listener: TcpListener
+}
+
+impl GdbServer {
+    /// Listen on `address` (e.g. `"127.0.0.1:2159"`).
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<GdbServer> {
+        Ok(GdbServer { listener: TcpListener::bind(address)? })
+    }
+
+    /// Get the address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Wait for a client to connect, then serve `gameboy` until it detaches or disconnects.
+    ///
+    /// The emulator only runs when the client continues or steps. Any breakpoints and
+    /// watchpoints set by the client are removed before this returns.
+    pub fn serve(&self, gameboy: &mut Gameboy) -> io::Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_nodelay(true)?;
+
+        let mut session = Session {
+            stream,
+            no_ack: false,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new()
+        };
+        let result = session.serve(gameboy);
+        for id in session.watchpoints.into_values() {
+            gameboy.remove_watch(id);
+        }
+        result
+    }
+}
+
+/// Watchpoint kind, as in the `Z2`, `Z3`, and `Z4` packets.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum WatchKind {
+    Write,
+    Read,
+    Access
+}
+
+enum Action {
+    Reply(String),
+    Resume { step: bool, address: Option<u16> },
+    Detach
+}
+
+struct Session {
+    stream: TcpStream,
+    no_ack: bool,
+    breakpoints: BTreeSet<u16>,
+    watchpoints: BTreeMap<(WatchKind, u16, u16), WatchId>
+}
+
+impl Session {
+    fn serve(&mut self, gameboy: &mut Gameboy) -> io::Result<()> {
+        loop {
+            let Some(packet) = self.read_packet()? else { return Ok(()) };
+            match self.handle(gameboy, &packet) {
+                Action::Reply(reply) => self.write_packet(&reply)?,
+                Action::Resume { step, address } => {
+                    if let Some(address) = address {
+                        let mut registers = gameboy.get_registers();
+                        registers.pc = address;
+                        gameboy.set_registers(&registers);
+                    }
+                    let reply = self.resume(gameboy, step)?;
+                    self.write_packet(&reply)?;
+                }
+                Action::Detach => {
+                    self.write_packet("OK")?;
+                    return Ok(())
+                }
+            }
+        }
+    }
+
+    fn handle(&mut self, gameboy: &mut Gameboy, packet: &str) -> Action {
+        let reply = |s: &str| Action::Reply(String::from(s));
+        let (command, arguments) = packet.split_at(packet.chars().next().map(char::len_utf8).unwrap_or(0));
+
+        match command {
+            "?" => reply("S05"),
+            "g" => {
+                let registers = gameboy.get_registers();
+                Action::Reply(register_values(&registers).iter().map(|v| hex_u16(*v)).collect())
+            },
+            "G" => {
+                let mut values = [0u16; REGISTER_COUNT];
+                for (i, value) in values.iter_mut().enumerate() {
+                    match arguments.get(i * 4..i * 4 + 4).and_then(parse_hex_u16) {
+                        Some(v) => *value = v,
+                        None => return reply("E01")
+                    }
+                }
+                gameboy.set_registers(&registers_from_values(values));
+                reply("OK")
+            },
+            "p" => {
+                let registers = register_values(&gameboy.get_registers());
+                match parse_hex(arguments).and_then(|n| registers.get(n as usize)) {
+                    Some(value) => Action::Reply(hex_u16(*value)),
+                    None => reply("E01")
+                }
+            },
+            "P" => {
+                let Some((n, value)) = arguments.split_once('=') else { return reply("E01") };
+                let mut registers = register_values(&gameboy.get_registers());
+                match (parse_hex(n).and_then(|n| registers.get_mut(n as usize)), parse_hex_u16(value)) {
+                    (Some(register), Some(value)) => {
+                        *register = value;
+                        gameboy.set_registers(&registers_from_values(registers));
+                        reply("OK")
+                    },
+                    _ => reply("E01")
+                }
+            },
+            "m" => {
+                let Some((address, length)) = parse_range(arguments) else { return reply("E01") };
+                let mut bytes = alloc::vec![0u8; length];
+                gameboy.peek_range(address, &mut bytes);
+                Action::Reply(bytes.iter().map(|b| format!("{b:02x}")).collect())
+            },
+            "M" => {
+                let Some((range, data)) = arguments.split_once(':') else { return reply("E01") };
+                let Some((address, length)) = parse_range(range) else { return reply("E01") };
+                let Some(bytes) = parse_hex_bytes(data).filter(|b| b.len() == length) else { return reply("E01") };
+                for (i, byte) in bytes.into_iter().enumerate() {
+                    gameboy.write(address.wrapping_add(i as u16), byte);
+                }
+                reply("OK")
+            },
+            "Z" | "z" => self.handle_breakpoint(gameboy, command == "Z", arguments),
+            "c" | "s" => {
+                let address = match arguments {
+                    "" => None,
+                    a => match parse_hex(a).and_then(|a| u16::try_from(a).ok()) {
+                        Some(a) => Some(a),
+                        None => return reply("E01")
+                    }
+                };
+                Action::Resume { step: command == "s", address }
+            },
+            "v" => {
+                if arguments == "Cont?" {
+                    return reply("vCont;c;C;s;S")
+                }
+                // Only one thread exists, so use the first action.
+                match arguments.strip_prefix("Cont;").and_then(|a| a.chars().next()) {
+                    Some('c' | 'C') => Action::Resume { step: false, address: None },
+                    Some('s' | 'S') => Action::Resume { step: true, address: None },
+                    _ => reply("")
+                }
+            },
+            "q" => self.handle_query(arguments),
+            "Q" => match arguments {
+                "StartNoAckMode" => {
+                    self.no_ack = true;
+                    reply("OK")
+                },
+                _ => reply("")
+            },
+            "H" | "T" => reply("OK"),
+            "D" | "k" => Action::Detach,
+            _ => reply("")
+        }
+    }
+
+    fn handle_query(&self, query: &str) -> Action {
+        let reply = |s: &str| Action::Reply(String::from(s));
+        if query.starts_with("Supported") {
+            return reply("PacketSize=4000;qXfer:features:read+;swbreak+;hwbreak+;QStartNoAckMode+;vContSupported+")
+        }
+        if let Some(range) = query.strip_prefix("Xfer:features:read:target.xml:") {
+            let Some((offset, length)) = range.split_once(',').and_then(|(o, l)| Some((parse_hex(o)? as usize, parse_hex(l)? as usize))) else {
+                return reply("E01")
+            };
+            let data = TARGET_XML.as_bytes();
+            let start = offset.min(data.len());
+            let end = offset.saturating_add(length).min(data.len());
+            let prefix = if end < data.len() { 'm' } else { 'l' };
+            let mut output = String::from(prefix);
+            output.push_str(&String::from_utf8_lossy(&data[start..end]));
+            return Action::Reply(output)
+        }
+        match query {
+            "Attached" => reply("1"),
+            "C" => reply("QC1"),
+            "fThreadInfo" => reply("m1"),
+            "sThreadInfo" => reply("l"),
+            _ => reply("")
+        }
+    }
+
+    fn handle_breakpoint(&mut self, gameboy: &mut Gameboy, insert: bool, arguments: &str) -> Action {
+        let reply = |s: &str| Action::Reply(String::from(s));
+        let mut parts = arguments.split(',');
+        let (Some(kind), Some(address), Some(length)) = (parts.next(), parts.next(), parts.next()) else { return reply("E01") };
+        let (Some(address), Some(length)) = (parse_hex(address).and_then(|a| u16::try_from(a).ok()), parse_hex(length)) else {
+            return reply("E01")
+        };
+
+        let kind = match kind {
+            "0" | "1" => {
+                match insert {
+                    true => self.breakpoints.insert(address),
+                    false => self.breakpoints.remove(&address)
+                };
+                return reply("OK")
+            },
+            "2" => WatchKind::Write,
+            "3" => WatchKind::Read,
+            "4" => WatchKind::Access,
+            _ => return reply("")
+        };
+
+        let end = (address as u32).saturating_add(length.max(1) - 1).min(0xFFFF) as u16;
+        if insert {
+            let range = address..=end;
+            let watch = match kind {
+                WatchKind::Write => Watch::write(range),
+                WatchKind::Read => Watch::read(range),
+                WatchKind::Access => Watch::access(range)
+            };
+            let id = gameboy.add_watch(watch);
+            if let Some(old) = self.watchpoints.insert((kind, address, end), id) {
+                gameboy.remove_watch(old);
+            }
+        }
+        else if let Some(id) = self.watchpoints.remove(&(kind, address, end)) {
+            gameboy.remove_watch(id);
+        }
+        reply("OK")
+    }
+
+    fn resume(&mut self, gameboy: &mut Gameboy, step: bool) -> io::Result<String> {
+        if step {
+            gameboy.step_instruction();
+            return Ok(self.stop_reply(gameboy, StopReason::InstructionExecuted))
+        }
+
+        loop {
+            let mut interrupted = false;
+            let mut next_check = INTERRUPT_CHECK_TICKS;
+            let breakpoints = &self.breakpoints;
+            let stream = &mut self.stream;
+            let outcome = gameboy.run_with(|gb, ticks| {
+                if breakpoints.contains(&gb.get_registers().pc) {
+                    return Some(StopReason::PcReached)
+                }
+                if ticks >= next_check {
+                    next_check = ticks + INTERRUPT_CHECK_TICKS;
+                    if check_interrupt(stream) {
+                        interrupted = true;
+                        return Some(StopReason::ConditionMet)
+                    }
+                }
+                None
+            });
+
+            if interrupted {
+                return Ok(String::from("S02"))
+            }
+
+            // Ignore watches that weren't set by the client.
+            if outcome.reason == StopReason::WatchTriggered && self.find_watch_hit(gameboy).is_none() {
+                continue
+            }
+            return Ok(self.stop_reply(gameboy, outcome.reason))
+        }
+    }
+
+    fn find_watch_hit(&self, gameboy: &Gameboy) -> Option<(WatchKind, u16)> {
+        gameboy.get_watch_hits().iter().find_map(|hit| {
+            let (kind, _, _) = self.watchpoints.iter().find(|(_, id)| **id == hit.id)?.0;
+            Some((*kind, hit.address))
+        })
+    }
+
+    fn stop_reply(&self, gameboy: &Gameboy, reason: StopReason) -> String {
+        match reason {
+            StopReason::PcReached => String::from("T05swbreak:;"),
+            StopReason::WatchTriggered => match self.find_watch_hit(gameboy) {
+                Some((kind, address)) => {
+                    let name = match kind {
+                        WatchKind::Write => "watch",
+                        WatchKind::Read => "rwatch",
+                        WatchKind::Access => "awatch"
+                    };
+                    format!("T05{name}:{address:04x};")
+                },
+                None => String::from("S05")
+            },
+            _ => String::from("S05")
+        }
+    }
+
+    /// Read the next packet, acknowledging it. Returns `None` if the client disconnected.
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let Some(byte) = self.read_byte()? else { return Ok(None) };
+            match byte {
+                b'$' => (),
+                // Interrupt requests are only meaningful while running.
+                _ => continue
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    Some(b'#') => break,
+                    Some(b) => data.push(b),
+                    None => return Ok(None)
+                }
+            }
+            let (Some(high), Some(low)) = (self.read_byte()?, self.read_byte()?) else { return Ok(None) };
+
+            let checksum = data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+            let expected = parse_hex_bytes(core::str::from_utf8(&[high, low]).unwrap_or_default()).and_then(|b| b.first().copied());
+            if !self.no_ack {
+                let valid = expected == Some(checksum);
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+                if !valid {
+                    continue
+                }
+            }
+            return Ok(Some(String::from_utf8_lossy(&unescape(&data)).into_owned()))
+        }
+    }
+
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let escaped = escape(data.as_bytes());
+        let checksum = escaped.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        let mut packet = Vec::with_capacity(escaped.len() + 4);
+        packet.push(b'$');
+        packet.extend_from_slice(&escaped);
+        packet.extend_from_slice(format!("#{checksum:02x}").as_bytes());
+
+        loop {
+            self.stream.write_all(&packet)?;
+            if self.no_ack {
+                return Ok(())
+            }
+            // Resend until the client acknowledges the packet.
+            match self.read_byte()? {
+                Some(b'+') | None => return Ok(()),
+                _ => continue
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0u8];
+        loop {
+            match self.stream.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e)
+            }
+        }
+    }
+}
+
+/// Returns true if the client sent an interrupt request (`0x03`), without blocking.
+fn check_interrupt(stream: &mut TcpStream) -> bool {
+    if stream.set_nonblocking(true).is_err() {
+        return false
+    }
+    let mut byte = [0u8];
+    let interrupted = matches!(stream.read(&mut byte), Ok(1) if byte[0] == 0x03);
+    let _ = stream.set_nonblocking(false);
+    interrupted
+}
+
+fn register_values(registers: &Registers) -> [u16; REGISTER_COUNT] {
+    [registers.af, registers.bc, registers.de, registers.hl, registers.sp, registers.pc]
+}
+
+fn registers_from_values(values: [u16; REGISTER_COUNT]) -> Registers {
+    let [af, bc, de, hl, sp, pc] = values;
+    Registers { af, bc, de, hl, sp, pc }
+}
+
+/// Format a register as little endian hex.
+fn hex_u16(value: u16) -> String {
+    let mut output = String::new();
+    for byte in value.to_le_bytes() {
+        let _ = write!(output, "{byte:02x}");
+    }
+    output
+}
+
+/// Parse a little endian register value.
+fn parse_hex_u16(value: &str) -> Option<u16> {
+    match parse_hex_bytes(value)?.as_slice() {
+        [low, high] => Some(u16::from_le_bytes([*low, *high])),
+        _ => None
+    }
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value, 16).ok()
+}
+
+fn parse_hex_bytes(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None
+    }
+    (0..value.len()).step_by(2).map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// Parse `addr,length`, making sure it fits in the address space.
+fn parse_range(value: &str) -> Option<(u16, usize)> {
+    let (address, length) = value.split_once(',')?;
+    let (address, length) = (parse_hex(address)? as usize, parse_hex(length)? as usize);
+    (address + length <= 0x10000).then_some((address as u16, length))
+}
+
+fn escape(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len());
+    for byte in data {
+        match byte {
+            b'$' | b'#' | b'}' | b'*' => output.extend_from_slice(&[b'}', byte ^ 0x20]),
+            b => output.push(*b)
+        }
+    }
+    output
+}
+
+fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'}' => output.extend(bytes.next().map(|b| b ^ 0x20)),
+            b => output.push(*b)
+        }
+    }
+    output
+}
+
+#[cfg(all(test, feature = "gdb-server"))]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+    use crate::Model;
+
+    /// Minimal client that frames packets like GDB does.
+    struct Client {
+        stream: TcpStream,
+        no_ack: bool
+    }
+
+    impl Client {
+        fn send_raw(&mut self, data: &[u8]) {
+            self.stream.write_all(data).unwrap();
+        }
+
+        fn read_byte(&mut self) -> u8 {
+            let mut byte = [0u8];
+            self.stream.read_exact(&mut byte).unwrap();
+            byte[0]
+        }
+
+        fn send(&mut self, data: &str) {
+            let checksum = data.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+            self.send_raw(format!("${data}#{checksum:02x}").as_bytes());
+            if !self.no_ack {
+                assert_eq!(self.read_byte(), b'+');
+            }
+        }
+
+        fn receive(&mut self) -> String {
+            assert_eq!(self.read_byte(), b'$');
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte() {
+                    b'#' => break,
+                    b => data.push(b)
+                }
+            }
+            let checksum = [self.read_byte(), self.read_byte()];
+            let expected = data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+            assert_eq!(core::str::from_utf8(&checksum).unwrap(), format!("{expected:02x}"));
+            if !self.no_ack {
+                self.send_raw(b"+");
+            }
+            String::from_utf8(unescape(&data)).unwrap()
+        }
+
+        fn command(&mut self, data: &str) -> String {
+            self.send(data);
+            self.receive()
+        }
+    }
+
+    fn test_gameboy() -> Gameboy {
+        let mut boot_rom = [0u8; 0x100];
+        boot_rom[0xFC..].copy_from_slice(&[0x3E, 0x01, 0xE0, 0x50]); // ld a, $01; ldh [$50], a
+
+        let mut rom = alloc::vec![0u8; 0x8000];
+        rom[0x100..0x10B].copy_from_slice(&[
+            0x31, 0xFE, 0xFF, // $0100: ld sp, $FFFE
+            0x3E, 0x42,       // $0103: ld a, $42
+            0xEA, 0x00, 0xC0, // $0105: ld [$C000], a
+            0x00,             // $0108: nop
+            0x18, 0xFE        // $0109: jr $0109
+        ]);
+
+        let mut gameboy = Gameboy::new(Model::DmgB);
+        gameboy.load_boot_rom(&boot_rom);
+        gameboy.load_rom(&rom);
+        gameboy
+    }
+
+    #[test]
+    fn scripted_session() {
+        let server = GdbServer::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        let server_thread = thread::spawn(move || server.serve(&mut test_gameboy()));
+
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(30))).unwrap();
+        let mut client = Client { stream, no_ack: false };
+
+        // A bad checksum is rejected, then the packet is resent.
+        client.send_raw(b"$?#00");
+        assert_eq!(client.read_byte(), b'-');
+        assert_eq!(client.command("?"), "S05");
+        assert!(client.command("qSupported:swbreak+").contains("QStartNoAckMode+"));
+
+        // Run the boot ROM up to the breakpoint at the entry point.
+        assert_eq!(client.command("Z0,100,1"), "OK");
+        assert_eq!(client.command("c"), "T05swbreak:;");
+        assert_eq!(client.command("p5"), "0001");
+        assert_eq!(client.command("z0,100,1"), "OK");
+
+        assert_eq!(client.command("s"), "S05");
+        assert_eq!(client.command("p5"), "0301");
+        assert_eq!(client.command("p4"), "feff");
+
+        // Registers are little endian register pairs.
+        assert_eq!(client.command("P1=3412"), "OK");
+        assert_eq!(client.command("p1"), "3412");
+        let registers = client.command("g");
+        assert_eq!(registers.len(), REGISTER_COUNT * 4);
+        assert_eq!(&registers[4..8], "3412");
+        let registers = format!("{}7856{}", &registers[..8], &registers[12..]);
+        assert_eq!(client.command(&format!("G{registers}")), "OK");
+        assert_eq!(client.command("g"), registers);
+        assert_eq!(client.command("p2"), "7856");
+        assert_eq!(client.command("p6"), "E01");
+
+        assert_eq!(client.command("Mc000,2:abcd"), "OK");
+        assert_eq!(client.command("mc000,2"), "abcd");
+        assert_eq!(client.command("m0100,3"), "31feff");
+        assert_eq!(client.command("mffff,2"), "E01");
+
+        // A watchpoint covering the whole address space can be inserted and removed.
+        assert_eq!(client.command("Z2,0,10000"), "OK");
+        assert_eq!(client.command("z2,0,10000"), "OK");
+
+        // Watchpoints stop after the instruction that accessed memory.
+        assert_eq!(client.command("Z2,c000,1"), "OK");
+        assert_eq!(client.command("c"), "T05watch:c000;");
+        assert_eq!(client.command("p5"), "0801");
+        assert_eq!(client.command("z2,c000,1"), "OK");
+
+        assert_eq!(client.command("QStartNoAckMode"), "OK");
+        client.no_ack = true;
+        assert_eq!(client.command("mc000,1"), "42");
+
+        assert_eq!(client.command("D"), "OK");
+        server_thread.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+/// Describes how often a button with autofire is pressed while it is held.
+///
+/// Rates are measured in emulated frames, so they are unaffected by turbo or the clock
+/// multiplier.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AutofireRate {
+    /// Number of frames the button is pressed for
+    pub pressed_frames: u16,
+
+    /// Number of frames the button is released for
+    pub released_frames: u16
+}
+
+impl AutofireRate {
+    /// Press the button every other frame.
+    ///
+    /// This is the fastest rate that games can register.
+    pub const FASTEST: AutofireRate = AutofireRate::new(1, 1);
+
+    /// Instantiate a new rate.
+    pub const fn new(pressed_frames: u16, released_frames: u16) -> AutofireRate {
+        AutofireRate { pressed_frames, released_frames }
+    }
+
+    const fn period(self) -> u32 {
+        self.pressed_frames as u32 + self.released_frames as u32
+    }
+
+    const fn is_pressed(self, phase: u32) -> bool {
+        self.released_frames == 0 || phase < self.pressed_frames as u32
+    }
+}
+
+/// Scripted sequence of inputs.
+///
+/// Input from a macro is combined with the input set by the user. For example,
+/// `InputMacro::new().hold(1 << InputButton::Right, 30).tap(1 << InputButton::A)` holds right for
+/// 30 frames and then taps A.
+///
+/// Steps are measured in emulated frames, so macros behave the same regardless of turbo or the
+/// clock multiplier.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct InputMacro {
+    steps: Vec<InputMacroStep>
+}
+
+/// One step of an [`InputMacro`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct InputMacroStep {
+    /// Buttons to press (see [`InputButton`](crate::InputButton))
+    pub mask: u8,
+
+    /// Number of frames to press the buttons for
+    pub frames: u32
+}
+
+impl InputMacro {
+    /// Instantiate an empty macro.
+    pub const fn new() -> InputMacro {
+        InputMacro { steps: Vec::new() }
+    }
+
+    /// Press the buttons in `mask` for `frames` frames.
+    pub fn hold(mut self, mask: u8, frames: u32) -> InputMacro {
+        if frames > 0 {
+            self.steps.push(InputMacroStep { mask, frames });
+        }
+        self
+    }
+
+    /// Press the buttons in `mask` for one frame, then release them for one frame.
+    pub fn tap(self, mask: u8) -> InputMacro {
+        self.hold(mask, 1).wait(1)
+    }
+
+    /// Press nothing for `frames` frames.
+    pub fn wait(self, frames: u32) -> InputMacro {
+        self.hold(0, frames)
+    }
+
+    /// Get all steps.
+    pub fn get_steps(&self) -> &[InputMacroStep] {
+        self.steps.as_slice()
+    }
+
+    /// Get the total length in frames.
+    pub fn get_frame_count(&self) -> u64 {
+        self.steps.iter().map(|s| s.frames as u64).sum()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct InputScheduler {
+    autofire: [Option<AutofireRate>; 8],
+    autofire_phase: [u32; 8],
+    macros: VecDeque<InputMacro>,
+    step: usize,
+    step_frame: u32
+}
+
+impl InputScheduler {
+    pub(crate) fn set_autofire(&mut self, button: u8, rate: Option<AutofireRate>) {
+        self.autofire[button as usize] = rate;
+        self.autofire_phase[button as usize] = 0;
+    }
+
+    pub(crate) fn queue_macro(&mut self, input_macro: InputMacro) {
+        if !input_macro.steps.is_empty() {
+            self.macros.push_back(input_macro);
+        }
+    }
+
+    pub(crate) fn clear_macros(&mut self) {
+        self.macros.clear();
+        self.step = 0;
+        self.step_frame = 0;
+    }
+
+    pub(crate) fn is_macro_running(&self) -> bool {
+        !self.macros.is_empty()
+    }
+
+    /// Get the input mask to apply, given the input set by the user.
+    pub(crate) fn input_mask(&self, user_input: u8) -> u8 {
+        let mut mask = user_input;
+        for (button, rate) in self.autofire.iter().enumerate() {
+            if let Some(rate) = rate && !rate.is_pressed(self.autofire_phase[button]) {
+                mask &= !(1 << button);
+            }
+        }
+
+        let macro_mask = self.macros.front().map(|m| m.steps[self.step].mask).unwrap_or(0);
+        mask | macro_mask
+    }
+
+    /// Advance by one frame.
+    pub(crate) fn end_frame(&mut self, user_input: u8) {
+        for (button, rate) in self.autofire.iter().enumerate() {
+            let phase = &mut self.autofire_phase[button];
+            match rate {
+                Some(rate) if (user_input & (1 << button)) != 0 => *phase = (*phase + 1) % rate.period().max(1),
+                _ => *phase = 0
+            }
+        }
+
+        let Some(input_macro) = self.macros.front() else {
+            return
+        };
+        self.step_frame += 1;
+        if self.step_frame < input_macro.steps[self.step].frames {
+            return
+        }
+        self.step_frame = 0;
+        self.step += 1;
+        if self.step == input_macro.steps.len() {
+            self.step = 0;
+            self.macros.pop_front();
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Model;
+use crate::{Model, Movie, MovieError, MovieMode, MovieSnapshot, MovieStart};
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::pin::Pin;
@@ -43,11 +43,14 @@ impl Gameboy {
     /// # Remarks
     ///
     /// If turbo is disabled, this may sleep to maintain the correct frame rate.
+    ///
+    /// Any watches triggered during this step can be retrieved with
+    /// [`get_watch_hits`](crate::RunnableInstanceFunctions::get_watch_hits) afterwards.
     pub fn run(&mut self) -> u32 {
         self.do_with_inner_mut(|inner| inner.run())
     }
 
-    /// Runs until vblank is called.
+    /// Runs until vblank is called or a watch is triggered.
     ///
     /// Returns the number of nanoseconds passed since the last frame.
     ///
@@ -55,10 +58,119 @@ impl Gameboy {
     ///
     /// This will not run at a capped speed. It is essentially the same as turning on turbo mode for
     /// one frame.
+    ///
+    /// If a watch is triggered, this returns before vblank, at the end of the [`run`](Self::run)
+    /// step the watch was triggered in. The hits can be retrieved with
+    /// [`get_watch_hits`](crate::RunnableInstanceFunctions::get_watch_hits) afterwards.
     pub fn run_frame(&mut self) -> u64 {
         self.do_with_inner_mut(|inner| inner.run_frame())
     }
 
+    /// Run until exactly one instruction has been executed.
+    ///
+    /// Interrupt dispatch and halted cycles do not count as instructions, so this may run for more
+    /// than one step.
+    ///
+    /// # Remarks
+    ///
+    /// If the CPU does not execute an instruction for an entire frame (e.g. it is halted with no
+    /// interrupts enabled), this returns [`StopReason::Idle`].
+    pub fn step_instruction(&mut self) -> RunOutcome {
+        self.do_with_inner_mut(|inner| inner.set_counting_instructions(true));
+        let outcome = self.run_with(|gb, ticks| {
+            if gb.inner.instructions_executed > 0 {
+                Some(StopReason::InstructionExecuted)
+            }
+            else if ticks >= TICKS_PER_FRAME {
+                Some(StopReason::Idle)
+            }
+            else {
+                None
+            }
+        });
+        self.do_with_inner_mut(|inner| inner.set_counting_instructions(false));
+        outcome
+    }
+
+    /// Run for at least `ticks` 8 MiHz ticks (`8 388 608 Hz`).
+    ///
+    /// Emulation can only be stopped between steps, so this may run slightly longer than `ticks`.
+    pub fn run_cycles(&mut self, ticks: u64) -> RunOutcome {
+        self.run_with(|_, elapsed| (elapsed >= ticks).then_some(StopReason::CyclesElapsed))
+    }
+
+    /// Run until the next instruction to be executed is at `address`, or until at least
+    /// `max_ticks` 8 MiHz ticks have elapsed, if set.
+    ///
+    /// At least one step is always run, so this will not return immediately if PC is already at
+    /// `address`.
+    ///
+    /// # Remarks
+    ///
+    /// Without `max_ticks`, this does not return if `address` is never reached.
+    pub fn run_until_pc(&mut self, address: u16, max_ticks: Option<u64>) -> RunOutcome {
+        self.run_with_limit(max_ticks, |gb, _| (gb.get_registers().pc == address).then_some(StopReason::PcReached))
+    }
+
+    /// Run until LY changes to `line`, or until at least `max_ticks` 8 MiHz ticks have elapsed, if
+    /// set.
+    ///
+    /// If LY is already `line`, this runs until it is reached again (usually on the next frame).
+    ///
+    /// # Remarks
+    ///
+    /// Without `max_ticks`, this does not return if `line` is never reached (e.g. the LCD is
+    /// disabled).
+    ///
+    /// Panics if `line` is over 153, since LY never reaches it.
+    pub fn run_until_line(&mut self, line: u8, max_ticks: Option<u64>) -> RunOutcome {
+        assert!(line <= MAX_LY, "line {line} is over {MAX_LY}, so it is never reached");
+        let mut previous_line = self.get_ly();
+        self.run_with_limit(max_ticks, |gb, _| {
+            let current_line = gb.get_ly();
+            let reached = current_line == line && previous_line != line;
+            previous_line = current_line;
+            reached.then_some(StopReason::LineReached)
+        })
+    }
+
+    /// Run until `predicate` returns true, or until at least `max_ticks` 8 MiHz ticks have
+    /// elapsed, if set.
+    ///
+    /// The predicate is checked after every step.
+    pub fn run_until<F: FnMut(&Gameboy) -> bool>(&mut self, max_ticks: Option<u64>, mut predicate: F) -> RunOutcome {
+        self.run_with_limit(max_ticks, |gb, _| predicate(gb).then_some(StopReason::ConditionMet))
+    }
+
+    fn run_with_limit<F: FnMut(&Gameboy, u64) -> Option<StopReason>>(&mut self, max_ticks: Option<u64>, mut check: F) -> RunOutcome {
+        self.run_with(|gb, ticks| {
+            check(gb, ticks).or_else(|| max_ticks.is_some_and(|max| ticks >= max).then_some(StopReason::CyclesElapsed))
+        })
+    }
+
+    /// Run until `check` returns a reason to stop or a watch is triggered.
+    ///
+    /// Like [`run_frame`](Self::run_frame), this runs with turbo enabled.
+    pub(crate) fn run_with<F: FnMut(&Gameboy, u64) -> Option<StopReason>>(&mut self, mut check: F) -> RunOutcome {
+        self.do_with_inner_mut(|inner| inner.force_turbo_mode());
+        let mut ticks = 0;
+        let outcome = loop {
+            ticks += self.run() as u64;
+            if let Some(reason) = check(self, ticks) {
+                break RunOutcome { reason, ticks }
+            }
+            if !self.get_watch_hits().is_empty() {
+                break RunOutcome { reason: StopReason::WatchTriggered, ticks }
+            }
+        };
+        self.do_with_inner_mut(|inner| inner.restore_turbo_mode());
+        outcome
+    }
+
+    fn get_ly(&self) -> u8 {
+        self.peek(0xFF44)
+    }
+
     /// Set the model to `model` and reset the emulator.
     pub fn switch_model_and_reset(&mut self, model: Model) {
         self.do_with_inner_mut(|inner| inner.switch_model_and_reset(model));
@@ -86,6 +198,64 @@ impl Gameboy {
         self.do_with_inner_mut(|inner| inner.load_save_state(state))
     }
 
+    /// Start recording a movie.
+    ///
+    /// The emulator is set up according to `start` before recording begins. Any active movie is
+    /// discarded.
+    pub fn start_movie_recording(&mut self, start: MovieStart) -> Result<(), MovieError> {
+        self.do_with_inner_mut(|inner| {
+            let movie = Movie { rom_crc32: inner.get_rom_crc32(), rerecord_count: 0, start, frames: Vec::new() };
+            inner.start_movie(movie, MovieMode::ReadWrite, true)
+        })
+    }
+
+    /// Start playing back a movie.
+    ///
+    /// The emulator is set up according to the movie's starting condition before playback begins.
+    /// Any active movie is discarded.
+    ///
+    /// Returns `Err` if the movie was recorded with a different ROM or its save state could not be
+    /// loaded.
+    pub fn start_movie_playback(&mut self, movie: Movie, mode: MovieMode) -> Result<(), MovieError> {
+        self.do_with_inner_mut(|inner| inner.start_movie(movie, mode, false))
+    }
+
+    /// Stop the active movie, returning it.
+    pub fn stop_movie(&mut self) -> Option<Movie> {
+        self.do_with_inner_mut(|inner| inner.stop_movie())
+    }
+
+    /// Get the active movie.
+    pub fn get_movie(&self) -> Option<&Movie> {
+        self.inner.get_movie()
+    }
+
+    /// Set the mode of the active movie.
+    ///
+    /// Switching a movie that is being recorded to [`MovieMode::ReadOnly`] stops recording.
+    pub fn set_movie_mode(&mut self, mode: MovieMode) {
+        self.do_with_inner_mut(|inner| inner.set_movie_mode(mode))
+    }
+
+    /// Create a save state paired with the current frame of the active movie.
+    ///
+    /// Returns `None` if no movie is active.
+    ///
+    /// # Remarks
+    ///
+    /// Snapshots should be created between frames (e.g. after [`run_frame`](Self::run_frame)).
+    pub fn create_movie_snapshot(&self) -> Option<MovieSnapshot> {
+        self.inner.create_movie_snapshot()
+    }
+
+    /// Load a snapshot into the active movie.
+    ///
+    /// In [`MovieMode::ReadWrite`], the movie is truncated at the snapshot, recording resumes from
+    /// there, and the rerecord count is incremented.
+    pub fn load_movie_snapshot(&mut self, snapshot: &MovieSnapshot) -> Result<(), MovieError> {
+        self.do_with_inner_mut(|inner| inner.load_movie_snapshot(snapshot))
+    }
+
     /// Rewind the emulator one frame backwards.
     ///
     /// Returns false if the end of the buffer was reached.
@@ -114,5 +284,46 @@ pub enum ReadSaveStateError {
     Other
 }
 
+/// Number of 8 MiHz ticks in one frame.
+const TICKS_PER_FRAME: u64 = 70224 * 2;
+
+/// Last value of LY.
+const MAX_LY: u8 = 153;
+
+/// Describes why [`Gameboy::step_instruction`] or one of the `run_until` functions returned.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RunOutcome {
+    /// Reason emulation stopped
+    pub reason: StopReason,
+
+    /// Number of 8 MiHz ticks (`8 388 608 Hz`) emulated
+    pub ticks: u64
+}
+
+/// Reason emulation stopped.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum StopReason {
+    /// An instruction was executed.
+    InstructionExecuted,
+
+    /// No instruction was executed for an entire frame.
+    Idle,
+
+    /// The requested number of ticks elapsed (or the limit, for the `run_until` functions).
+    CyclesElapsed,
+
+    /// PC reached the requested address.
+    PcReached,
+
+    /// LY reached the requested line.
+    LineReached,
+
+    /// The predicate returned true.
+    ConditionMet,
+
+    /// A watch was triggered (see [`get_watch_hits`](RunnableInstanceFunctions::get_watch_hits)).
+    WatchTriggered
+}
+
 struct NullCallbacks;
 impl GameboyCallbacks for NullCallbacks {}
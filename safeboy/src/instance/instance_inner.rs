@@ -2,19 +2,36 @@ use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::cell::Cell;
 use core::ffi::{c_char, c_void, CStr};
 use core::marker::PhantomPinned;
 use core::mem::transmute;
-use core::ops::{Shl, ShlAssign};
-use sameboy_sys::{GB_alloc, GB_apu_set_sample_callback, GB_connect_printer, GB_dealloc, GB_gameboy_t, GB_get_clock_rate, GB_get_direct_access, GB_get_palette, GB_get_registers, GB_get_rom_title, GB_get_sample_rate, GB_get_save_state_size, GB_get_screen_height, GB_get_screen_width, GB_get_unmultiplied_clock_rate, GB_get_usual_frame_rate, GB_init, GB_is_background_rendering_disabled, GB_is_cgb, GB_is_cgb_in_cgb_mode, GB_is_hle_sgb, GB_is_object_rendering_disabled, GB_is_odd_frame, GB_is_sgb, GB_load_battery_from_buffer, GB_load_boot_rom_from_buffer, GB_load_rom_from_buffer, GB_load_state_from_buffer, GB_model_t, GB_palette_t, GB_palette_t_GB_color_s, GB_quick_reset, GB_reset, GB_rewind_pop, GB_rewind_reset, GB_run, GB_run_frame, GB_save_battery_size, GB_save_battery_to_buffer, GB_save_state_to_buffer, GB_set_allow_illegal_inputs, GB_set_background_rendering_disabled, GB_set_boot_rom_load_callback, GB_set_border_mode, GB_set_clock_multiplier, GB_set_color_correction_mode, GB_set_execution_callback, GB_set_infrared_callback, GB_set_input_callback, GB_set_key_mask, GB_set_key_state, GB_set_light_temperature, GB_set_log_callback, GB_set_object_rendering_disabled, GB_set_palette, GB_set_pixels_output, GB_set_read_memory_callback, GB_set_rendering_disabled, GB_set_rewind_length, GB_set_rgb_encode_callback, GB_set_rtc_mode, GB_set_rumble_callback, GB_set_sample_rate, GB_set_serial_transfer_bit_end_callback, GB_set_serial_transfer_bit_start_callback, GB_set_turbo_mode, GB_set_update_input_hint_callback, GB_set_user_data, GB_set_vblank_callback, GB_set_write_memory_callback, GB_switch_model_and_reset};
+use core::ops::{BitAnd, BitOr, BitOrAssign, Range, Shl, ShlAssign};
+use sameboy_sys::{GB_alloc, GB_apu_set_sample_callback, GB_clear_joyp_accessed, GB_connect_printer, GB_dealloc, GB_gameboy_t, GB_get_clock_rate, GB_get_direct_access, GB_get_joyp_accessed, GB_get_palette, GB_get_registers, GB_get_rom_crc32, GB_get_rom_title, GB_get_sample_rate, GB_get_save_state_size, GB_get_screen_height, GB_get_screen_width, GB_get_unmultiplied_clock_rate, GB_get_usual_frame_rate, GB_init, GB_is_background_rendering_disabled, GB_is_cgb, GB_is_cgb_in_cgb_mode, GB_is_hle_sgb, GB_is_object_rendering_disabled, GB_is_odd_frame, GB_is_sgb, GB_load_battery_from_buffer, GB_load_boot_rom_from_buffer, GB_load_rom_from_buffer, GB_load_state_from_buffer, GB_model_t, GB_palette_t, GB_palette_t_GB_color_s, GB_quick_reset, GB_read_memory, GB_reset, GB_rewind_pop, GB_rewind_reset, GB_run, GB_safe_read_memory, GB_save_battery_size, GB_save_battery_to_buffer, GB_save_state_to_buffer, GB_set_allow_illegal_inputs, GB_set_background_rendering_disabled, GB_set_boot_rom_load_callback, GB_set_border_mode, GB_set_clock_multiplier, GB_set_color_correction_mode, GB_set_execution_callback, GB_set_infrared_callback, GB_set_input_callback, GB_set_key_mask, GB_set_light_temperature, GB_set_log_callback, GB_set_object_rendering_disabled, GB_set_palette, GB_set_pixels_output, GB_set_read_memory_callback, GB_set_rendering_disabled, GB_set_rewind_length, GB_set_rgb_encode_callback, GB_set_rtc_mode, GB_set_rumble_callback, GB_set_sample_rate, GB_set_serial_transfer_bit_end_callback, GB_set_serial_transfer_bit_start_callback, GB_set_turbo_mode, GB_set_update_input_hint_callback, GB_set_user_data, GB_set_vblank_callback, GB_set_write_memory_callback, GB_switch_model_and_reset, GB_write_memory, safeboy_get_cycles_since_run, safeboy_get_ime, safeboy_is_boot_rom_mapped, safeboy_is_double_speed, safeboy_is_halted, safeboy_is_stopped, safeboy_set_ime};
 
 pub(crate) mod callback_wrapper;
 mod callbacks;
+mod cheats;
+
+#[cfg(feature = "cheat-search")]
+mod cheat_search;
+
+#[cfg(feature = "debugger")]
+mod debugger;
 use crate::instance::callback_wrapper::*;
+#[cfg(feature = "debugger")]
+use sameboy_sys::{safeboy_debugger_add_symbol, GB_cpu_disassemble, GB_debugger_clear_symbols, GB_debugger_set_reload_callback};
 use crate::instance::NullCallbacks;
 use crate::rgb_encoder::{encode_a8r8g8b8, RgbEncoder};
-use crate::{Gameboy, Model};
+use crate::input_scheduler::InputScheduler;
+use crate::watch::WatchManager;
+use crate::{decode_instruction, AchievementId, AchievementSet, AutofireRate, Autosplitter, AutosplitterEvent, BacktraceFrame, CallStack, CdlFlags, CodeDataLogger, FileOffset, Gameboy, InputMacro, Instruction, Interrupt, InterruptFlags, InterruptState, MappedAddress, MemoryMap, MemoryRegion, Model, Movie, MovieError, MovieMode, MovieReset, MovieSession, MovieSnapshot, MovieStart, MovieStatus, Profiler, RamWatchChange, RamWatchList, SymbolTable, TraceEntry, Tracer, Watch, WatchHit, WatchId};
 pub use callbacks::*;
+pub use cheats::*;
+#[cfg(feature = "cheat-search")]
+pub use cheat_search::*;
+#[cfg(feature = "debugger")]
+pub use debugger::*;
 
 /// Describes a running instance.
 ///
@@ -28,6 +45,31 @@ pub struct RunningGameboy {
     pub(crate) is_running: bool,
     pub(crate) rgb_encoder: RgbEncoder,
     rom_title: String,
+    lag_frame: bool,
+    lag_frame_count: u64,
+    input_mask: u8,
+    input_scheduler: InputScheduler,
+    movie: Option<MovieSession>,
+    movie_reset_pending: Option<MovieReset>,
+    pub(crate) memory_hooks: MemoryHooks,
+    pub(crate) peeking: Cell<bool>,
+    watches: WatchManager,
+    counting_instructions: bool,
+    pub(crate) instructions_executed: u64,
+    #[cfg(any(feature = "debugger", feature = "cheat-search"))]
+    pub(crate) log_capture: Option<String>,
+    callbacks_installed: bool,
+    symbols: Option<SymbolTable>,
+    elapsed_ticks: u64,
+    vblank_occurred: bool,
+    turbo_mode: TurboMode,
+    tracer: Option<Tracer>,
+    code_data_logger: Option<CodeDataLogger>,
+    call_stack: Option<CallStack>,
+    profiler: Option<Profiler>,
+    ram_watches: Option<RamWatchList>,
+    achievements: Option<AchievementSet>,
+    autosplitter: Option<Autosplitter>,
     _unpin: PhantomPinned
 }
 
@@ -45,6 +87,31 @@ impl RunningGameboy {
             rgb_encoder: encode_a8r8g8b8,
             is_running: false,
             rom_title: String::new(),
+            lag_frame: false,
+            lag_frame_count: 0,
+            input_mask: 0,
+            input_scheduler: InputScheduler::default(),
+            movie: None,
+            movie_reset_pending: None,
+            memory_hooks: MemoryHooks::NONE,
+            peeking: Cell::new(false),
+            watches: WatchManager::default(),
+            counting_instructions: false,
+            instructions_executed: 0,
+            #[cfg(any(feature = "debugger", feature = "cheat-search"))]
+            log_capture: None,
+            callbacks_installed: false,
+            symbols: None,
+            elapsed_ticks: 0,
+            vblank_occurred: false,
+            turbo_mode: TurboMode::Disabled,
+            tracer: None,
+            code_data_logger: None,
+            call_stack: None,
+            profiler: None,
+            ram_watches: None,
+            achievements: None,
+            autosplitter: None,
             _unpin: PhantomPinned
         };
         instance.reset_pixel_buffer();
@@ -78,32 +145,63 @@ impl RunningGameboy {
 
     pub(super) fn run(&mut self) -> u32 {
         self.assert_not_running();
+        self.watches.clear_hits();
         unsafe {
             self.is_running = true;
             let return_value = GB_run(self.gb);
             self.is_running = false;
+            self.elapsed_ticks += return_value as u64;
+            self.finish_movie_reset();
             return_value
         }
     }
 
     pub(super) fn run_frame(&mut self) -> u64 {
         self.assert_not_running();
+        self.watches.clear_hits();
+        // Turbo is enabled for the frame since the user handles FPS capping manually.
+        self.force_turbo_mode();
         unsafe {
             self.is_running = true;
-            let return_value = GB_run_frame(self.gb);
+            self.vblank_occurred = false;
+            let mut ticks = 0u64;
+            while !self.vblank_occurred && self.watches.get_hits().is_empty() {
+                let return_value = GB_run(self.gb) as u64;
+                self.elapsed_ticks += return_value;
+                ticks += return_value;
+            }
             self.is_running = false;
-            return_value
+            self.restore_turbo_mode();
+            self.finish_movie_reset();
+            ticks * 1_000_000_000 / 2 / GB_get_clock_rate(self.gb) as u64
         }
     }
 
+    /// Enable turbo so [`run`](Self::run) doesn't sleep, until [`restore_turbo_mode`](Self::restore_turbo_mode)
+    /// is called.
+    pub(super) fn force_turbo_mode(&mut self) {
+        unsafe { GB_set_turbo_mode(self.gb, true, true) }
+    }
+
+    /// Restore the turbo mode set by the user.
+    pub(super) fn restore_turbo_mode(&mut self) {
+        self.set_turbo_mode(self.turbo_mode);
+    }
+
     pub(super) fn reset(&mut self) {
         self.assert_not_running();
         unsafe { GB_reset(self.gb) };
+        if let Some(movie) = self.movie.as_mut() {
+            movie.on_reset(MovieReset::Hard);
+        }
     }
 
     pub(super) fn partial_reset(&mut self) {
         self.assert_not_running();
         unsafe { GB_quick_reset(self.gb) };
+        if let Some(movie) = self.movie.as_mut() {
+            movie.on_reset(MovieReset::Quick);
+        }
     }
 
     pub(super) fn rewind_pop(&mut self)-> bool {
@@ -142,6 +240,350 @@ impl RunningGameboy {
         }
     }
 
+    pub(super) fn start_movie(&mut self, movie: Movie, mode: MovieMode, recording: bool) -> Result<(), MovieError> {
+        self.assert_not_running();
+
+        let actual = self.get_rom_crc32();
+        if !recording && movie.rom_crc32 != actual {
+            return Err(MovieError::RomMismatch { expected: movie.rom_crc32, actual })
+        }
+
+        self.movie = None;
+        self.movie_reset_pending = None;
+        match &movie.start {
+            MovieStart::PowerOn { model, rtc_mode } => {
+                self.set_rtc_mode(*rtc_mode);
+                self.switch_model_and_reset(*model);
+            },
+            MovieStart::SaveState(state) => self.load_save_state(state).map_err(MovieError::SaveState)?
+        }
+
+        let session = MovieSession::new(movie, mode, recording);
+        if let Some(reset) = session.starts_with_reset() {
+            self.apply_movie_reset(reset);
+        }
+        self.movie = Some(session);
+        unsafe { GB_set_key_mask(self.gb, 0) };
+        Ok(())
+    }
+
+    pub(super) fn stop_movie(&mut self) -> Option<Movie> {
+        self.assert_not_running();
+        self.movie_reset_pending = None;
+        let movie = self.movie.take()?.into_movie();
+        self.update_key_mask();
+        Some(movie)
+    }
+
+    pub(super) fn get_movie(&self) -> Option<&Movie> {
+        self.movie.as_ref().map(MovieSession::movie)
+    }
+
+    pub(super) fn set_movie_mode(&mut self, mode: MovieMode) {
+        if let Some(movie) = self.movie.as_mut() {
+            movie.set_mode(mode);
+        }
+    }
+
+    pub(super) fn create_movie_snapshot(&self) -> Option<MovieSnapshot> {
+        let frame = self.movie.as_ref()?.frame();
+        Some(MovieSnapshot { frame, state: self.create_save_state() })
+    }
+
+    pub(super) fn load_movie_snapshot(&mut self, snapshot: &MovieSnapshot) -> Result<(), MovieError> {
+        self.movie.as_ref().ok_or(MovieError::NoMovie)?.check_snapshot(snapshot)?;
+        self.load_save_state(&snapshot.state).map_err(MovieError::SaveState)?;
+        self.movie_reset_pending = None;
+        self.movie.as_mut().expect("checked above").seek(snapshot.frame);
+        Ok(())
+    }
+
+    pub(crate) fn latch_movie_input(&mut self) {
+        let Some(movie) = self.movie.as_mut() else {
+            return
+        };
+        if let Some(mask) = movie.latch_input(self.input_scheduler.input_mask(self.input_mask)) {
+            unsafe { GB_set_key_mask(self.gb, mask as _) };
+        }
+    }
+
+    pub(crate) fn end_movie_frame(&mut self) {
+        if let Some(reset) = self.movie.as_mut().and_then(MovieSession::end_frame) {
+            self.movie_reset_pending = Some(reset);
+        }
+    }
+
+    pub(crate) fn end_input_frame(&mut self) {
+        self.input_scheduler.end_frame(self.input_mask);
+        self.update_key_mask();
+    }
+
+    /// Check an access against all watches, returning the indices of any new hits.
+    pub(crate) fn check_watches(&mut self, access: MemoryHooks, address: u16, value: u8) -> Range<usize> {
+        if !self.watches.is_watched(access, address) {
+            return 0..0
+        }
+        let bank = self.get_bank_for_address(address);
+        let old_value = self.peek(address);
+        self.watches.check(access, address, bank, value, old_value)
+    }
+
+    /// Log the instruction about to be executed, if logging code/data accesses.
+    pub(crate) fn log_code(&mut self, pc: u16, opcode: u8) {
+        if self.code_data_logger.is_none() {
+            return
+        }
+
+        // Operands don't affect the length of an instruction.
+        let length = decode_instruction(&[opcode, 0, 0], pc).map(|i| i.length).unwrap_or(1);
+        for i in 0..length {
+            self.log_rom_access(pc.wrapping_add(i as u16), CdlFlags::CODE);
+        }
+    }
+
+    /// Log a data read, if logging code/data accesses.
+    pub(crate) fn log_data(&mut self, address: u16) {
+        if self.code_data_logger.is_none() || address >= 0x8000 {
+            return
+        }
+
+        // Instruction fetches are logged as code once the opcode is executed. PC is always
+        // incremented past the byte being fetched before it is read.
+        if self.get_registers().pc == address.wrapping_add(1) {
+            return
+        }
+        self.log_rom_access(address, CdlFlags::DATA);
+    }
+
+    fn log_rom_access(&mut self, address: u16, flags: CdlFlags) {
+        let Some(offset) = self.get_rom_offset(address) else { return };
+        let rom_size = unsafe { direct_access(self.gb, DirectAccessRegion::ROM) }.data.len();
+        if let Some(logger) = self.code_data_logger.as_mut() {
+            logger.resize(rom_size);
+            logger.log(offset, flags);
+        }
+    }
+
+    /// Get the offset in the ROM file currently mapped to `address`, if it is mapped to ROM.
+    pub(crate) fn get_rom_offset(&self, address: u16) -> Option<usize> {
+        if address >= 0x8000 || unsafe { safeboy_is_boot_rom_mapped(self.gb, address) } {
+            return None
+        }
+        let rom_size = unsafe { direct_access(self.gb, DirectAccessRegion::ROM) }.data.len();
+        if rom_size == 0 {
+            return None
+        }
+        let region = MemoryRegion::from_address(address);
+        let mapped = MappedAddress { region, bank: self.get_region_bank(region), offset: address - region.get_start_address() };
+        let Some(FileOffset::Rom(offset)) = mapped.get_file_offset() else {
+            return None
+        };
+        Some(offset % rom_size)
+    }
+
+    /// Update the call stack for the instruction about to be executed, if tracking calls.
+    ///
+    /// Returns the interrupt that was just dispatched and its return address, if any.
+    pub(crate) fn track_call_stack(&mut self, pc: u16) -> Option<(Interrupt, u16)> {
+        let (instruction, bank, sp, stack_top, stack_top_bank) = match self.call_stack {
+            Some(_) => self.get_call_stack_state(pc),
+            None => return None
+        };
+        let interrupt = self.call_stack.as_mut()?.step(instruction, bank, sp, stack_top, stack_top_bank)?;
+        Some((interrupt, stack_top))
+    }
+
+    /// Attribute time to the function being executed, if profiling.
+    pub(crate) fn profile_instruction(&mut self, pc: u16) {
+        if self.profiler.is_none() {
+            return
+        }
+        let (instruction, bank, sp, stack_top, stack_top_bank) = self.get_call_stack_state(pc);
+        let ticks = self.get_elapsed_ticks();
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.step(instruction, bank, sp, stack_top, stack_top_bank, ticks);
+        }
+    }
+
+    /// Finish profiling the current frame, if profiling.
+    pub(crate) fn end_profiler_frame(&mut self) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.end_frame();
+        }
+    }
+
+    /// Re-evaluate the RAM watches, if set, returning a copy of the changes.
+    ///
+    /// The changes are copied since the callbacks they're passed to can replace the list.
+    pub(crate) fn update_ram_watches(&mut self) -> Vec<RamWatchChange> {
+        let Some(mut ram_watches) = self.ram_watches.take() else {
+            return Vec::new()
+        };
+        ram_watches.update(|address, buffer| self.peek_range(address, buffer), |address| self.get_bank_for_address(address));
+        let changes = ram_watches.get_changes().to_vec();
+        self.ram_watches = Some(ram_watches);
+        changes
+    }
+
+    /// Evaluate the achievements, if set, returning a copy of the ones that unlocked.
+    ///
+    /// The IDs are copied since the callbacks they're passed to can replace the set.
+    pub(crate) fn update_achievements(&mut self) -> Vec<AchievementId> {
+        let Some(mut achievements) = self.achievements.take() else {
+            return Vec::new()
+        };
+        achievements.update(|address| u16::try_from(address).map_or(0, |address| self.peek(address)));
+        let triggered = achievements.get_triggered().to_vec();
+        self.achievements = Some(achievements);
+        triggered
+    }
+
+    /// Evaluate the autosplitter, if set, returning a copy of the events.
+    ///
+    /// The events are copied since the callbacks they're passed to can replace the autosplitter.
+    pub(crate) fn update_autosplitter(&mut self) -> Vec<AutosplitterEvent> {
+        let Some(mut autosplitter) = self.autosplitter.take() else {
+            return Vec::new()
+        };
+        let ticks = self.get_elapsed_ticks();
+        let clock_rate = self.get_clock_rate();
+        autosplitter.update(|address, buffer| self.peek_range(address, buffer), |address| self.get_bank_for_address(address), ticks, clock_rate);
+        let events = autosplitter.get_events().to_vec();
+        self.autosplitter = Some(autosplitter);
+        events
+    }
+
+    /// Get the instruction at `pc`, its bank, SP, the word at SP, and its bank.
+    fn get_call_stack_state(&self, pc: u16) -> (Instruction, Option<u16>, u16, u16, Option<u16>) {
+        let instruction = self.decode_instruction_at(pc);
+        let bank = self.get_bank_for_address(pc);
+        let sp = self.get_registers().sp;
+        let stack_top = u16::from_le_bytes([self.peek(sp), self.peek(sp.wrapping_add(1))]);
+        (instruction, bank, sp, stack_top, self.get_bank_for_address(stack_top))
+    }
+
+    /// Get the bank currently mapped to `address`, if the address is in ROM, VRAM, cartridge RAM,
+    /// or work RAM.
+    pub(crate) fn get_bank_for_address(&self, address: u16) -> Option<u16> {
+        match MemoryRegion::from_address(address) {
+            MemoryRegion::Wram0 => Some(0),
+            region if region.is_banked() => Some(self.get_region_bank(region)),
+            _ => None
+        }
+    }
+
+    /// Get the bank currently mapped to `region` (0 if it is not banked).
+    fn get_region_bank(&self, region: MemoryRegion) -> u16 {
+        let access = match region {
+            MemoryRegion::Rom0 => DirectAccessRegion::ROM0,
+            MemoryRegion::RomX => DirectAccessRegion::ROM,
+            MemoryRegion::Vram => DirectAccessRegion::VRAM,
+            MemoryRegion::Sram => DirectAccessRegion::CartRAM,
+            MemoryRegion::WramX => DirectAccessRegion::RAM,
+            _ => return 0
+        };
+        unsafe { direct_access(self.gb, access) }.bank
+    }
+
+    /// Call `function`, returning everything it logged instead of passing it to the log callback.
+    #[cfg(any(feature = "debugger", feature = "cheat-search"))]
+    pub(crate) fn capture_log<F: FnOnce(*mut GB_gameboy_t)>(&mut self, function: F) -> String {
+        let previous = self.log_capture.replace(String::new());
+        unsafe { GB_set_log_callback(self.gb, Some(log_callback)) };
+        function(self.gb);
+        if !self.callbacks_installed {
+            // Restore SameBoy's default logging
+            unsafe { GB_set_log_callback(self.gb, None) };
+        }
+        core::mem::replace(&mut self.log_capture, previous).unwrap_or_default()
+    }
+
+    /// Count executed instructions in `instructions_executed` until this is called with false.
+    pub(super) fn set_counting_instructions(&mut self, counting: bool) {
+        self.counting_instructions = counting;
+        self.instructions_executed = 0;
+        self.update_memory_hooks();
+    }
+
+    /// Record the instruction about to be executed, if tracing.
+    pub(crate) fn trace_instruction(&mut self, pc: u16, opcode: u8) {
+        if !self.tracer.as_mut().is_some_and(|t| t.should_record(pc)) {
+            return
+        }
+
+        // PC has already been incremented past the opcode at this point.
+        let mut registers = self.get_registers();
+        registers.pc = pc;
+        let mut bytes = [0u8; 4];
+        self.peek_range(pc, &mut bytes);
+
+        let entry = TraceEntry {
+            registers,
+            bank: self.get_bank_for_address(pc),
+            opcode,
+            bytes,
+            ly: self.peek(0xFF44),
+            ticks: self.get_elapsed_ticks()
+        };
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record(entry);
+        }
+    }
+
+    /// Get the bank of `address` as used in symbol files.
+    fn get_symbol_bank_for_address(&self, address: u16) -> u16 {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.get_bank_for_address(address).unwrap_or(0)
+        }
+    }
+
+    /// Describe `address` in `bank` with the symbol table.
+    fn describe_banked_address(&self, address: u16, bank: Option<u16>) -> Option<String> {
+        let bank = match address {
+            0x0000..=0x3FFF => 0,
+            _ => bank.unwrap_or(0)
+        };
+        self.symbols.as_ref()?.describe(bank, address)
+    }
+
+    fn update_memory_hooks(&mut self) {
+        let mut hooks = self.memory_hooks | self.watches.get_hooks();
+        if self.counting_instructions || self.tracer.is_some() || self.call_stack.is_some() || self.profiler.is_some() {
+            hooks |= MemoryHooks::EXECUTE;
+        }
+        if self.code_data_logger.is_some() {
+            hooks |= MemoryHooks::READ | MemoryHooks::EXECUTE;
+        }
+        unsafe {
+            GB_set_read_memory_callback(self.gb, hooks.contains(MemoryHooks::READ).then_some(read_memory_callback));
+            GB_set_write_memory_callback(self.gb, hooks.contains(MemoryHooks::WRITE).then_some(write_memory_callback));
+            GB_set_execution_callback(self.gb, hooks.contains(MemoryHooks::EXECUTE).then_some(execution_callback));
+        }
+    }
+
+    fn finish_movie_reset(&mut self) {
+        // Resets can't be done from inside of a callback, so they're done once the emulator stops.
+        if let Some(reset) = self.movie_reset_pending.take() {
+            self.apply_movie_reset(reset);
+        }
+    }
+
+    fn apply_movie_reset(&mut self, reset: MovieReset) {
+        match reset {
+            MovieReset::Hard => unsafe { GB_reset(self.gb) },
+            MovieReset::Quick => unsafe { GB_quick_reset(self.gb) }
+        }
+    }
+
+    pub(crate) fn update_key_mask(&mut self) {
+        // While a movie is active, input is only applied when it is latched for the frame.
+        if self.movie.is_none() {
+            let mask = self.input_scheduler.input_mask(self.input_mask);
+            unsafe { GB_set_key_mask(self.gb, mask as _) };
+        }
+    }
+
     pub(super) fn finish_init(&mut self) {
         self.assert_not_running();
 
@@ -150,9 +592,17 @@ impl RunningGameboy {
 
         // RGB encoder
         self.set_rgb_encoder(encode_a8r8g8b8);
+
+        // Lag frames, movies, and scheduled input are tracked in these, so they're always needed.
+        unsafe {
+            GB_set_vblank_callback(self.gb, Some(vblank_callback));
+            GB_set_update_input_hint_callback(self.gb, Some(update_input_hint_callback));
+        }
     }
 
     fn setup_callbacks(&mut self) {
+        self.callbacks_installed = true;
+
         // Set callbacks and sane defaults so the callbacks work.
         unsafe {
             GB_set_vblank_callback(self.gb, Some(vblank_callback));
@@ -165,6 +615,8 @@ impl RunningGameboy {
             // Console stuff
             GB_set_log_callback(self.gb, Some(log_callback));
             GB_set_input_callback(self.gb, Some(input_callback));
+            #[cfg(feature = "debugger")]
+            GB_debugger_set_reload_callback(self.gb, Some(debugger_reload_callback));
             // won't do GB_set_async_input_callback yet since it's probably really unsafe
 
             // Camera stuff
@@ -181,6 +633,15 @@ impl RunningGameboy {
         }
     }
 
+    pub(crate) fn update_lag_frame(&mut self) {
+        // If the game never read JOYP since the last vblank, this was a lag frame.
+        self.lag_frame = !self.is_joypad_accessed();
+        if self.lag_frame {
+            self.lag_frame_count += 1;
+        }
+        self.clear_joypad_accessed();
+    }
+
     pub(super) fn fixup_rom_title(&mut self) {
         let mut name = [0u8; 17];
         unsafe { GB_get_rom_title(self.gb, name.as_mut_ptr() as *mut c_char) };
@@ -224,6 +685,11 @@ pub trait RunnableInstanceFunctions {
     fn get_pixel_buffer(&self) -> PixelBufferRead<'_>;
 
     /// Set the current state for one button.
+    ///
+    /// # Remarks
+    ///
+    /// While a movie is active, input is latched once per frame, on the first input hint of the
+    /// frame (after the [`update_input_hint`](GameboyCallbacks::update_input_hint) callback).
     fn set_input_button_state(&mut self, button: InputButton, state: bool);
 
     /// Set the current state for all buttons.
@@ -232,8 +698,59 @@ pub trait RunnableInstanceFunctions {
     ///
     /// For example `set_input_button_mask((1 << InputButton::A) | (1 << InputButton::B))` presses
     /// the A and B buttons while releasing all other buttons.
+    ///
+    /// # Remarks
+    ///
+    /// While a movie is active, input is latched once per frame, on the first input hint of the
+    /// frame (after the [`update_input_hint`](GameboyCallbacks::update_input_hint) callback).
     fn set_input_button_mask(&mut self, state: u8);
 
+    /// Set (or remove) autofire for a button.
+    ///
+    /// While the button is held, it will be pressed and released at the given rate.
+    fn set_autofire(&mut self, button: InputButton, rate: Option<AutofireRate>);
+
+    /// Queue an input macro.
+    ///
+    /// Macros are run one at a time in the order they were queued, and their input is combined
+    /// with the input set by the user.
+    fn queue_input_macro(&mut self, input_macro: InputMacro);
+
+    /// Stop the current input macro and remove all queued macros.
+    fn clear_input_macros(&mut self);
+
+    /// Returns true if an input macro is running.
+    fn is_input_macro_running(&self) -> bool;
+
+    /// Get the status of the active movie, if any.
+    fn get_movie_status(&self) -> Option<MovieStatus>;
+
+    /// Get the current frame of the active movie, if any.
+    fn get_movie_frame(&self) -> Option<u32>;
+
+    /// Returns true if the game has read the joypad register (JOYP) since the last vblank.
+    fn is_joypad_accessed(&self) -> bool;
+
+    /// Clear the joypad access flag.
+    ///
+    /// # Remarks
+    ///
+    /// This is done automatically on every vblank to update the lag frame state, so calling this
+    /// mid-frame may cause the current frame to be counted as a lag frame.
+    fn clear_joypad_accessed(&mut self);
+
+    /// Returns true if the game did not poll input during the last completed frame.
+    ///
+    /// This is updated on every vblank, before the vblank callback is called.
+    fn is_lag_frame(&self) -> bool;
+
+    /// Get the number of lag frames since the instance was created (or since the counter was last
+    /// reset).
+    fn get_lag_frame_count(&self) -> u64;
+
+    /// Reset the lag frame counter to 0.
+    fn reset_lag_frame_count(&mut self);
+
     /// Load the boot ROM.
     fn load_boot_rom(&mut self, boot_rom: &[u8]);
 
@@ -254,9 +771,160 @@ pub trait RunnableInstanceFunctions {
 
     /// Set whether or not I/O and execution callbacks are enabled.
     ///
+    /// This is the same as calling [`set_memory_hooks`](RunnableInstanceFunctions::set_memory_hooks)
+    /// with [`MemoryHooks::ALL`] or [`MemoryHooks::NONE`].
+    ///
     /// By default, memory callbacks are **not** enabled.
     fn set_memory_callbacks_enabled(&mut self, enabled: bool);
 
+    /// Set which I/O and execution callbacks are enabled.
+    ///
+    /// Each hook adds overhead to every memory access (or every instruction), so only enable the
+    /// hooks you need.
+    ///
+    /// By default, no hooks are enabled.
+    fn set_memory_hooks(&mut self, hooks: MemoryHooks);
+
+    /// Get which I/O and execution callbacks are enabled.
+    fn get_memory_hooks(&self) -> MemoryHooks;
+
+    /// Add a breakpoint or watchpoint.
+    ///
+    /// When a watch is triggered, the [`watch_triggered`](GameboyCallbacks::watch_triggered)
+    /// callback is called, and the hit is recorded. [`Gameboy::run`] returns at the end of the
+    /// step in which it was triggered, and the hits can be retrieved with
+    /// [`get_watch_hits`](RunnableInstanceFunctions::get_watch_hits).
+    ///
+    /// The hooks needed for the watch are enabled automatically, but the corresponding user
+    /// callbacks are only called if enabled with
+    /// [`set_memory_hooks`](RunnableInstanceFunctions::set_memory_hooks).
+    fn add_watch(&mut self, watch: Watch) -> WatchId;
+
+    /// Remove a watch.
+    ///
+    /// Returns false if no watch with the given ID exists.
+    fn remove_watch(&mut self, id: WatchId) -> bool;
+
+    /// Remove all watches.
+    fn clear_watches(&mut self);
+
+    /// Get all watches that were triggered since the emulator last started running.
+    fn get_watch_hits(&self) -> &[WatchHit];
+
+    /// Get the number of ticks emulated since the instance was created.
+    ///
+    /// Ticks run at twice [the clock rate](Self::get_clock_rate), so this is in 8 MiHz ticks only
+    /// on a DMG or CGB without a clock multiplier (the original SGB's clock is slightly faster).
+    ///
+    /// This is not affected by resets or loading save states.
+    fn get_elapsed_ticks(&self) -> u64;
+
+    /// Set (or remove) the execution tracer.
+    ///
+    /// While a tracer is set, the execution hook is enabled and every instruction is passed to the
+    /// tracer before any watches or callbacks.
+    fn set_tracer(&mut self, tracer: Option<Tracer>);
+
+    /// Get the execution tracer.
+    fn get_tracer(&self) -> Option<&Tracer>;
+
+    /// Get the execution tracer mutably (e.g. to take its entries).
+    fn get_tracer_mut(&mut self) -> Option<&mut Tracer>;
+
+    /// Set (or remove) the code/data logger.
+    ///
+    /// While a logger is set, the read and execution hooks are enabled, and every ROM byte that is
+    /// executed or read is flagged in the logger.
+    fn set_code_data_logger(&mut self, logger: Option<CodeDataLogger>);
+
+    /// Get the code/data logger.
+    fn get_code_data_logger(&self) -> Option<&CodeDataLogger>;
+
+    /// Get the code/data logger mutably.
+    fn get_code_data_logger_mut(&mut self) -> Option<&mut CodeDataLogger>;
+
+    /// Set (or remove) the call stack tracker.
+    ///
+    /// While a call stack is set, the execution hook is enabled, and
+    /// [`interrupt_serviced`](GameboyCallbacks::interrupt_serviced) is called whenever an interrupt
+    /// is serviced.
+    fn set_call_stack(&mut self, call_stack: Option<CallStack>);
+
+    /// Get the call stack tracker.
+    fn get_call_stack(&self) -> Option<&CallStack>;
+
+    /// Get a backtrace, starting with the current PC and followed by each call, innermost first.
+    ///
+    /// Addresses are named with the symbol table, if one is set. Returns an empty list if no call
+    /// stack tracker is set.
+    fn get_backtrace(&self) -> Vec<BacktraceFrame>;
+
+    /// Set (or remove) the profiler.
+    ///
+    /// While a profiler is set, the execution hook is enabled, and each frame is reported when
+    /// vblank is reached.
+    fn set_profiler(&mut self, profiler: Option<Profiler>);
+
+    /// Get the profiler.
+    fn get_profiler(&self) -> Option<&Profiler>;
+
+    /// Get the profiler mutably (e.g. to reset it).
+    fn get_profiler_mut(&mut self) -> Option<&mut Profiler>;
+
+    /// Set (or remove) the RAM watch list.
+    ///
+    /// The watches are evaluated every time vblank is reached, and each change is passed to
+    /// [`ram_watch_changed`](GameboyCallbacks::ram_watch_changed).
+    fn set_ram_watch_list(&mut self, ram_watches: Option<RamWatchList>);
+
+    /// Get the RAM watch list.
+    fn get_ram_watch_list(&self) -> Option<&RamWatchList>;
+
+    /// Get the RAM watch list mutably (e.g. to add watches).
+    fn get_ram_watch_list_mut(&mut self) -> Option<&mut RamWatchList>;
+
+    /// Set (or remove) the achievement set.
+    ///
+    /// The achievements are evaluated every time vblank is reached, and each one that unlocks is
+    /// passed to [`achievement_triggered`](GameboyCallbacks::achievement_triggered).
+    fn set_achievement_set(&mut self, achievements: Option<AchievementSet>);
+
+    /// Get the achievement set.
+    fn get_achievement_set(&self) -> Option<&AchievementSet>;
+
+    /// Get the achievement set mutably (e.g. to add achievements).
+    fn get_achievement_set_mut(&mut self) -> Option<&mut AchievementSet>;
+
+    /// Set (or remove) the autosplitter.
+    ///
+    /// The autosplitter is evaluated every time vblank is reached, and each event is passed to
+    /// [`autosplitter_event`](GameboyCallbacks::autosplitter_event).
+    fn set_autosplitter(&mut self, autosplitter: Option<Autosplitter>);
+
+    /// Get the autosplitter.
+    fn get_autosplitter(&self) -> Option<&Autosplitter>;
+
+    /// Get the autosplitter mutably (e.g. to add splits).
+    fn get_autosplitter_mut(&mut self) -> Option<&mut Autosplitter>;
+
+    /// Set (or remove) the symbols used for naming addresses.
+    ///
+    /// If the `debugger` feature is enabled, the symbols are also loaded into SameBoy's debugger
+    /// (replacing any symbols it has), so they are shown in its disassembly and commands.
+    fn set_symbols(&mut self, symbols: Option<SymbolTable>);
+
+    /// Get the symbols used for naming addresses.
+    fn get_symbols(&self) -> Option<&SymbolTable>;
+
+    /// Get the name of the symbol at `address`, using the bank currently mapped there.
+    fn get_name_for_address(&self, address: u16) -> Option<&str>;
+
+    /// Describe `address` relative to the nearest preceding label, using the bank currently mapped
+    /// there.
+    ///
+    /// See [`SymbolTable::describe`].
+    fn describe_address(&self, address: u16) -> Option<String>;
+
     /// Set whether or not rendering is enabled.
     /// 
     /// If rendering is disabled, the pixel buffer won't be updated. Note, however, that the vblank
@@ -286,9 +954,75 @@ pub trait RunnableInstanceFunctions {
     /// Get direct access to a given region.
     fn direct_access(&'_ self, access: DirectAccessRegion) -> DirectAccessData<'_>;
 
+    /// Get the banks currently mapped to the CPU's address space.
+    fn get_memory_map(&self) -> MemoryMap;
+
     /// Get direct access to a given region.
     fn direct_access_mut(&'_ mut self, access: DirectAccessRegion) -> DirectAccessDataMut<'_>;
 
+    /// Read a byte from the CPU's address space without any side effects.
+    ///
+    /// This reads whatever is currently mapped at `address`, including the current ROM, VRAM,
+    /// WRAM, and SRAM banks. No hooks or watches are triggered, so this is safe to call from inside
+    /// of any callback.
+    fn peek(&self, address: u16) -> u8;
+
+    /// Read a byte from the CPU's address space as if the CPU read it.
+    ///
+    /// # Remarks
+    ///
+    /// This may have side effects (e.g. reading some I/O registers), and it will call the
+    /// [`read_memory`](GameboyCallbacks::read_memory) callback if it is enabled, so avoid calling
+    /// this from inside of that callback.
+    fn read(&mut self, address: u16) -> u8;
+
+    /// Write a byte to the CPU's address space as if the CPU wrote it.
+    ///
+    /// # Remarks
+    ///
+    /// This has the same side effects as a CPU write (e.g. switching MBC banks or writing to I/O
+    /// registers), and it will call the [`write_memory`](GameboyCallbacks::write_memory) callback
+    /// if it is enabled, so avoid calling this from inside of that callback.
+    fn write(&mut self, address: u16, value: u8);
+
+    /// Fill `buffer` with bytes starting at `address` without any side effects.
+    ///
+    /// The address wraps around at `0xFFFF`.
+    fn peek_range(&self, address: u16, buffer: &mut [u8]);
+
+    /// Fill `buffer` with bytes starting at `address` as if the CPU read them.
+    ///
+    /// The address wraps around at `0xFFFF`. See [`read`](RunnableInstanceFunctions::read) for
+    /// remarks.
+    fn read_range(&mut self, address: u16, buffer: &mut [u8]);
+
+    /// Write `data` starting at `address` as if the CPU wrote it.
+    ///
+    /// The address wraps around at `0xFFFF`. See [`write`](RunnableInstanceFunctions::write) for
+    /// remarks.
+    fn write_range(&mut self, address: u16, data: &[u8]);
+
+    /// Read a little endian `u16` starting at `address` without any side effects.
+    fn peek_u16(&self, address: u16) -> u16;
+
+    /// Read a little endian `u16` starting at `address` as if the CPU read it.
+    ///
+    /// See [`read`](RunnableInstanceFunctions::read) for remarks.
+    fn read_u16(&mut self, address: u16) -> u16;
+
+    /// Decode the instruction at `address` without any side effects.
+    ///
+    /// This uses the built-in decoder (see [`decode_instruction`](crate::decode_instruction)), so
+    /// it does not require the `debugger` feature.
+    fn decode_instruction_at(&self, address: u16) -> Instruction;
+
+    /// Disassemble `count` instructions starting at `address` with SameBoy's disassembler.
+    ///
+    /// Returns one string per line of output. This includes labels if symbols are loaded, and the
+    /// current instruction is marked with `->`.
+    #[cfg(feature = "debugger")]
+    fn disassemble(&mut self, address: u16, count: u16) -> Vec<String>;
+
     /// Returns true if the current emulator is a Game Boy Color.
     fn is_cgb(&self) -> bool;
 
@@ -388,8 +1122,33 @@ pub trait RunnableInstanceFunctions {
     fn get_registers(&self) -> Registers;
 
     /// Set the CPU registers.
+    ///
+    /// The lower four bits of `F` are always cleared, as they do not exist on hardware.
     fn set_registers(&mut self, registers: &Registers);
 
+    /// Get the CPU registers along with the interrupt and power state of the CPU.
+    fn get_cpu_state(&self) -> CpuState;
+
+    /// Set the CPU registers along with IME, IE, and IF.
+    ///
+    /// The HALT, STOP, and double speed states are read-only and are ignored.
+    fn set_cpu_state(&mut self, state: &CpuState);
+
+    /// Get IME, IE, and IF.
+    fn interrupts(&self) -> InterruptState;
+
+    /// Set IME, IE, and IF.
+    ///
+    /// Any interrupt that is enabled and requested will be serviced before the next instruction if
+    /// IME is set, and will wake the CPU from HALT regardless.
+    fn set_interrupts(&mut self, state: &InterruptState);
+
+    /// Request `interrupt` by setting its bit in IF, as if the hardware raised it.
+    ///
+    /// Use [`IoRegister::write_to`](crate::IoRegister::write_to) to change the registers that
+    /// raise interrupts (e.g. LYC or the timer) with their usual side effects.
+    fn request_interrupt(&mut self, interrupt: Interrupt);
+
     /// Set the maximum rewind length in seconds.
     fn set_rewind_length(&mut self, seconds: f64);
 
@@ -410,6 +1169,9 @@ pub trait RunnableInstanceFunctions {
 
     /// Get the ROM name.
     fn get_rom_title(&self) -> &str;
+
+    /// Get the CRC32 of the loaded ROM.
+    fn get_rom_crc32(&self) -> u32;
 }
 
 
@@ -439,6 +1201,61 @@ impl RunnableInstanceFunctions for Gameboy {
         self.do_with_inner_mut(|inner| inner.set_input_button_mask(state))
     }
 
+    #[inline]
+    fn set_autofire(&mut self, button: InputButton, rate: Option<AutofireRate>) {
+        self.do_with_inner_mut(|inner| inner.set_autofire(button, rate))
+    }
+
+    #[inline]
+    fn queue_input_macro(&mut self, input_macro: InputMacro) {
+        self.do_with_inner_mut(|inner| inner.queue_input_macro(input_macro))
+    }
+
+    #[inline]
+    fn clear_input_macros(&mut self) {
+        self.do_with_inner_mut(|inner| inner.clear_input_macros())
+    }
+
+    #[inline]
+    fn is_input_macro_running(&self) -> bool {
+        self.inner.is_input_macro_running()
+    }
+
+    #[inline]
+    fn get_movie_status(&self) -> Option<MovieStatus> {
+        self.inner.get_movie_status()
+    }
+
+    #[inline]
+    fn get_movie_frame(&self) -> Option<u32> {
+        self.inner.get_movie_frame()
+    }
+
+    #[inline]
+    fn is_joypad_accessed(&self) -> bool {
+        self.inner.is_joypad_accessed()
+    }
+
+    #[inline]
+    fn clear_joypad_accessed(&mut self) {
+        self.do_with_inner_mut(|inner| inner.clear_joypad_accessed())
+    }
+
+    #[inline]
+    fn is_lag_frame(&self) -> bool {
+        self.inner.is_lag_frame()
+    }
+
+    #[inline]
+    fn get_lag_frame_count(&self) -> u64 {
+        self.inner.get_lag_frame_count()
+    }
+
+    #[inline]
+    fn reset_lag_frame_count(&mut self) {
+        self.do_with_inner_mut(|inner| inner.reset_lag_frame_count())
+    }
+
     #[inline]
     fn load_boot_rom(&mut self, boot_rom: &[u8]) {
         self.do_with_inner_mut(|inner| inner.load_boot_rom(boot_rom))
@@ -464,6 +1281,172 @@ impl RunnableInstanceFunctions for Gameboy {
         self.do_with_inner_mut(|inner| inner.set_memory_callbacks_enabled(enabled))
     }
 
+    #[inline]
+    fn set_memory_hooks(&mut self, hooks: MemoryHooks) {
+        self.do_with_inner_mut(|inner| inner.set_memory_hooks(hooks))
+    }
+
+    #[inline]
+    fn get_memory_hooks(&self) -> MemoryHooks {
+        self.inner.get_memory_hooks()
+    }
+
+    #[inline]
+    fn add_watch(&mut self, watch: Watch) -> WatchId {
+        self.do_with_inner_mut(|inner| inner.add_watch(watch))
+    }
+
+    #[inline]
+    fn remove_watch(&mut self, id: WatchId) -> bool {
+        self.do_with_inner_mut(|inner| inner.remove_watch(id))
+    }
+
+    #[inline]
+    fn clear_watches(&mut self) {
+        self.do_with_inner_mut(|inner| inner.clear_watches())
+    }
+
+    #[inline]
+    fn get_watch_hits(&self) -> &[WatchHit] {
+        self.inner.get_watch_hits()
+    }
+
+    #[inline]
+    fn get_elapsed_ticks(&self) -> u64 {
+        self.inner.get_elapsed_ticks()
+    }
+
+    #[inline]
+    fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.do_with_inner_mut(|inner| inner.set_tracer(tracer))
+    }
+
+    #[inline]
+    fn get_tracer(&self) -> Option<&Tracer> {
+        self.inner.get_tracer()
+    }
+
+    #[inline]
+    fn get_tracer_mut(&mut self) -> Option<&mut Tracer> {
+        // SAFETY: We aren't moving or invalidating anything here.
+        unsafe { self.inner.as_mut().get_unchecked_mut() }.get_tracer_mut()
+    }
+
+    #[inline]
+    fn set_code_data_logger(&mut self, logger: Option<CodeDataLogger>) {
+        self.do_with_inner_mut(|inner| inner.set_code_data_logger(logger))
+    }
+
+    #[inline]
+    fn get_code_data_logger(&self) -> Option<&CodeDataLogger> {
+        self.inner.get_code_data_logger()
+    }
+
+    #[inline]
+    fn get_code_data_logger_mut(&mut self) -> Option<&mut CodeDataLogger> {
+        // SAFETY: We aren't moving or invalidating anything here.
+        unsafe { self.inner.as_mut().get_unchecked_mut() }.get_code_data_logger_mut()
+    }
+
+    #[inline]
+    fn set_call_stack(&mut self, call_stack: Option<CallStack>) {
+        self.do_with_inner_mut(|inner| inner.set_call_stack(call_stack))
+    }
+
+    #[inline]
+    fn get_call_stack(&self) -> Option<&CallStack> {
+        self.inner.get_call_stack()
+    }
+
+    #[inline]
+    fn get_backtrace(&self) -> Vec<BacktraceFrame> {
+        self.inner.get_backtrace()
+    }
+
+    #[inline]
+    fn set_profiler(&mut self, profiler: Option<Profiler>) {
+        self.do_with_inner_mut(|inner| inner.set_profiler(profiler))
+    }
+
+    #[inline]
+    fn get_profiler(&self) -> Option<&Profiler> {
+        self.inner.get_profiler()
+    }
+
+    #[inline]
+    fn get_profiler_mut(&mut self) -> Option<&mut Profiler> {
+        // SAFETY: We aren't moving or invalidating anything here.
+        unsafe { self.inner.as_mut().get_unchecked_mut() }.get_profiler_mut()
+    }
+
+    #[inline]
+    fn set_ram_watch_list(&mut self, ram_watches: Option<RamWatchList>) {
+        self.do_with_inner_mut(|inner| inner.set_ram_watch_list(ram_watches))
+    }
+
+    #[inline]
+    fn get_ram_watch_list(&self) -> Option<&RamWatchList> {
+        self.inner.get_ram_watch_list()
+    }
+
+    #[inline]
+    fn get_ram_watch_list_mut(&mut self) -> Option<&mut RamWatchList> {
+        // SAFETY: We aren't moving or invalidating anything here.
+        unsafe { self.inner.as_mut().get_unchecked_mut() }.get_ram_watch_list_mut()
+    }
+
+    #[inline]
+    fn set_achievement_set(&mut self, achievements: Option<AchievementSet>) {
+        self.do_with_inner_mut(|inner| inner.set_achievement_set(achievements))
+    }
+
+    #[inline]
+    fn get_achievement_set(&self) -> Option<&AchievementSet> {
+        self.inner.get_achievement_set()
+    }
+
+    #[inline]
+    fn get_achievement_set_mut(&mut self) -> Option<&mut AchievementSet> {
+        // SAFETY: We aren't moving or invalidating anything here.
+        unsafe { self.inner.as_mut().get_unchecked_mut() }.get_achievement_set_mut()
+    }
+
+    #[inline]
+    fn set_autosplitter(&mut self, autosplitter: Option<Autosplitter>) {
+        self.do_with_inner_mut(|inner| inner.set_autosplitter(autosplitter))
+    }
+
+    #[inline]
+    fn get_autosplitter(&self) -> Option<&Autosplitter> {
+        self.inner.get_autosplitter()
+    }
+
+    #[inline]
+    fn get_autosplitter_mut(&mut self) -> Option<&mut Autosplitter> {
+        // SAFETY: We aren't moving or invalidating anything here.
+        unsafe { self.inner.as_mut().get_unchecked_mut() }.get_autosplitter_mut()
+    }
+
+    #[inline]
+    fn set_symbols(&mut self, symbols: Option<SymbolTable>) {
+        self.do_with_inner_mut(|inner| inner.set_symbols(symbols))
+    }
+
+    #[inline]
+    fn get_symbols(&self) -> Option<&SymbolTable> {
+        self.inner.get_symbols()
+    }
+
+    #[inline]
+    fn get_name_for_address(&self, address: u16) -> Option<&str> {
+        self.inner.get_name_for_address(address)
+    }
+
+    #[inline]
+    fn describe_address(&self, address: u16) -> Option<String> {
+        self.inner.describe_address(address)
+    }
+
     #[inline]
     fn set_rendering_enabled(&mut self, enabled: bool) {
         self.do_with_inner_mut(|inner| inner.set_rendering_enabled(enabled))
@@ -494,11 +1477,67 @@ impl RunnableInstanceFunctions for Gameboy {
         self.inner.direct_access(access)
     }
 
+    #[inline]
+    fn get_memory_map(&self) -> MemoryMap {
+        self.inner.get_memory_map()
+    }
+
     #[inline]
     fn direct_access_mut(&'_ mut self, access: DirectAccessRegion) -> DirectAccessDataMut<'_> {
         self.do_with_inner_mut(|inner| unsafe { transmute::<DirectAccessDataMut, DirectAccessDataMut>(inner.direct_access_mut(access)) })
     }
 
+    #[inline]
+    fn peek(&self, address: u16) -> u8 {
+        self.inner.peek(address)
+    }
+
+    #[inline]
+    fn read(&mut self, address: u16) -> u8 {
+        self.do_with_inner_mut(|inner| inner.read(address))
+    }
+
+    #[inline]
+    fn write(&mut self, address: u16, value: u8) {
+        self.do_with_inner_mut(|inner| inner.write(address, value))
+    }
+
+    #[inline]
+    fn peek_range(&self, address: u16, buffer: &mut [u8]) {
+        self.inner.peek_range(address, buffer)
+    }
+
+    #[inline]
+    fn read_range(&mut self, address: u16, buffer: &mut [u8]) {
+        self.do_with_inner_mut(|inner| inner.read_range(address, buffer))
+    }
+
+    #[inline]
+    fn write_range(&mut self, address: u16, data: &[u8]) {
+        self.do_with_inner_mut(|inner| inner.write_range(address, data))
+    }
+
+    #[inline]
+    fn peek_u16(&self, address: u16) -> u16 {
+        self.inner.peek_u16(address)
+    }
+
+    #[inline]
+    fn read_u16(&mut self, address: u16) -> u16 {
+        self.do_with_inner_mut(|inner| inner.read_u16(address))
+    }
+
+    #[inline]
+    fn decode_instruction_at(&self, address: u16) -> Instruction {
+        self.inner.decode_instruction_at(address)
+    }
+
+    #[cfg(feature = "debugger")]
+    #[inline]
+    fn disassemble(&mut self, address: u16, count: u16) -> Vec<String> {
+        self.do_with_inner_mut(|inner| inner.disassemble(address, count))
+    }
+
     #[inline]
     fn is_cgb(&self) -> bool {
         self.inner.is_cgb()
@@ -600,18 +1639,43 @@ impl RunnableInstanceFunctions for Gameboy {
     }
 
     #[inline]
-    fn set_rtc_mode(&mut self, rtc_mode: RtcMode) {
-        self.do_with_inner_mut(|inner| inner.set_rtc_mode(rtc_mode))
+    fn set_rtc_mode(&mut self, rtc_mode: RtcMode) {
+        self.do_with_inner_mut(|inner| inner.set_rtc_mode(rtc_mode))
+    }
+
+    #[inline]
+    fn get_registers(&self) -> Registers {
+        self.inner.get_registers()
+    }
+
+    #[inline]
+    fn set_registers(&mut self, registers: &Registers) {
+        self.do_with_inner_mut(|inner| inner.set_registers(registers))
+    }
+
+    #[inline]
+    fn get_cpu_state(&self) -> CpuState {
+        self.inner.get_cpu_state()
+    }
+
+    #[inline]
+    fn set_cpu_state(&mut self, state: &CpuState) {
+        self.do_with_inner_mut(|inner| inner.set_cpu_state(state))
+    }
+
+    #[inline]
+    fn interrupts(&self) -> InterruptState {
+        self.inner.interrupts()
     }
 
     #[inline]
-    fn get_registers(&self) -> Registers {
-        self.inner.get_registers()
+    fn set_interrupts(&mut self, state: &InterruptState) {
+        self.do_with_inner_mut(|inner| inner.set_interrupts(state))
     }
 
     #[inline]
-    fn set_registers(&mut self, registers: &Registers) {
-        self.do_with_inner_mut(|inner| inner.set_registers(registers))
+    fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.do_with_inner_mut(|inner| inner.request_interrupt(interrupt))
     }
 
     #[inline]
@@ -638,6 +1702,11 @@ impl RunnableInstanceFunctions for Gameboy {
     fn get_rom_title(&self) -> &str {
         self.inner.get_rom_title()
     }
+
+    #[inline]
+    fn get_rom_crc32(&self) -> u32 {
+        self.inner.get_rom_crc32()
+    }
 }
 
 impl RunnableInstanceFunctions for RunningGameboy {
@@ -658,12 +1727,75 @@ impl RunnableInstanceFunctions for RunningGameboy {
 
     #[inline]
     fn set_input_button_state(&mut self, button: InputButton, state: bool) {
-        unsafe { GB_set_key_state(self.gb, button as u32, state) }
+        let mask = 1u8 << button;
+        if state {
+            self.input_mask |= mask;
+        }
+        else {
+            self.input_mask &= !mask;
+        }
+        self.update_key_mask();
     }
 
     #[inline]
     fn set_input_button_mask(&mut self, state: u8) {
-        unsafe { GB_set_key_mask(self.gb, state as _) }
+        self.input_mask = state;
+        self.update_key_mask();
+    }
+
+    fn set_autofire(&mut self, button: InputButton, rate: Option<AutofireRate>) {
+        self.input_scheduler.set_autofire(button as u8, rate);
+        self.update_key_mask();
+    }
+
+    fn queue_input_macro(&mut self, input_macro: InputMacro) {
+        self.input_scheduler.queue_macro(input_macro);
+        self.update_key_mask();
+    }
+
+    fn clear_input_macros(&mut self) {
+        self.input_scheduler.clear_macros();
+        self.update_key_mask();
+    }
+
+    #[inline]
+    fn is_input_macro_running(&self) -> bool {
+        self.input_scheduler.is_macro_running()
+    }
+
+    #[inline]
+    fn get_movie_status(&self) -> Option<MovieStatus> {
+        self.movie.as_ref().map(MovieSession::status)
+    }
+
+    #[inline]
+    fn get_movie_frame(&self) -> Option<u32> {
+        self.movie.as_ref().map(MovieSession::frame)
+    }
+
+    #[inline]
+    fn is_joypad_accessed(&self) -> bool {
+        unsafe { GB_get_joyp_accessed(self.gb) }
+    }
+
+    #[inline]
+    fn clear_joypad_accessed(&mut self) {
+        unsafe { GB_clear_joyp_accessed(self.gb) }
+    }
+
+    #[inline]
+    fn is_lag_frame(&self) -> bool {
+        self.lag_frame
+    }
+
+    #[inline]
+    fn get_lag_frame_count(&self) -> u64 {
+        self.lag_frame_count
+    }
+
+    #[inline]
+    fn reset_lag_frame_count(&mut self) {
+        self.lag_frame_count = 0;
     }
 
     #[inline]
@@ -691,12 +1823,188 @@ impl RunnableInstanceFunctions for RunningGameboy {
         }
     }
 
+    #[inline]
     fn set_memory_callbacks_enabled(&mut self, enabled: bool) {
+        self.set_memory_hooks(if enabled { MemoryHooks::ALL } else { MemoryHooks::NONE })
+    }
+
+    fn set_memory_hooks(&mut self, hooks: MemoryHooks) {
+        self.memory_hooks = hooks;
+        self.update_memory_hooks();
+    }
+
+    #[inline]
+    fn get_memory_hooks(&self) -> MemoryHooks {
+        self.memory_hooks
+    }
+
+    fn add_watch(&mut self, watch: Watch) -> WatchId {
+        let id = self.watches.add(watch);
+        self.update_memory_hooks();
+        id
+    }
+
+    fn remove_watch(&mut self, id: WatchId) -> bool {
+        let removed = self.watches.remove(id);
+        self.update_memory_hooks();
+        removed
+    }
+
+    fn clear_watches(&mut self) {
+        self.watches.clear();
+        self.update_memory_hooks();
+    }
+
+    #[inline]
+    fn get_watch_hits(&self) -> &[WatchHit] {
+        self.watches.get_hits()
+    }
+
+    fn get_elapsed_ticks(&self) -> u64 {
+        // While running, the current step hasn't been added yet.
+        match self.is_running {
+            true => self.elapsed_ticks + unsafe { safeboy_get_cycles_since_run(self.gb) } as u64,
+            false => self.elapsed_ticks
+        }
+    }
+
+    fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+        self.update_memory_hooks();
+    }
+
+    #[inline]
+    fn get_tracer(&self) -> Option<&Tracer> {
+        self.tracer.as_ref()
+    }
+
+    #[inline]
+    fn get_tracer_mut(&mut self) -> Option<&mut Tracer> {
+        self.tracer.as_mut()
+    }
+
+    fn set_code_data_logger(&mut self, logger: Option<CodeDataLogger>) {
+        self.code_data_logger = logger;
+        if let Some(logger) = self.code_data_logger.as_mut() {
+            logger.resize(unsafe { direct_access(self.gb, DirectAccessRegion::ROM) }.data.len());
+        }
+        self.update_memory_hooks();
+    }
+
+    #[inline]
+    fn get_code_data_logger(&self) -> Option<&CodeDataLogger> {
+        self.code_data_logger.as_ref()
+    }
+
+    #[inline]
+    fn get_code_data_logger_mut(&mut self) -> Option<&mut CodeDataLogger> {
+        self.code_data_logger.as_mut()
+    }
+
+    fn set_call_stack(&mut self, call_stack: Option<CallStack>) {
+        self.call_stack = call_stack;
+        self.update_memory_hooks();
+    }
+
+    #[inline]
+    fn get_call_stack(&self) -> Option<&CallStack> {
+        self.call_stack.as_ref()
+    }
+
+    fn get_backtrace(&self) -> Vec<BacktraceFrame> {
+        let Some(call_stack) = self.call_stack.as_ref() else { return Vec::new() };
+
+        let pc = self.get_registers().pc;
+        let bank = self.get_bank_for_address(pc);
+        let current = BacktraceFrame { address: pc, bank, name: self.describe_banked_address(pc, bank) };
+
+        let (instruction, _, sp, stack_top, stack_top_bank) = self.get_call_stack_state(pc);
+        let frames = call_stack.preview(instruction, bank, sp, stack_top, stack_top_bank);
+
+        let calls = frames.iter().rev().map(|frame| BacktraceFrame {
+            address: frame.call_address,
+            bank: frame.call_bank,
+            name: self.describe_banked_address(frame.call_address, frame.call_bank)
+        });
+        core::iter::once(current).chain(calls).collect()
+    }
+
+    fn set_profiler(&mut self, profiler: Option<Profiler>) {
+        self.profiler = profiler;
+        self.update_memory_hooks();
+    }
+
+    #[inline]
+    fn get_profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    #[inline]
+    fn get_profiler_mut(&mut self) -> Option<&mut Profiler> {
+        self.profiler.as_mut()
+    }
+
+    fn set_ram_watch_list(&mut self, ram_watches: Option<RamWatchList>) {
+        self.ram_watches = ram_watches;
+    }
+
+    fn get_ram_watch_list(&self) -> Option<&RamWatchList> {
+        self.ram_watches.as_ref()
+    }
+
+    fn get_ram_watch_list_mut(&mut self) -> Option<&mut RamWatchList> {
+        self.ram_watches.as_mut()
+    }
+
+    fn set_achievement_set(&mut self, achievements: Option<AchievementSet>) {
+        self.achievements = achievements;
+    }
+
+    fn get_achievement_set(&self) -> Option<&AchievementSet> {
+        self.achievements.as_ref()
+    }
+
+    fn get_achievement_set_mut(&mut self) -> Option<&mut AchievementSet> {
+        self.achievements.as_mut()
+    }
+
+    fn set_autosplitter(&mut self, autosplitter: Option<Autosplitter>) {
+        self.autosplitter = autosplitter;
+    }
+
+    fn get_autosplitter(&self) -> Option<&Autosplitter> {
+        self.autosplitter.as_ref()
+    }
+
+    fn get_autosplitter_mut(&mut self) -> Option<&mut Autosplitter> {
+        self.autosplitter.as_mut()
+    }
+
+    fn set_symbols(&mut self, symbols: Option<SymbolTable>) {
+        #[cfg(feature = "debugger")]
         unsafe {
-            GB_set_read_memory_callback(self.gb, enabled.then_some(read_memory_callback));
-            GB_set_write_memory_callback(self.gb, enabled.then_some(write_memory_callback));
-            GB_set_execution_callback(self.gb, enabled.then_some(execution_callback));
+            GB_debugger_clear_symbols(self.gb);
+            for symbol in symbols.iter().flat_map(|s| s.get_symbols()) {
+                let name = debugger::to_c_string(&symbol.name);
+                safeboy_debugger_add_symbol(self.gb, symbol.bank, symbol.address, name.as_ptr() as *const c_char);
+            }
         }
+        self.symbols = symbols;
+    }
+
+    #[inline]
+    fn get_symbols(&self) -> Option<&SymbolTable> {
+        self.symbols.as_ref()
+    }
+
+    fn get_name_for_address(&self, address: u16) -> Option<&str> {
+        let bank = self.get_symbol_bank_for_address(address);
+        self.symbols.as_ref()?.name_for_address(bank, address)
+    }
+
+    fn describe_address(&self, address: u16) -> Option<String> {
+        let bank = self.get_symbol_bank_for_address(address);
+        self.symbols.as_ref()?.describe(bank, address)
     }
 
     #[inline]
@@ -739,12 +2047,96 @@ impl RunnableInstanceFunctions for RunningGameboy {
         }
     }
 
+    fn get_memory_map(&self) -> MemoryMap {
+        MemoryMap {
+            rom0_bank: self.get_region_bank(MemoryRegion::Rom0),
+            romx_bank: self.get_region_bank(MemoryRegion::RomX),
+            vram_bank: self.get_region_bank(MemoryRegion::Vram),
+            sram_bank: self.get_region_bank(MemoryRegion::Sram),
+            wramx_bank: self.get_region_bank(MemoryRegion::WramX)
+        }
+    }
+
     fn direct_access_mut(&'_ mut self, access: DirectAccessRegion) -> DirectAccessDataMut<'_> {
         unsafe {
             direct_access(self.gb, access)
         }
     }
 
+    #[inline]
+    fn peek(&self, address: u16) -> u8 {
+        // SameBoy calls the read callback here, too, but peeking should not trigger any hooks.
+        let was_peeking = self.peeking.replace(true);
+        let value = unsafe { GB_safe_read_memory(self.gb, address) };
+        self.peeking.set(was_peeking);
+        value
+    }
+
+    #[inline]
+    fn read(&mut self, address: u16) -> u8 {
+        unsafe { GB_read_memory(self.gb, address) }
+    }
+
+    #[inline]
+    fn write(&mut self, address: u16, value: u8) {
+        unsafe { GB_write_memory(self.gb, address, value) }
+    }
+
+    fn peek_range(&self, address: u16, buffer: &mut [u8]) {
+        let mut address = address;
+        for byte in buffer {
+            *byte = self.peek(address);
+            address = address.wrapping_add(1);
+        }
+    }
+
+    fn read_range(&mut self, address: u16, buffer: &mut [u8]) {
+        let mut address = address;
+        for byte in buffer {
+            *byte = self.read(address);
+            address = address.wrapping_add(1);
+        }
+    }
+
+    fn write_range(&mut self, address: u16, data: &[u8]) {
+        let mut address = address;
+        for &byte in data {
+            self.write(address, byte);
+            address = address.wrapping_add(1);
+        }
+    }
+
+    #[inline]
+    fn peek_u16(&self, address: u16) -> u16 {
+        u16::from_le_bytes([self.peek(address), self.peek(address.wrapping_add(1))])
+    }
+
+    #[inline]
+    fn read_u16(&mut self, address: u16) -> u16 {
+        let low = self.read(address);
+        let high = self.read(address.wrapping_add(1));
+        u16::from_le_bytes([low, high])
+    }
+
+    fn decode_instruction_at(&self, address: u16) -> Instruction {
+        let mut bytes = [0u8; 3];
+        self.peek_range(address, &mut bytes);
+        decode_instruction(&bytes, address).expect("instructions are at most 3 bytes long")
+    }
+
+    #[cfg(feature = "debugger")]
+    fn disassemble(&mut self, address: u16, count: u16) -> Vec<String> {
+        // The disassembler reads memory, but it should not trigger any hooks.
+        let was_peeking = self.peeking.replace(true);
+        let output = self.capture_log(|gb| unsafe { GB_cpu_disassemble(gb, address, count) });
+        self.peeking.set(was_peeking);
+
+        output.lines()
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
     #[inline]
     fn is_cgb(&self) -> bool {
         unsafe { GB_is_cgb(self.gb) }
@@ -870,7 +2262,7 @@ impl RunnableInstanceFunctions for RunningGameboy {
 
     fn set_registers(&mut self, registers: &Registers) {
         let registers_out = unsafe { &mut *GB_get_registers(self.gb) };
-        registers_out.__bindgen_anon_1.af = registers.af;
+        registers_out.__bindgen_anon_1.af = registers.af & 0xFFF0;
         registers_out.__bindgen_anon_1.bc = registers.bc;
         registers_out.__bindgen_anon_1.de = registers.de;
         registers_out.__bindgen_anon_1.hl = registers.hl;
@@ -878,6 +2270,49 @@ impl RunnableInstanceFunctions for RunningGameboy {
         registers_out.__bindgen_anon_1.pc = registers.pc;
     }
 
+    fn get_cpu_state(&self) -> CpuState {
+        unsafe {
+            CpuState {
+                registers: self.get_registers(),
+                ime: safeboy_get_ime(self.gb),
+                interrupt_enable: direct_access(self.gb, DirectAccessRegion::IE).data[0],
+                interrupt_flag: direct_access(self.gb, DirectAccessRegion::IO).data[0x0F] & 0x1F,
+                halted: safeboy_is_halted(self.gb),
+                stopped: safeboy_is_stopped(self.gb),
+                double_speed: safeboy_is_double_speed(self.gb)
+            }
+        }
+    }
+
+    fn set_cpu_state(&mut self, state: &CpuState) {
+        self.set_registers(&state.registers);
+        unsafe {
+            safeboy_set_ime(self.gb, state.ime);
+            direct_access(self.gb, DirectAccessRegion::IE).data[0] = state.interrupt_enable;
+            direct_access(self.gb, DirectAccessRegion::IO).data[0x0F] = state.interrupt_flag & 0x1F;
+        }
+    }
+
+    fn interrupts(&self) -> InterruptState {
+        InterruptState {
+            ime: unsafe { safeboy_get_ime(self.gb) },
+            enabled: InterruptFlags::from_byte(self.direct_access(DirectAccessRegion::IE).data[0]),
+            requested: InterruptFlags::from_byte(self.direct_access(DirectAccessRegion::IO).data[0x0F])
+        }
+    }
+
+    fn set_interrupts(&mut self, state: &InterruptState) {
+        unsafe { safeboy_set_ime(self.gb, state.ime) };
+        // The upper bits of IE are writable, so preserve them.
+        let ie = &mut self.direct_access_mut(DirectAccessRegion::IE).data[0];
+        *ie = (*ie & 0xE0) | state.enabled.to_byte();
+        self.direct_access_mut(DirectAccessRegion::IO).data[0x0F] = state.requested.to_byte();
+    }
+
+    fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.direct_access_mut(DirectAccessRegion::IO).data[0x0F] |= interrupt.get_mask();
+    }
+
     #[inline]
     fn set_rewind_length(&mut self, seconds: f64) {
         unsafe { GB_set_rewind_length(self.gb, seconds) }
@@ -889,6 +2324,7 @@ impl RunnableInstanceFunctions for RunningGameboy {
     }
 
     fn set_turbo_mode(&mut self, mode: TurboMode) {
+        self.turbo_mode = mode;
         match mode {
             TurboMode::Disabled => unsafe { GB_set_turbo_mode(self.gb, false, true) },
             TurboMode::EnabledFrameSkipped => unsafe { GB_set_turbo_mode(self.gb, true, false) },
@@ -908,12 +2344,19 @@ impl RunnableInstanceFunctions for RunningGameboy {
     fn get_rom_title(&self) -> &str {
         self.rom_title.as_str()
     }
+
+    #[inline]
+    fn get_rom_crc32(&self) -> u32 {
+        unsafe { GB_get_rom_crc32(self.gb) }
+    }
 }
 
 /// Represents all registers packed into 16-bit values.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Registers {
     /// High = A, Low = flags
+    ///
+    /// The lower four bits of the flags are always zero on hardware.
     pub af: u16,
 
     /// High = B, Low = C
@@ -932,6 +2375,145 @@ pub struct Registers {
     pub pc: u16
 }
 
+impl Registers {
+    /// Get the A register.
+    pub const fn get_a(&self) -> u8 { (self.af >> 8) as u8 }
+
+    /// Get the F (flags) register.
+    pub const fn get_f(&self) -> u8 { self.af as u8 }
+
+    /// Get the B register.
+    pub const fn get_b(&self) -> u8 { (self.bc >> 8) as u8 }
+
+    /// Get the C register.
+    pub const fn get_c(&self) -> u8 { self.bc as u8 }
+
+    /// Get the D register.
+    pub const fn get_d(&self) -> u8 { (self.de >> 8) as u8 }
+
+    /// Get the E register.
+    pub const fn get_e(&self) -> u8 { self.de as u8 }
+
+    /// Get the H register.
+    pub const fn get_h(&self) -> u8 { (self.hl >> 8) as u8 }
+
+    /// Get the L register.
+    pub const fn get_l(&self) -> u8 { self.hl as u8 }
+
+    /// Set the A register.
+    pub const fn set_a(&mut self, a: u8) { self.af = set_high(self.af, a) }
+
+    /// Set the F (flags) register.
+    ///
+    /// The lower four bits are cleared.
+    pub const fn set_f(&mut self, f: u8) { self.af = set_low(self.af, f & 0xF0) }
+
+    /// Set the B register.
+    pub const fn set_b(&mut self, b: u8) { self.bc = set_high(self.bc, b) }
+
+    /// Set the C register.
+    pub const fn set_c(&mut self, c: u8) { self.bc = set_low(self.bc, c) }
+
+    /// Set the D register.
+    pub const fn set_d(&mut self, d: u8) { self.de = set_high(self.de, d) }
+
+    /// Set the E register.
+    pub const fn set_e(&mut self, e: u8) { self.de = set_low(self.de, e) }
+
+    /// Set the H register.
+    pub const fn set_h(&mut self, h: u8) { self.hl = set_high(self.hl, h) }
+
+    /// Set the L register.
+    pub const fn set_l(&mut self, l: u8) { self.hl = set_low(self.hl, l) }
+
+    /// Get the decoded flags.
+    pub const fn get_flags(&self) -> CpuFlags {
+        CpuFlags::from_bits(self.get_f())
+    }
+
+    /// Set the flags.
+    pub const fn set_flags(&mut self, flags: CpuFlags) {
+        self.set_f(flags.to_bits())
+    }
+}
+
+const fn set_high(pair: u16, value: u8) -> u16 {
+    (pair & 0x00FF) | ((value as u16) << 8)
+}
+
+const fn set_low(pair: u16, value: u8) -> u16 {
+    (pair & 0xFF00) | (value as u16)
+}
+
+/// Decoded CPU flags (the F register).
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct CpuFlags {
+    /// Zero flag (Z, bit 7)
+    pub zero: bool,
+
+    /// Subtraction flag (N, bit 6)
+    pub subtract: bool,
+
+    /// Half carry flag (H, bit 5)
+    pub half_carry: bool,
+
+    /// Carry flag (C, bit 4)
+    pub carry: bool
+}
+
+impl CpuFlags {
+    /// Decode the flags from the F register.
+    pub const fn from_bits(f: u8) -> CpuFlags {
+        CpuFlags {
+            zero: (f & 0x80) != 0,
+            subtract: (f & 0x40) != 0,
+            half_carry: (f & 0x20) != 0,
+            carry: (f & 0x10) != 0
+        }
+    }
+
+    /// Encode the flags into the F register.
+    pub const fn to_bits(self) -> u8 {
+        ((self.zero as u8) << 7) | ((self.subtract as u8) << 6) | ((self.half_carry as u8) << 5) | ((self.carry as u8) << 4)
+    }
+}
+
+/// Represents the registers along with the interrupt and power state of the CPU.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CpuState {
+    /// CPU registers
+    pub registers: Registers,
+
+    /// Interrupt master enable
+    pub ime: bool,
+
+    /// Enabled interrupts (IE, `$FFFF`)
+    pub interrupt_enable: u8,
+
+    /// Requested interrupts (IF, `$FF0F`)
+    ///
+    /// Only the lower five bits are used.
+    pub interrupt_flag: u8,
+
+    /// The CPU is halted by the `HALT` instruction (read-only).
+    pub halted: bool,
+
+    /// The CPU is stopped by the `STOP` instruction (read-only).
+    pub stopped: bool,
+
+    /// The CPU is running in CGB double speed mode (read-only).
+    pub double_speed: bool
+}
+
+impl CpuState {
+    /// Get interrupts that are both enabled and requested (IE & IF).
+    ///
+    /// These will be serviced once IME is set (or will wake the CPU from HALT regardless of IME).
+    pub const fn get_pending_interrupts(&self) -> u8 {
+        self.interrupt_enable & self.interrupt_flag & 0x1F
+    }
+}
+
 /// Input buttons mapped to a Game Boy.
 #[derive(Copy, Clone, PartialEq, Debug)]
 #[repr(u32)]
@@ -975,6 +2557,72 @@ impl ShlAssign<InputButton> for u8 {
     }
 }
 
+/// Set of memory and execution hooks.
+///
+/// Hooks can be combined with `|`, for example `MemoryHooks::READ | MemoryHooks::WRITE`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MemoryHooks(u8);
+
+impl MemoryHooks {
+    /// No hooks.
+    pub const NONE: MemoryHooks = MemoryHooks(0);
+
+    /// Call [`read_memory`](GameboyCallbacks::read_memory) when memory is read.
+    pub const READ: MemoryHooks = MemoryHooks(1 << 0);
+
+    /// Call [`write_memory`](GameboyCallbacks::write_memory) when memory is written.
+    pub const WRITE: MemoryHooks = MemoryHooks(1 << 1);
+
+    /// Call [`executing_instruction`](GameboyCallbacks::executing_instruction) when an instruction
+    /// is executed.
+    pub const EXECUTE: MemoryHooks = MemoryHooks(1 << 2);
+
+    /// All hooks.
+    pub const ALL: MemoryHooks = MemoryHooks(Self::READ.0 | Self::WRITE.0 | Self::EXECUTE.0);
+
+    /// Returns true if all hooks in `other` are also in `self`.
+    pub const fn contains(self, other: MemoryHooks) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Get all hooks in either `self` or `other`.
+    pub const fn union(self, other: MemoryHooks) -> MemoryHooks {
+        MemoryHooks(self.0 | other.0)
+    }
+
+    /// Returns true if there are no hooks.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Get the raw bits.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl BitOr for MemoryHooks {
+    type Output = MemoryHooks;
+
+    fn bitor(self, rhs: MemoryHooks) -> Self::Output {
+        MemoryHooks(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for MemoryHooks {
+    fn bitor_assign(&mut self, rhs: MemoryHooks) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for MemoryHooks {
+    type Output = MemoryHooks;
+
+    fn bitand(self, rhs: MemoryHooks) -> Self::Output {
+        MemoryHooks(self.0 & rhs.0)
+    }
+}
+
 /// SNES/SFC border mode.
 #[derive(Copy, Clone, PartialEq, Debug)]
 #[repr(u32)]
@@ -1110,6 +2758,7 @@ impl MonochromePalette {
 }
 
 /// Specifies an RTC mode for timing the real-time clock.
+#[derive(Copy, Clone, PartialEq, Debug)]
 #[repr(u32)]
 pub enum RtcMode {
     /// One second in the emulator is one second on the host.
@@ -1143,7 +2792,14 @@ unsafe fn direct_access(gb: *mut GB_gameboy_t, access: DirectAccessRegion) -> Di
     let mut size = 0usize;
     let data = unsafe {
         let ptr = GB_get_direct_access(gb, access as _, &mut size, &mut bank) as *mut u8;
-        core::slice::from_raw_parts_mut(ptr, size)
+
+        // Regions that don't exist (e.g. cartridge RAM on a cartridge without any) are null.
+        if ptr.is_null() {
+            &mut []
+        }
+        else {
+            core::slice::from_raw_parts_mut(ptr, size)
+        }
     };
     DirectAccessDataMut {
         data, bank
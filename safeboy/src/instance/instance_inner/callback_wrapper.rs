@@ -1,9 +1,10 @@
 #![expect(unsafe_op_in_unsafe_fn)]
 
-use crate::{BootRomType, GameboyCallbacks, RunningGameboy, LogAttributes, PrinterPage, VBlankType};
+use crate::{BootRomType, GameboyCallbacks, MemoryHooks, RunnableInstanceFunctions, RunningGameboy, LogAttributes, PrinterPage, VBlankType};
 use alloc::vec;
 use alloc::vec::Vec;
 use core::ffi::{c_char, CStr};
+use core::ops::Range;
 use core::ptr::null_mut;
 use sameboy_sys::{GB_boot_rom_t, GB_gameboy_s, GB_gameboy_t, GB_get_user_data, GB_log_attributes_t, GB_log_attributes_t_GB_LOG_BOLD, GB_log_attributes_t_GB_LOG_DASHED_UNDERLINE, GB_sample_t, GB_vblank_type_t};
 
@@ -25,15 +26,54 @@ pub(crate) unsafe extern "C" fn rgb_encode_callback(gb: *mut GB_gameboy_s, r: u8
     (get_instance(gb).rgb_encoder)(r, g, b)
 }
 
+unsafe fn dispatch_watch_hits(gb: *mut GB_gameboy_s, hits: Range<usize>) {
+    for i in hits {
+        let hit = get_instance(gb).get_watch_hits()[i];
+        get_instance_callback(gb).watch_triggered(get_instance(gb), hit);
+    }
+}
+
 pub unsafe extern "C" fn read_memory_callback(gb: *mut GB_gameboy_t, address: u16, original_data: u8) -> u8 {
+    // Peeking must not have any side effects.
+    if get_instance(gb).peeking.get() {
+        return original_data
+    }
+    get_instance(gb).log_data(address);
+
+    let hits = get_instance(gb).check_watches(MemoryHooks::READ, address, original_data);
+    dispatch_watch_hits(gb, hits);
+
+    if !get_instance(gb).memory_hooks.contains(MemoryHooks::READ) {
+        return original_data
+    }
     get_instance_callback(gb).read_memory(get_instance(gb), address, original_data)
 }
 
 pub unsafe extern "C" fn write_memory_callback(gb: *mut GB_gameboy_t, address: u16, data: u8) -> bool {
+    let hits = get_instance(gb).check_watches(MemoryHooks::WRITE, address, data);
+    dispatch_watch_hits(gb, hits);
+
+    if !get_instance(gb).memory_hooks.contains(MemoryHooks::WRITE) {
+        return true
+    }
     get_instance_callback(gb).write_memory(get_instance(gb), address, data)
 }
 
 pub unsafe extern "C" fn execution_callback(gb: *mut GB_gameboy_t, pc: u16, opcode: u8) {
+    get_instance(gb).instructions_executed += 1;
+    get_instance(gb).trace_instruction(pc, opcode);
+    get_instance(gb).log_code(pc, opcode);
+    get_instance(gb).profile_instruction(pc);
+    if let Some((interrupt, return_address)) = get_instance(gb).track_call_stack(pc) {
+        get_instance_callback(gb).interrupt_serviced(get_instance(gb), interrupt, return_address);
+    }
+
+    let hits = get_instance(gb).check_watches(MemoryHooks::EXECUTE, pc, opcode);
+    dispatch_watch_hits(gb, hits);
+
+    if !get_instance(gb).memory_hooks.contains(MemoryHooks::EXECUTE) {
+        return
+    }
     get_instance_callback(gb).executing_instruction(get_instance(gb), pc, opcode);
 }
 
@@ -54,11 +94,27 @@ pub unsafe extern "C" fn vblank_callback(gb: *mut GB_gameboy_t, vblank_type: GB_
         sameboy_sys::GB_vblank_type_t_GB_VBLANK_TYPE_REPEAT => VBlankType::Repeat,
         unknown => panic!("Unknown vblank type {unknown}")
     };
+    get_instance(gb).vblank_occurred = true;
+    get_instance(gb).update_lag_frame();
+    get_instance(gb).end_movie_frame();
+    get_instance(gb).end_input_frame();
+    get_instance(gb).end_profiler_frame();
+    for change in get_instance(gb).update_ram_watches() {
+        get_instance_callback(gb).ram_watch_changed(get_instance(gb), change);
+    }
+    for id in get_instance(gb).update_achievements() {
+        get_instance_callback(gb).achievement_triggered(get_instance(gb), id);
+    }
+    for event in get_instance(gb).update_autosplitter() {
+        get_instance_callback(gb).autosplitter_event(get_instance(gb), event);
+    }
     get_instance_callback(gb).vblank(get_instance(gb), vblank_type);
 }
 
 pub unsafe extern "C" fn update_input_hint_callback(gb: *mut GB_gameboy_t) {
     get_instance_callback(gb).update_input_hint(get_instance(gb));
+    get_instance(gb).update_key_mask();
+    get_instance(gb).latch_movie_input();
 }
 
 pub unsafe extern "C" fn load_boot_rom_callback(gb: *mut GB_gameboy_t, boot_rom_type: GB_boot_rom_t) {
@@ -95,6 +151,12 @@ pub unsafe extern "C" fn apu_sample_callback(gb: *mut GB_gameboy_t, samples: *mu
 pub unsafe extern "C" fn log_callback(gb: *mut GB_gameboy_t, string: *const c_char, attributes: GB_log_attributes_t) {
     let string = CStr::from_ptr(string).to_string_lossy();
 
+    #[cfg(any(feature = "debugger", feature = "cheat-search"))]
+    if let Some(capture) = get_instance(gb).log_capture.as_mut() {
+        capture.push_str(&string);
+        return
+    }
+
     let parameters = LogAttributes {
         bold: (attributes & GB_log_attributes_t_GB_LOG_BOLD) != 0,
         dashed_underline: (attributes & GB_log_attributes_t_GB_LOG_DASHED_UNDERLINE) != 0,
@@ -104,6 +166,11 @@ pub unsafe extern "C" fn log_callback(gb: *mut GB_gameboy_t, string: *const c_ch
     get_instance_callback(gb).console_log(get_instance(gb), string.as_ref(), parameters);
 }
 
+#[cfg(feature = "debugger")]
+pub unsafe extern "C" fn debugger_reload_callback(gb: *mut GB_gameboy_t) {
+    get_instance_callback(gb).debugger_reload(get_instance(gb));
+}
+
 unsafe extern "C" {
     fn malloc(size: usize) -> *mut u8;
 }
@@ -123,7 +190,7 @@ pub unsafe extern "C" fn input_callback(gb: *mut GB_gameboy_t) -> *mut c_char {
     }
 
     let data_slice = core::slice::from_raw_parts_mut(data, len);
-    let (before, after) = data_slice.split_at_mut(len);
+    let (before, after) = data_slice.split_at_mut(input_bytes.len());
     before.copy_from_slice(input_bytes);
     after.fill(0);
 
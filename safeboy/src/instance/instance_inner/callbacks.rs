@@ -1,6 +1,6 @@
 use alloc::borrow::Cow;
 use alloc::vec::Vec;
-use crate::RunningGameboy;
+use crate::{AchievementId, AutosplitterEvent, Interrupt, RamWatchChange, RunningGameboy, WatchHit};
 
 /// Callbacks you can implement when using the emulator.
 #[allow(unused_variables)]
@@ -11,8 +11,9 @@ pub trait GameboyCallbacks {
     ///
     /// # Remarks
     ///
-    /// This callback is disabled by default. Use the `enable_memory_callbacks` function to enable
-    /// it.
+    /// This callback is disabled by default. Enable it with
+    /// [`MemoryHooks::READ`](crate::MemoryHooks::READ) (see
+    /// [`set_memory_hooks`](crate::RunnableInstanceFunctions::set_memory_hooks)).
     fn read_memory(&mut self, instance: &mut RunningGameboy, address: u16, original_data: u8) -> u8 {
         original_data
     }
@@ -23,8 +24,9 @@ pub trait GameboyCallbacks {
     ///
     /// # Remarks
     ///
-    /// This callback is disabled by default. Use the `enable_memory_callbacks` function to enable
-    /// it.
+    /// This callback is disabled by default. Enable it with
+    /// [`MemoryHooks::WRITE`](crate::MemoryHooks::WRITE) (see
+    /// [`set_memory_hooks`](crate::RunnableInstanceFunctions::set_memory_hooks)).
     fn write_memory(&mut self, instance: &mut RunningGameboy, address: u16, data: u8) -> bool {
         true
     }
@@ -33,10 +35,44 @@ pub trait GameboyCallbacks {
     ///
     /// # Remarks
     ///
-    /// This callback is disabled by default. Use the `enable_memory_callbacks` function to enable
-    /// it.
+    /// This callback is disabled by default. Enable it with
+    /// [`MemoryHooks::EXECUTE`](crate::MemoryHooks::EXECUTE) (see
+    /// [`set_memory_hooks`](crate::RunnableInstanceFunctions::set_memory_hooks)).
     fn executing_instruction(&mut self, instance: &mut RunningGameboy, address: u16, opcode: u8) {}
 
+    /// A breakpoint or watchpoint was triggered.
+    ///
+    /// This is called before the corresponding memory callback (if enabled). See
+    /// [`add_watch`](crate::RunnableInstanceFunctions::add_watch).
+    fn watch_triggered(&mut self, instance: &mut RunningGameboy, hit: WatchHit) {}
+
+    /// An interrupt was serviced, and the CPU is about to execute its handler.
+    ///
+    /// `return_address` is the address of the instruction that was interrupted. This is only called
+    /// while a call stack tracker is set (see
+    /// [`set_call_stack`](crate::RunnableInstanceFunctions::set_call_stack)).
+    fn interrupt_serviced(&mut self, instance: &mut RunningGameboy, interrupt: Interrupt, return_address: u16) {}
+
+    /// The value of a RAM watch changed.
+    ///
+    /// This is called when vblank is reached, before [`vblank`](GameboyCallbacks::vblank). See
+    /// [`set_ram_watch_list`](crate::RunnableInstanceFunctions::set_ram_watch_list).
+    fn ram_watch_changed(&mut self, instance: &mut RunningGameboy, change: RamWatchChange) {}
+
+    /// An achievement unlocked.
+    ///
+    /// This is called when vblank is reached, after
+    /// [`ram_watch_changed`](GameboyCallbacks::ram_watch_changed). See
+    /// [`set_achievement_set`](crate::RunnableInstanceFunctions::set_achievement_set).
+    fn achievement_triggered(&mut self, instance: &mut RunningGameboy, id: AchievementId) {}
+
+    /// The autosplitter emitted an event.
+    ///
+    /// This is called when vblank is reached, after
+    /// [`achievement_triggered`](GameboyCallbacks::achievement_triggered). See
+    /// [`set_autosplitter`](crate::RunnableInstanceFunctions::set_autosplitter).
+    fn autosplitter_event(&mut self, instance: &mut RunningGameboy, event: AutosplitterEvent) {}
+
     /// Vertical blank was reached.
     ///
     /// Generally this means a full frame was rendered, thus it is safe to read the pixel buffer.
@@ -86,6 +122,12 @@ pub trait GameboyCallbacks {
     /// Text input is being requested.
     fn console_input(&mut self, instance: &mut RunningGameboy) -> Option<Cow<'static, str>> { None }
 
+    /// The debugger's `reset reload` command was used.
+    ///
+    /// The ROM (and boot ROM, if needed) should be reloaded from disk.
+    #[cfg(feature = "debugger")]
+    fn debugger_reload(&mut self, instance: &mut RunningGameboy) {}
+
     /// An audio sample has been generated by the APU.
     fn apu_sample(&mut self, instance: &mut RunningGameboy, left: i16, right: i16) {}
 }
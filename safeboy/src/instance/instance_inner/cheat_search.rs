@@ -0,0 +1,194 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_char;
+use sameboy_sys::{GB_cheat_search_data_type_t, GB_cheat_search_data_type_t_GB_CHEAT_SEARCH_DATA_TYPE_16BIT, GB_cheat_search_data_type_t_GB_CHEAT_SEARCH_DATA_TYPE_16BIT_BE, GB_cheat_search_data_type_t_GB_CHEAT_SEARCH_DATA_TYPE_8BIT, GB_cheat_search_filter, GB_cheat_search_get_results, GB_cheat_search_reset, GB_cheat_search_result_count, GB_cheat_search_result_t};
+use crate::{Cheat, Gameboy, RunningGameboy};
+
+/// Handle to SameBoy's RAM search, for finding the addresses of in-game values.
+///
+/// WRAM, cartridge RAM, and HRAM are searched. Every call to [`CheatSearch::filter`] compares the
+/// current value of each remaining candidate with its value from the previous call (or 0 on the
+/// first call), and discards candidates that do not match. If no candidates remain, the next call
+/// starts a new search.
+///
+/// The search is discarded when the emulator is reset.
+pub struct CheatSearch<'a> {
+    instance: &'a mut RunningGameboy
+}
+
+impl Gameboy {
+    /// Get a handle to the RAM search.
+    ///
+    /// This is not available on [`RunningGameboy`], as SameBoy does not allow filtering while the
+    /// emulator is running.
+    pub fn cheat_search(&mut self) -> CheatSearch<'_> {
+        // SAFETY: The handle only borrows the instance; it can't be used to move it.
+        let instance = unsafe { self.inner.as_mut().get_unchecked_mut() };
+        CheatSearch { instance }
+    }
+}
+
+impl CheatSearch<'_> {
+    /// Discard all results, starting a new search.
+    pub fn reset(&mut self) {
+        unsafe { GB_cheat_search_reset(self.instance.gb) }
+    }
+
+    /// Discard candidates whose values do not match `filter`.
+    ///
+    /// `width` must be the same for every call in a search.
+    ///
+    /// Returns `Err` if the filter is an invalid expression.
+    pub fn filter(&mut self, filter: &CheatSearchFilter, width: CheatSearchDataWidth) -> Result<(), CheatSearchError> {
+        let mut expression: Vec<u8> = filter.to_expression().bytes().take_while(|b| *b != 0).collect();
+        expression.push(0);
+
+        let mut succeeded = false;
+        let message = self.instance.capture_log(|gb| unsafe {
+            succeeded = GB_cheat_search_filter(gb, expression.as_ptr() as *const c_char, width.into());
+        });
+
+        if !succeeded {
+            return Err(CheatSearchError { message: String::from(message.trim_end()) })
+        }
+        Ok(())
+    }
+
+    /// Get the number of remaining candidates.
+    pub fn get_result_count(&self) -> usize {
+        unsafe { GB_cheat_search_result_count(self.instance.gb) }
+    }
+
+    /// Get all remaining candidates.
+    ///
+    /// The value of each candidate is its value as of the last call to [`CheatSearch::filter`].
+    pub fn get_results(&self) -> Vec<CheatSearchResult> {
+        let count = self.get_result_count();
+        let mut results = Vec::with_capacity(count);
+        unsafe {
+            GB_cheat_search_get_results(self.instance.gb, results.as_mut_ptr());
+            results.set_len(count);
+        }
+        results
+            .into_iter()
+            .map(|r: GB_cheat_search_result_t| CheatSearchResult { address: r.addr, bank: r.bank, value: r.value })
+            .collect()
+    }
+}
+
+/// Width of the values compared by a RAM search.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CheatSearchDataWidth {
+    /// 8-bit values
+    U8,
+
+    /// 16-bit little endian values
+    U16,
+
+    /// 16-bit big endian values
+    U16BigEndian
+}
+
+impl From<CheatSearchDataWidth> for GB_cheat_search_data_type_t {
+    fn from(value: CheatSearchDataWidth) -> Self {
+        match value {
+            CheatSearchDataWidth::U8 => GB_cheat_search_data_type_t_GB_CHEAT_SEARCH_DATA_TYPE_8BIT,
+            CheatSearchDataWidth::U16 => GB_cheat_search_data_type_t_GB_CHEAT_SEARCH_DATA_TYPE_16BIT,
+            CheatSearchDataWidth::U16BigEndian => GB_cheat_search_data_type_t_GB_CHEAT_SEARCH_DATA_TYPE_16BIT_BE
+        }
+    }
+}
+
+/// Condition a candidate's value must meet to remain in a RAM search.
+#[derive(Clone, PartialEq, Debug)]
+pub enum CheatSearchFilter {
+    /// The value equals the given value.
+    Equal(u16),
+
+    /// The value does not equal the given value.
+    NotEqual(u16),
+
+    /// The value is greater than the given value.
+    GreaterThan(u16),
+
+    /// The value is less than the given value.
+    LessThan(u16),
+
+    /// The value changed since the last filter.
+    Changed,
+
+    /// The value did not change since the last filter.
+    Unchanged,
+
+    /// The value increased since the last filter.
+    Increased,
+
+    /// The value decreased since the last filter.
+    Decreased,
+
+    /// The value increased by exactly the given amount since the last filter.
+    IncreasedBy(u16),
+
+    /// The value decreased by exactly the given amount since the last filter.
+    DecreasedBy(u16),
+
+    /// A debugger expression, where `old` is the value as of the last filter and `new` is the
+    /// current value (e.g. `"new == old + 1"`).
+    Expression(String)
+}
+
+impl CheatSearchFilter {
+    /// Get the debugger expression for this filter.
+    pub fn to_expression(&self) -> String {
+        match self {
+            CheatSearchFilter::Equal(v) => format!("new == ${v:X}"),
+            CheatSearchFilter::NotEqual(v) => format!("new != ${v:X}"),
+            CheatSearchFilter::GreaterThan(v) => format!("new > ${v:X}"),
+            CheatSearchFilter::LessThan(v) => format!("new < ${v:X}"),
+            CheatSearchFilter::Changed => String::from("new != old"),
+            CheatSearchFilter::Unchanged => String::from("new == old"),
+            CheatSearchFilter::Increased => String::from("new > old"),
+            CheatSearchFilter::Decreased => String::from("new < old"),
+            CheatSearchFilter::IncreasedBy(v) => format!("new == old + ${v:X}"),
+            CheatSearchFilter::DecreasedBy(v) => format!("new == old - ${v:X}"),
+            CheatSearchFilter::Expression(e) => e.clone()
+        }
+    }
+}
+
+/// Candidate address remaining in a RAM search.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CheatSearchResult {
+    /// Address
+    pub address: u16,
+
+    /// WRAM or cartridge RAM bank (0 for HRAM)
+    pub bank: u16,
+
+    /// Value as of the last filter
+    pub value: u16
+}
+
+impl CheatSearchResult {
+    /// Create a cheat that makes reads from this address return `value`.
+    ///
+    /// 16-bit values need a second cheat for the next address.
+    pub fn to_cheat(&self, value: u8, description: &str) -> Cheat {
+        Cheat {
+            description: String::from(description),
+            address: self.address,
+            bank: Some(self.bank),
+            value,
+            old_value: None,
+            enabled: true
+        }
+    }
+}
+
+/// Describes an error from filtering a RAM search.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CheatSearchError {
+    /// Error message from the debugger's expression evaluator
+    pub message: String
+}
@@ -0,0 +1,192 @@
+use alloc::vec::Vec;
+use core::ffi::{c_char, CStr};
+use sameboy_sys::{GB_add_cheat, GB_cheat_t, GB_cheats_enabled, GB_get_cheats, GB_remove_all_cheats, GB_remove_cheat, GB_set_cheats_enabled, GB_update_cheat};
+use crate::cheat::truncate_description;
+use crate::{Cheat, CheatCodeError, CheatList, Gameboy, RunningGameboy};
+
+const ANY_BANK: u16 = 0xFFFF;
+
+/// Handle to SameBoy's cheat engine.
+///
+/// Cheats are identified by their index in [`Cheats::get_cheats`]. Removing a cheat shifts the
+/// indices of all cheats after it.
+pub struct Cheats<'a> {
+    instance: &'a mut RunningGameboy
+}
+
+impl Gameboy {
+    /// Get a handle to the cheat engine.
+    pub fn cheats(&mut self) -> Cheats<'_> {
+        // SAFETY: The handle only borrows the instance; it can't be used to move it.
+        let instance = unsafe { self.inner.as_mut().get_unchecked_mut() };
+        Cheats { instance }
+    }
+}
+
+impl RunningGameboy {
+    /// Get a handle to the cheat engine.
+    pub fn cheats(&mut self) -> Cheats<'_> {
+        Cheats { instance: self }
+    }
+}
+
+impl Cheats<'_> {
+    /// Set whether cheats are applied.
+    ///
+    /// This does not change whether each individual cheat is enabled.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        unsafe { GB_set_cheats_enabled(self.instance.gb, enabled) }
+    }
+
+    /// Returns true if cheats are applied.
+    pub fn is_enabled(&self) -> bool {
+        unsafe { GB_cheats_enabled(self.instance.gb) }
+    }
+
+    /// Add a cheat, returning its index.
+    pub fn add(&mut self, cheat: &Cheat) -> usize {
+        let description = to_c_description(&cheat.description);
+        unsafe {
+            GB_add_cheat(
+                self.instance.gb,
+                description.as_ptr() as *const c_char,
+                cheat.address,
+                cheat.bank.unwrap_or(ANY_BANK),
+                cheat.value,
+                cheat.old_value.unwrap_or(0),
+                cheat.old_value.is_some(),
+                cheat.enabled
+            );
+        }
+        self.len() - 1
+    }
+
+    /// Parse a Game Genie or GameShark code and add it, returning its index.
+    ///
+    /// See [`Cheat::from_code`] for the accepted formats.
+    pub fn import(&mut self, code: &str, description: &str, enabled: bool) -> Result<usize, CheatCodeError> {
+        let mut cheat = Cheat::from_code(code, description)?;
+        cheat.enabled = enabled;
+        Ok(self.add(&cheat))
+    }
+
+    /// Replace the cheat at `index`.
+    ///
+    /// Returns `false` if there is no such cheat.
+    pub fn update(&mut self, index: usize, cheat: &Cheat) -> bool {
+        let Some(ptr) = self.get_cheat_ptr(index) else {
+            return false
+        };
+        let description = to_c_description(&cheat.description);
+        unsafe {
+            GB_update_cheat(
+                self.instance.gb,
+                ptr,
+                description.as_ptr() as *const c_char,
+                cheat.address,
+                cheat.bank.unwrap_or(ANY_BANK),
+                cheat.value,
+                cheat.old_value.unwrap_or(0),
+                cheat.old_value.is_some(),
+                cheat.enabled
+            );
+        }
+        true
+    }
+
+    /// Enable or disable the cheat at `index`.
+    ///
+    /// Returns `false` if there is no such cheat.
+    pub fn set_cheat_enabled(&mut self, index: usize, enabled: bool) -> bool {
+        let Some(mut cheat) = self.get_cheat(index) else {
+            return false
+        };
+        cheat.enabled = enabled;
+        self.update(index, &cheat)
+    }
+
+    /// Remove the cheat at `index`.
+    ///
+    /// Returns `false` if there is no such cheat.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let Some(ptr) = self.get_cheat_ptr(index) else {
+            return false
+        };
+        unsafe { GB_remove_cheat(self.instance.gb, ptr) };
+        true
+    }
+
+    /// Add all cheats in `list`.
+    ///
+    /// If `replace_existing` is true, all existing cheats are removed first.
+    pub fn load_list(&mut self, list: &CheatList, replace_existing: bool) {
+        if replace_existing {
+            self.clear();
+        }
+        for cheat in &list.cheats {
+            self.add(cheat);
+        }
+    }
+
+    /// Get all cheats as a [`CheatList`].
+    pub fn to_list(&self) -> CheatList {
+        CheatList { cheats: self.get_cheats() }
+    }
+
+    /// Remove all cheats.
+    pub fn clear(&mut self) {
+        unsafe { GB_remove_all_cheats(self.instance.gb) }
+    }
+
+    /// Get the number of cheats.
+    pub fn len(&self) -> usize {
+        self.get_cheat_ptrs().len()
+    }
+
+    /// Returns true if there are no cheats.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the cheat at `index`.
+    pub fn get_cheat(&self, index: usize) -> Option<Cheat> {
+        self.get_cheat_ptr(index).map(|ptr| unsafe { read_cheat(&*ptr) })
+    }
+
+    /// Get all cheats.
+    pub fn get_cheats(&self) -> Vec<Cheat> {
+        self.get_cheat_ptrs().iter().map(|ptr| unsafe { read_cheat(&**ptr) }).collect()
+    }
+
+    fn get_cheat_ptr(&self, index: usize) -> Option<*const GB_cheat_t> {
+        self.get_cheat_ptrs().get(index).copied()
+    }
+
+    fn get_cheat_ptrs(&self) -> &[*const GB_cheat_t] {
+        let mut size = 0usize;
+        let cheats = unsafe { GB_get_cheats(self.instance.gb, &mut size) };
+        if cheats.is_null() || size == 0 {
+            return &[]
+        }
+        unsafe { core::slice::from_raw_parts(cheats, size) }
+    }
+}
+
+fn read_cheat(cheat: &GB_cheat_t) -> Cheat {
+    let description = unsafe { CStr::from_ptr(cheat.description.as_ptr()) };
+    Cheat {
+        description: description.to_string_lossy().into_owned(),
+        address: cheat.address,
+        bank: (cheat.bank != ANY_BANK).then_some(cheat.bank),
+        value: cheat.value,
+        old_value: cheat.use_old_value.then_some(cheat.old_value),
+        enabled: cheat.enabled
+    }
+}
+
+/// Convert to a null-terminated string, truncating at the first null.
+fn to_c_description(description: &str) -> Vec<u8> {
+    let mut bytes: Vec<u8> = truncate_description(description).bytes().take_while(|b| *b != 0).collect();
+    bytes.push(0);
+    bytes
+}
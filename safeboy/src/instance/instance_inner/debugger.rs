@@ -0,0 +1,171 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_void, CStr};
+use sameboy_sys::{GB_debugger_break, GB_debugger_complete_substring, GB_debugger_describe_address, GB_debugger_evaluate, GB_debugger_execute_command, GB_debugger_get_frame_cpu_usage, GB_debugger_get_second_cpu_usage, GB_debugger_is_stopped, GB_debugger_name_for_address, GB_debugger_set_disabled};
+use crate::{Gameboy, RunningGameboy};
+
+unsafe extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+/// Handle to SameBoy's built-in debugger.
+///
+/// All output from the debugger (including from commands) is passed to
+/// [`console_log`](crate::GameboyCallbacks::console_log), and input is requested with
+/// [`console_input`](crate::GameboyCallbacks::console_input) while the debugger is stopped.
+pub struct Debugger<'a> {
+    instance: &'a mut RunningGameboy
+}
+
+impl Gameboy {
+    /// Get a handle to the debugger.
+    pub fn debugger(&mut self) -> Debugger<'_> {
+        // SAFETY: The handle only borrows the instance; it can't be used to move it.
+        let instance = unsafe { self.inner.as_mut().get_unchecked_mut() };
+        Debugger { instance }
+    }
+}
+
+impl RunningGameboy {
+    /// Get a handle to the debugger.
+    pub fn debugger(&mut self) -> Debugger<'_> {
+        Debugger { instance: self }
+    }
+}
+
+impl Debugger<'_> {
+    /// Execute a debugger command (e.g. `"breakpoint $150"`).
+    ///
+    /// Type `help` for a list of commands.
+    ///
+    /// # Remarks
+    ///
+    /// Commands that resume execution (e.g. `continue` or `next`) take effect when the emulator is
+    /// next run.
+    pub fn execute_command(&mut self, command: &str) {
+        let mut command = to_c_string(command);
+        let was_running = core::mem::replace(&mut self.instance.is_running, true);
+        unsafe { GB_debugger_execute_command(self.instance.gb, command.as_mut_ptr() as *mut c_char) };
+        self.instance.is_running = was_running;
+    }
+
+    /// Evaluate an expression (e.g. `"[hl] + 1"` or a symbol name).
+    ///
+    /// Returns `Err` if the expression is invalid.
+    pub fn evaluate(&mut self, expression: &str) -> Result<DebuggerValue, EvaluateError> {
+        let expression = to_c_string(expression);
+        let mut value = 0u16;
+        let mut bank = 0u16;
+
+        let mut failed = false;
+        let message = self.instance.capture_log(|gb| unsafe {
+            failed = GB_debugger_evaluate(gb, expression.as_ptr() as *const c_char, &mut value, &mut bank);
+        });
+
+        if failed {
+            return Err(EvaluateError { message: String::from(message.trim_end()) })
+        }
+        Ok(DebuggerValue { value, bank: (bank != 0xFFFF).then_some(bank) })
+    }
+
+    /// Stop execution and enter the debugger.
+    ///
+    /// The debugger will prompt for commands the next time the emulator is run.
+    pub fn break_execution(&mut self) {
+        unsafe { GB_debugger_break(self.instance.gb) }
+    }
+
+    /// Returns true if execution is stopped in the debugger.
+    pub fn is_stopped(&self) -> bool {
+        unsafe { GB_debugger_is_stopped(self.instance.gb) }
+    }
+
+    /// Set whether the debugger is disabled.
+    ///
+    /// If disabled, breakpoints and watchpoints set through debugger commands are ignored.
+    pub fn set_disabled(&mut self, disabled: bool) {
+        unsafe { GB_debugger_set_disabled(self.instance.gb, disabled) }
+    }
+
+    /// Get the fraction of the last frame the CPU spent running rather than halted or stopped
+    /// (`0.0` to `1.0`).
+    pub fn get_frame_cpu_usage(&self) -> f64 {
+        unsafe { GB_debugger_get_frame_cpu_usage(self.instance.gb) }
+    }
+
+    /// Get the fraction of the last second the CPU spent running rather than halted or stopped
+    /// (`0.0` to `1.0`).
+    pub fn get_second_cpu_usage(&self) -> f64 {
+        unsafe { GB_debugger_get_second_cpu_usage(self.instance.gb) }
+    }
+
+    /// Get the name of the symbol at `address` in SameBoy's debugger, using the bank currently
+    /// mapped there.
+    pub fn name_for_address(&self, address: u16) -> Option<String> {
+        let name = unsafe { GB_debugger_name_for_address(self.instance.gb, address) };
+        copy_c_string(name)
+    }
+
+    /// Describe `address` with SameBoy's debugger.
+    ///
+    /// If `bank` is `None`, the bank currently mapped to `address` is used. If `exact_match` is
+    /// true, this only returns the name of a symbol at exactly `address`. Otherwise, it returns a
+    /// description such as `Main+$012 ($01:$4012)`. If `prefer_local` is true, local labels are
+    /// preferred.
+    pub fn describe_address(&self, address: u16, bank: Option<u16>, exact_match: bool, prefer_local: bool) -> Option<String> {
+        let description = unsafe { GB_debugger_describe_address(self.instance.gb, address, bank.unwrap_or(0xFFFF), exact_match, prefer_local) };
+        copy_c_string(description)
+    }
+
+    /// Get all possible completions for `input`.
+    ///
+    /// Each completion is the text that would be appended to `input`.
+    pub fn complete(&mut self, input: &str) -> Vec<String> {
+        let mut completions = Vec::new();
+        let mut context = 0usize;
+        loop {
+            // The input is destroyed on every call.
+            let mut input = to_c_string(input);
+            let completion = unsafe { GB_debugger_complete_substring(self.instance.gb, input.as_mut_ptr() as *mut c_char, &mut context) };
+            if completion.is_null() {
+                break
+            }
+            unsafe {
+                completions.push(CStr::from_ptr(completion).to_string_lossy().into_owned());
+                free(completion as *mut c_void);
+            }
+        }
+        completions
+    }
+}
+
+/// Result of evaluating a debugger expression.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DebuggerValue {
+    /// Value of the expression
+    pub value: u16,
+
+    /// Bank, if the value is a banked address
+    pub bank: Option<u16>
+}
+
+/// Describes an error from evaluating a debugger expression.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvaluateError {
+    /// Error message from the debugger
+    pub message: String
+}
+
+fn copy_c_string(string: *const c_char) -> Option<String> {
+    if string.is_null() {
+        return None
+    }
+    Some(unsafe { CStr::from_ptr(string) }.to_string_lossy().into_owned())
+}
+
+/// Convert to a null-terminated string, truncating at the first null.
+pub(super) fn to_c_string(string: &str) -> Vec<u8> {
+    let mut bytes: Vec<u8> = string.bytes().take_while(|b| *b != 0).collect();
+    bytes.push(0);
+    bytes
+}
@@ -0,0 +1,82 @@
+use crate::InterruptFlags;
+
+/// Describes an interrupt source.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Interrupt {
+    /// Vertical blank (`$0040`)
+    VBlank,
+
+    /// LCD status (`$0048`)
+    Stat,
+
+    /// Timer overflow (`$0050`)
+    Timer,
+
+    /// Serial transfer completion (`$0058`)
+    Serial,
+
+    /// Joypad input (`$0060`)
+    Joypad
+}
+
+impl Interrupt {
+    /// All interrupts, in order of priority (highest first).
+    pub const ALL: [Interrupt; 5] = [Interrupt::VBlank, Interrupt::Stat, Interrupt::Timer, Interrupt::Serial, Interrupt::Joypad];
+
+    /// Get the address the CPU jumps to when servicing this interrupt.
+    pub const fn get_vector(self) -> u16 {
+        0x40 + (self as u16) * 8
+    }
+
+    /// Get the bit corresponding to this interrupt in the `IE` and `IF` registers.
+    pub const fn get_mask(self) -> u8 {
+        1 << (self as u8)
+    }
+
+    /// Get the interrupt serviced at `vector`, if any.
+    pub const fn from_vector(vector: u16) -> Option<Interrupt> {
+        match vector {
+            0x40 => Some(Interrupt::VBlank),
+            0x48 => Some(Interrupt::Stat),
+            0x50 => Some(Interrupt::Timer),
+            0x58 => Some(Interrupt::Serial),
+            0x60 => Some(Interrupt::Joypad),
+            _ => None
+        }
+    }
+}
+
+/// Interrupt state of the CPU.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct InterruptState {
+    /// Interrupt master enable
+    pub ime: bool,
+
+    /// Enabled interrupts (IE, `$FFFF`)
+    pub enabled: InterruptFlags,
+
+    /// Requested interrupts (IF, `$FF0F`)
+    pub requested: InterruptFlags
+}
+
+impl InterruptState {
+    /// Get interrupts that are both enabled and requested.
+    ///
+    /// These will be serviced once IME is set (or will wake the CPU from HALT regardless of IME).
+    pub const fn get_pending(&self) -> InterruptFlags {
+        InterruptFlags::from_byte(self.enabled.to_byte() & self.requested.to_byte())
+    }
+
+    /// Get the pending interrupt that will be serviced first, if any.
+    pub const fn get_next_pending(&self) -> Option<Interrupt> {
+        let pending = self.get_pending().to_byte();
+        let mut i = 0;
+        while i < Interrupt::ALL.len() {
+            if pending & Interrupt::ALL[i].get_mask() != 0 {
+                return Some(Interrupt::ALL[i]);
+            }
+            i += 1;
+        }
+        None
+    }
+}
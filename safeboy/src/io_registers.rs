@@ -0,0 +1,951 @@
+use crate::{DirectAccessRegion, Interrupt, RunnableInstanceFunctions};
+
+/// Typed view of one or more consecutive I/O registers.
+///
+/// Bit layouts follow [Pan Docs](https://gbdev.io/pandocs/Hardware_Reg_List.html).
+///
+/// # Remarks
+///
+/// Write-only bits (such as the period and trigger bits of the sound registers) read back as 1
+/// through the CPU's address space. [`IoRegister::from_io`] decodes the values stored by the
+/// emulator instead, which may include them.
+///
+/// [`IoRegister::read_from`] reads the other write-only fields of the sound channels from the
+/// values last written, and never sets fields that are actions rather than state (such as
+/// [`Timer::reset_divider`] and the `trigger` bits of the sound channels). Reading registers and
+/// writing them back with [`IoRegister::write_to`] therefore doesn't reset or restart anything,
+/// apart from reloading the length timers of the sound channels.
+pub trait IoRegister: Sized {
+    /// Address of the first register
+    const ADDRESS: u16;
+
+    /// Number of consecutive registers
+    const SIZE: usize = 1;
+
+    /// Decode the registers from `SIZE` bytes, starting with the register at `ADDRESS`.
+    fn decode(bytes: &[u8]) -> Self;
+
+    /// Encode the registers to `SIZE` bytes, starting with the register at `ADDRESS`.
+    fn encode(&self, bytes: &mut [u8]);
+
+    /// Decode the registers from the contents of [`DirectAccessRegion::IO`](crate::DirectAccessRegion::IO)
+    /// (`$FF00`-`$FF7F`).
+    ///
+    /// Returns `None` if the registers are outside of `io` (e.g. `IE`, which is at `$FFFF`).
+    fn from_io(io: &[u8]) -> Option<Self> {
+        let start = Self::ADDRESS.checked_sub(0xFF00)? as usize;
+        io.get(start..start + Self::SIZE).map(Self::decode)
+    }
+
+    /// Read the registers through the CPU's address space, without side effects.
+    fn read_from<G: RunnableInstanceFunctions + ?Sized>(gameboy: &G) -> Self {
+        let mut bytes = [0u8; 8];
+        let bytes = &mut bytes[..Self::SIZE];
+        gameboy.peek_range(Self::ADDRESS, bytes);
+        Self::decode(bytes)
+    }
+
+    /// Write the registers through the CPU's address space, in order.
+    ///
+    /// This has the same side effects as the CPU writing them (e.g. writing a trigger bit starts
+    /// a sound channel).
+    fn write_to<G: RunnableInstanceFunctions + ?Sized>(&self, gameboy: &mut G) {
+        write_registers(self, gameboy, 0);
+    }
+}
+
+/// Write the registers of `register` through the CPU's address space, starting at `first`.
+fn write_registers<R: IoRegister, G: RunnableInstanceFunctions + ?Sized>(register: &R, gameboy: &mut G, first: usize) {
+    let mut bytes = [0u8; 8];
+    let bytes = &mut bytes[..R::SIZE];
+    register.encode(bytes);
+    for (offset, byte) in bytes.iter().enumerate().skip(first) {
+        gameboy.write(R::ADDRESS.wrapping_add(offset as u16), *byte);
+    }
+}
+
+/// Decode the values last written to sound registers, since their write-only bits read back as 1.
+fn read_written<R: IoRegister, G: RunnableInstanceFunctions + ?Sized>(gameboy: &G) -> R {
+    R::from_io(gameboy.direct_access(DirectAccessRegion::IO).data).expect("sound registers are in the IO region")
+}
+
+const fn bit(byte: u8, bit: u8) -> bool {
+    (byte >> bit) & 1 != 0
+}
+
+const fn flag(set: bool, bit: u8) -> u8 {
+    (set as u8) << bit
+}
+
+/// Tile map area used by the background or window.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TileMapArea {
+    /// `$9800`-`$9BFF`
+    Map9800,
+
+    /// `$9C00`-`$9FFF`
+    Map9C00
+}
+
+/// Tile data area used by the background and window.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TileDataArea {
+    /// `$8800`-`$97FF`, with signed tile indices relative to `$9000`
+    Data8800,
+
+    /// `$8000`-`$8FFF`, with unsigned tile indices
+    Data8000
+}
+
+/// Height of objects.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ObjectSize {
+    /// 8x8 objects
+    Size8x8,
+
+    /// 8x16 objects
+    Size8x16
+}
+
+/// LCD control (`LCDC`, `$FF40`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Lcdc {
+    /// LCD and PPU are enabled (bit 7)
+    pub lcd_enabled: bool,
+
+    /// Tile map used by the window (bit 6)
+    pub window_tile_map: TileMapArea,
+
+    /// Window is enabled (bit 5)
+    pub window_enabled: bool,
+
+    /// Tile data used by the background and window (bit 4)
+    pub tile_data: TileDataArea,
+
+    /// Tile map used by the background (bit 3)
+    pub background_tile_map: TileMapArea,
+
+    /// Object size (bit 2)
+    pub object_size: ObjectSize,
+
+    /// Objects are enabled (bit 1)
+    pub objects_enabled: bool,
+
+    /// Background and window are enabled on DMG, or have priority over objects on CGB (bit 0)
+    pub background_window_enabled: bool
+}
+
+impl IoRegister for Lcdc {
+    const ADDRESS: u16 = 0xFF40;
+
+    fn decode(bytes: &[u8]) -> Self {
+        let b = bytes[0];
+        let map = |set| if set { TileMapArea::Map9C00 } else { TileMapArea::Map9800 };
+        Lcdc {
+            lcd_enabled: bit(b, 7),
+            window_tile_map: map(bit(b, 6)),
+            window_enabled: bit(b, 5),
+            tile_data: if bit(b, 4) { TileDataArea::Data8000 } else { TileDataArea::Data8800 },
+            background_tile_map: map(bit(b, 3)),
+            object_size: if bit(b, 2) { ObjectSize::Size8x16 } else { ObjectSize::Size8x8 },
+            objects_enabled: bit(b, 1),
+            background_window_enabled: bit(b, 0)
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = flag(self.lcd_enabled, 7)
+            | flag(self.window_tile_map == TileMapArea::Map9C00, 6)
+            | flag(self.window_enabled, 5)
+            | flag(self.tile_data == TileDataArea::Data8000, 4)
+            | flag(self.background_tile_map == TileMapArea::Map9C00, 3)
+            | flag(self.object_size == ObjectSize::Size8x16, 2)
+            | flag(self.objects_enabled, 1)
+            | flag(self.background_window_enabled, 0);
+    }
+}
+
+/// PPU mode.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PpuMode {
+    /// Mode 0 (horizontal blank)
+    HBlank,
+
+    /// Mode 1 (vertical blank)
+    VBlank,
+
+    /// Mode 2 (searching OAM)
+    OamScan,
+
+    /// Mode 3 (drawing pixels)
+    Drawing
+}
+
+/// LCD status (`STAT`, `$FF41`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Stat {
+    /// Request a STAT interrupt when LY equals LYC (bit 6)
+    pub lyc_interrupt: bool,
+
+    /// Request a STAT interrupt on mode 2 (bit 5)
+    pub oam_scan_interrupt: bool,
+
+    /// Request a STAT interrupt on mode 1 (bit 4)
+    pub vblank_interrupt: bool,
+
+    /// Request a STAT interrupt on mode 0 (bit 3)
+    pub hblank_interrupt: bool,
+
+    /// LY equals LYC (bit 2, read-only)
+    pub lyc_equal: bool,
+
+    /// Current PPU mode (bits 1-0, read-only)
+    pub mode: PpuMode
+}
+
+impl IoRegister for Stat {
+    const ADDRESS: u16 = 0xFF41;
+
+    fn decode(bytes: &[u8]) -> Self {
+        let b = bytes[0];
+        Stat {
+            lyc_interrupt: bit(b, 6),
+            oam_scan_interrupt: bit(b, 5),
+            vblank_interrupt: bit(b, 4),
+            hblank_interrupt: bit(b, 3),
+            lyc_equal: bit(b, 2),
+            mode: match b & 0b11 {
+                0 => PpuMode::HBlank,
+                1 => PpuMode::VBlank,
+                2 => PpuMode::OamScan,
+                _ => PpuMode::Drawing
+            }
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = 0x80
+            | flag(self.lyc_interrupt, 6)
+            | flag(self.oam_scan_interrupt, 5)
+            | flag(self.vblank_interrupt, 4)
+            | flag(self.hblank_interrupt, 3)
+            | flag(self.lyc_equal, 2)
+            | self.mode as u8;
+    }
+}
+
+/// Background scroll position (`SCY` and `SCX`, `$FF42`-`$FF43`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Scroll {
+    /// `SCY`
+    pub y: u8,
+
+    /// `SCX`
+    pub x: u8
+}
+
+impl IoRegister for Scroll {
+    const ADDRESS: u16 = 0xFF42;
+    const SIZE: usize = 2;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Scroll { y: bytes[0], x: bytes[1] }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = self.y;
+        bytes[1] = self.x;
+    }
+}
+
+/// Current and compared scanline (`LY` and `LYC`, `$FF44`-`$FF45`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LineCounter {
+    /// `LY` (read-only)
+    pub ly: u8,
+
+    /// `LYC`
+    pub lyc: u8
+}
+
+impl IoRegister for LineCounter {
+    const ADDRESS: u16 = 0xFF44;
+    const SIZE: usize = 2;
+
+    fn decode(bytes: &[u8]) -> Self {
+        LineCounter { ly: bytes[0], lyc: bytes[1] }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = self.ly;
+        bytes[1] = self.lyc;
+    }
+}
+
+/// Window position (`WY` and `WX`, `$FF4A`-`$FF4B`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WindowPosition {
+    /// `WY`
+    pub y: u8,
+
+    /// `WX` (the window's left edge is at `WX - 7`)
+    pub x: u8
+}
+
+impl IoRegister for WindowPosition {
+    const ADDRESS: u16 = 0xFF4A;
+    const SIZE: usize = 2;
+
+    fn decode(bytes: &[u8]) -> Self {
+        WindowPosition { y: bytes[0], x: bytes[1] }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = self.y;
+        bytes[1] = self.x;
+    }
+}
+
+/// Frequency TIMA is incremented at.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TimerClock {
+    /// Every 256 M-cycles (4096 Hz)
+    Every256,
+
+    /// Every 4 M-cycles (262144 Hz)
+    Every4,
+
+    /// Every 16 M-cycles (65536 Hz)
+    Every16,
+
+    /// Every 64 M-cycles (16384 Hz)
+    Every64
+}
+
+/// Timer registers (`DIV`, `TIMA`, `TMA`, and `TAC`, `$FF04`-`$FF07`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Timer {
+    /// Divider (`DIV`, read-only; see [`reset_divider`](Self::reset_divider))
+    pub divider: u8,
+
+    /// Reset `DIV` to 0 when written with [`write_to`](IoRegister::write_to) (write-only)
+    ///
+    /// Writing any value to `DIV` resets it, so it is only written if this is set.
+    pub reset_divider: bool,
+
+    /// Timer counter (`TIMA`)
+    pub counter: u8,
+
+    /// Value loaded into TIMA when it overflows (`TMA`)
+    pub modulo: u8,
+
+    /// TIMA is incremented (`TAC` bit 2)
+    pub enabled: bool,
+
+    /// Frequency TIMA is incremented at (`TAC` bits 1-0)
+    pub clock: TimerClock
+}
+
+impl IoRegister for Timer {
+    const ADDRESS: u16 = 0xFF04;
+    const SIZE: usize = 4;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Timer {
+            divider: bytes[0],
+            reset_divider: false,
+            counter: bytes[1],
+            modulo: bytes[2],
+            enabled: bit(bytes[3], 2),
+            clock: match bytes[3] & 0b11 {
+                0 => TimerClock::Every256,
+                1 => TimerClock::Every4,
+                2 => TimerClock::Every16,
+                _ => TimerClock::Every64
+            }
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = self.divider;
+        bytes[1] = self.counter;
+        bytes[2] = self.modulo;
+        bytes[3] = 0xF8 | flag(self.enabled, 2) | self.clock as u8;
+    }
+
+    fn write_to<G: RunnableInstanceFunctions + ?Sized>(&self, gameboy: &mut G) {
+        write_registers(self, gameboy, if self.reset_divider { 0 } else { 1 });
+    }
+}
+
+/// Set of interrupts, as in the `IE` and `IF` registers.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct InterruptFlags {
+    /// Vertical blank (bit 0)
+    pub vblank: bool,
+
+    /// LCD status (bit 1)
+    pub stat: bool,
+
+    /// Timer (bit 2)
+    pub timer: bool,
+
+    /// Serial (bit 3)
+    pub serial: bool,
+
+    /// Joypad (bit 4)
+    pub joypad: bool
+}
+
+impl InterruptFlags {
+    /// Decode from the lower 5 bits of `byte`.
+    pub const fn from_byte(byte: u8) -> InterruptFlags {
+        InterruptFlags { vblank: bit(byte, 0), stat: bit(byte, 1), timer: bit(byte, 2), serial: bit(byte, 3), joypad: bit(byte, 4) }
+    }
+
+    /// Encode to the lower 5 bits of a byte.
+    pub const fn to_byte(self) -> u8 {
+        flag(self.vblank, 0) | flag(self.stat, 1) | flag(self.timer, 2) | flag(self.serial, 3) | flag(self.joypad, 4)
+    }
+
+    /// Returns true if `interrupt` is set.
+    pub const fn contains(self, interrupt: Interrupt) -> bool {
+        self.to_byte() & interrupt.get_mask() != 0
+    }
+
+    /// Set or clear `interrupt`.
+    pub const fn set(&mut self, interrupt: Interrupt, value: bool) {
+        let byte = match value {
+            true => self.to_byte() | interrupt.get_mask(),
+            false => self.to_byte() & !interrupt.get_mask()
+        };
+        *self = InterruptFlags::from_byte(byte);
+    }
+}
+
+/// Interrupts that are enabled (`IE`, `$FFFF`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct InterruptEnable(pub InterruptFlags);
+
+impl IoRegister for InterruptEnable {
+    const ADDRESS: u16 = 0xFFFF;
+
+    fn decode(bytes: &[u8]) -> Self {
+        InterruptEnable(InterruptFlags::from_byte(bytes[0]))
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        // The upper bits of IE are writable, but unused.
+        bytes[0] = self.0.to_byte();
+    }
+}
+
+/// Interrupts that are requested (`IF`, `$FF0F`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct InterruptRequest(pub InterruptFlags);
+
+impl IoRegister for InterruptRequest {
+    const ADDRESS: u16 = 0xFF0F;
+
+    fn decode(bytes: &[u8]) -> Self {
+        InterruptRequest(InterruptFlags::from_byte(bytes[0]))
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = 0xE0 | self.0.to_byte();
+    }
+}
+
+/// CGB speed switch (`KEY1`, `$FF4D`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Key1 {
+    /// CPU is in double speed mode (bit 7, read-only)
+    pub double_speed: bool,
+
+    /// Speed will switch when `STOP` is executed (bit 0)
+    pub switch_armed: bool
+}
+
+impl IoRegister for Key1 {
+    const ADDRESS: u16 = 0xFF4D;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Key1 { double_speed: bit(bytes[0], 7), switch_armed: bit(bytes[0], 0) }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = 0x7E | flag(self.double_speed, 7) | flag(self.switch_armed, 0);
+    }
+}
+
+/// CGB VRAM bank (`VBK`, `$FF4F`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Vbk {
+    /// VRAM bank mapped to `$8000`-`$9FFF` (0 or 1)
+    pub bank: u8
+}
+
+impl IoRegister for Vbk {
+    const ADDRESS: u16 = 0xFF4F;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Vbk { bank: bytes[0] & 1 }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = 0xFE | (self.bank & 1);
+    }
+}
+
+/// CGB WRAM bank (`SVBK`, `$FF70`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Svbk {
+    /// Value of bits 2-0
+    ///
+    /// Bank 0 can't be mapped to `$D000`-`$DFFF`, so both 0 and 1 map bank 1 (see
+    /// [`Svbk::get_bank`]).
+    pub bank: u8
+}
+
+impl Svbk {
+    /// Get the WRAM bank mapped to `$D000`-`$DFFF`.
+    pub const fn get_bank(self) -> u8 {
+        match self.bank & 7 {
+            0 => 1,
+            b => b
+        }
+    }
+}
+
+impl IoRegister for Svbk {
+    const ADDRESS: u16 = 0xFF70;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Svbk { bank: bytes[0] & 7 }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = 0xF8 | (self.bank & 7);
+    }
+}
+
+/// CGB VRAM DMA (`HDMA1`-`HDMA5`, `$FF51`-`$FF55`).
+///
+/// `HDMA1`-`HDMA4` are write-only, and reading `HDMA5` returns the status of the current transfer
+/// instead.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Hdma {
+    /// Source address (`HDMA1` and `HDMA2`; the lower 4 bits are ignored)
+    pub source: u16,
+
+    /// Destination address in VRAM (`HDMA3` and `HDMA4`; only bits 12-4 are used)
+    pub destination: u16,
+
+    /// Transfer 16 bytes every horizontal blank rather than all at once (`HDMA5` bit 7)
+    ///
+    /// When read, this is set if no transfer is active.
+    pub hblank: bool,
+
+    /// Number of 16-byte blocks to transfer, minus one (`HDMA5` bits 6-0)
+    ///
+    /// When read, this is the number of blocks remaining, minus one.
+    pub blocks: u8
+}
+
+impl Hdma {
+    /// Get the number of bytes to transfer.
+    pub const fn get_length(self) -> u16 {
+        ((self.blocks & 0x7F) as u16 + 1) * 16
+    }
+}
+
+impl IoRegister for Hdma {
+    const ADDRESS: u16 = 0xFF51;
+    const SIZE: usize = 5;
+
+    fn decode(bytes: &[u8]) -> Self {
+        Hdma {
+            source: u16::from_be_bytes([bytes[0], bytes[1]]),
+            destination: u16::from_be_bytes([bytes[2], bytes[3]]),
+            hblank: bit(bytes[4], 7),
+            blocks: bytes[4] & 0x7F
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        [bytes[0], bytes[1]] = self.source.to_be_bytes();
+        [bytes[2], bytes[3]] = self.destination.to_be_bytes();
+        bytes[4] = flag(self.hblank, 7) | (self.blocks & 0x7F);
+    }
+}
+
+/// Duty cycle of a pulse channel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DutyCycle {
+    /// 12.5%
+    Eighth,
+
+    /// 25%
+    Quarter,
+
+    /// 50%
+    Half,
+
+    /// 75%
+    ThreeQuarters
+}
+
+impl DutyCycle {
+    const fn from_bits(bits: u8) -> DutyCycle {
+        match bits & 0b11 {
+            0 => DutyCycle::Eighth,
+            1 => DutyCycle::Quarter,
+            2 => DutyCycle::Half,
+            _ => DutyCycle::ThreeQuarters
+        }
+    }
+}
+
+/// Volume envelope of a pulse or noise channel (`NR12`, `NR22`, or `NR42`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Envelope {
+    /// Initial volume (bits 7-4)
+    pub initial_volume: u8,
+
+    /// Volume increases rather than decreases (bit 3)
+    pub increase: bool,
+
+    /// Number of 64 Hz ticks between volume changes, or 0 to disable the envelope (bits 2-0)
+    pub pace: u8
+}
+
+impl Envelope {
+    const fn from_byte(b: u8) -> Envelope {
+        Envelope { initial_volume: b >> 4, increase: bit(b, 3), pace: b & 7 }
+    }
+
+    const fn to_byte(self) -> u8 {
+        ((self.initial_volume & 0xF) << 4) | flag(self.increase, 3) | (self.pace & 7)
+    }
+}
+
+/// Frequency sweep of channel 1 (`NR10`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Sweep {
+    /// Number of 128 Hz ticks between period changes, or 0 to disable the sweep (bits 6-4)
+    pub pace: u8,
+
+    /// Period decreases (frequency increases) rather than increases (bit 3)
+    pub decrease: bool,
+
+    /// Shift applied to the period on every change (bits 2-0)
+    pub step: u8
+}
+
+/// Decode the period and control bits shared by `NRx3` and `NRx4`.
+const fn decode_period(low: u8, high: u8) -> (u16, bool, bool) {
+    (((high as u16 & 7) << 8) | low as u16, bit(high, 7), bit(high, 6))
+}
+
+/// Encode `NRx3` and `NRx4`.
+const fn encode_period(period: u16, trigger: bool, length_enabled: bool) -> [u8; 2] {
+    [period as u8, flag(trigger, 7) | flag(length_enabled, 6) | ((period >> 8) as u8 & 7)]
+}
+
+/// Pulse channel 1 (`NR10`-`NR14`, `$FF10`-`$FF14`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PulseChannel1 {
+    /// Frequency sweep (`NR10`)
+    pub sweep: Sweep,
+
+    /// Duty cycle (`NR11` bits 7-6)
+    pub duty: DutyCycle,
+
+    /// Initial length timer (`NR11` bits 5-0, write-only)
+    pub length_timer: u8,
+
+    /// Volume envelope (`NR12`)
+    pub envelope: Envelope,
+
+    /// Period (`NR13` and `NR14` bits 2-0, write-only)
+    pub period: u16,
+
+    /// Start the channel when written (`NR14` bit 7, write-only)
+    ///
+    /// This is always false when read with [`read_from`](IoRegister::read_from).
+    pub trigger: bool,
+
+    /// Stop the channel when the length timer expires (`NR14` bit 6)
+    pub length_enabled: bool
+}
+
+impl IoRegister for PulseChannel1 {
+    const ADDRESS: u16 = 0xFF10;
+    const SIZE: usize = 5;
+
+    fn decode(bytes: &[u8]) -> Self {
+        let (period, trigger, length_enabled) = decode_period(bytes[3], bytes[4]);
+        PulseChannel1 {
+            sweep: Sweep { pace: (bytes[0] >> 4) & 7, decrease: bit(bytes[0], 3), step: bytes[0] & 7 },
+            duty: DutyCycle::from_bits(bytes[1] >> 6),
+            length_timer: bytes[1] & 0x3F,
+            envelope: Envelope::from_byte(bytes[2]),
+            period,
+            trigger,
+            length_enabled
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        let s = &self.sweep;
+        bytes[0] = 0x80 | ((s.pace & 7) << 4) | flag(s.decrease, 3) | (s.step & 7);
+        bytes[1] = ((self.duty as u8) << 6) | (self.length_timer & 0x3F);
+        bytes[2] = self.envelope.to_byte();
+        [bytes[3], bytes[4]] = encode_period(self.period, self.trigger, self.length_enabled);
+    }
+
+    /// Write-only fields are the values last written, and [`trigger`](Self::trigger) is cleared.
+    fn read_from<G: RunnableInstanceFunctions + ?Sized>(gameboy: &G) -> Self {
+        PulseChannel1 { trigger: false, ..read_written(gameboy) }
+    }
+}
+
+/// Pulse channel 2 (`NR21`-`NR24`, `$FF16`-`$FF19`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PulseChannel2 {
+    /// Duty cycle (`NR21` bits 7-6)
+    pub duty: DutyCycle,
+
+    /// Initial length timer (`NR21` bits 5-0, write-only)
+    pub length_timer: u8,
+
+    /// Volume envelope (`NR22`)
+    pub envelope: Envelope,
+
+    /// Period (`NR23` and `NR24` bits 2-0, write-only)
+    pub period: u16,
+
+    /// Start the channel when written (`NR24` bit 7, write-only)
+    ///
+    /// This is always false when read with [`read_from`](IoRegister::read_from).
+    pub trigger: bool,
+
+    /// Stop the channel when the length timer expires (`NR24` bit 6)
+    pub length_enabled: bool
+}
+
+impl IoRegister for PulseChannel2 {
+    const ADDRESS: u16 = 0xFF16;
+    const SIZE: usize = 4;
+
+    fn decode(bytes: &[u8]) -> Self {
+        let (period, trigger, length_enabled) = decode_period(bytes[2], bytes[3]);
+        PulseChannel2 {
+            duty: DutyCycle::from_bits(bytes[0] >> 6),
+            length_timer: bytes[0] & 0x3F,
+            envelope: Envelope::from_byte(bytes[1]),
+            period,
+            trigger,
+            length_enabled
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = ((self.duty as u8) << 6) | (self.length_timer & 0x3F);
+        bytes[1] = self.envelope.to_byte();
+        [bytes[2], bytes[3]] = encode_period(self.period, self.trigger, self.length_enabled);
+    }
+
+    /// Write-only fields are the values last written, and [`trigger`](Self::trigger) is cleared.
+    fn read_from<G: RunnableInstanceFunctions + ?Sized>(gameboy: &G) -> Self {
+        PulseChannel2 { trigger: false, ..read_written(gameboy) }
+    }
+}
+
+/// Output level of the wave channel.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WaveOutputLevel {
+    /// Muted
+    Mute,
+
+    /// 100% volume
+    Full,
+
+    /// 50% volume
+    Half,
+
+    /// 25% volume
+    Quarter
+}
+
+/// Wave channel (`NR30`-`NR34`, `$FF1A`-`$FF1E`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WaveChannel {
+    /// DAC is enabled (`NR30` bit 7)
+    pub dac_enabled: bool,
+
+    /// Initial length timer (`NR31`, write-only)
+    pub length_timer: u8,
+
+    /// Output level (`NR32` bits 6-5)
+    pub output_level: WaveOutputLevel,
+
+    /// Period (`NR33` and `NR34` bits 2-0, write-only)
+    pub period: u16,
+
+    /// Start the channel when written (`NR34` bit 7, write-only)
+    ///
+    /// This is always false when read with [`read_from`](IoRegister::read_from).
+    pub trigger: bool,
+
+    /// Stop the channel when the length timer expires (`NR34` bit 6)
+    pub length_enabled: bool
+}
+
+impl IoRegister for WaveChannel {
+    const ADDRESS: u16 = 0xFF1A;
+    const SIZE: usize = 5;
+
+    fn decode(bytes: &[u8]) -> Self {
+        let (period, trigger, length_enabled) = decode_period(bytes[3], bytes[4]);
+        WaveChannel {
+            dac_enabled: bit(bytes[0], 7),
+            length_timer: bytes[1],
+            output_level: match (bytes[2] >> 5) & 0b11 {
+                0 => WaveOutputLevel::Mute,
+                1 => WaveOutputLevel::Full,
+                2 => WaveOutputLevel::Half,
+                _ => WaveOutputLevel::Quarter
+            },
+            period,
+            trigger,
+            length_enabled
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = 0x7F | flag(self.dac_enabled, 7);
+        bytes[1] = self.length_timer;
+        bytes[2] = 0x9F | ((self.output_level as u8) << 5);
+        [bytes[3], bytes[4]] = encode_period(self.period, self.trigger, self.length_enabled);
+    }
+
+    /// Write-only fields are the values last written, and [`trigger`](Self::trigger) is cleared.
+    fn read_from<G: RunnableInstanceFunctions + ?Sized>(gameboy: &G) -> Self {
+        WaveChannel { trigger: false, ..read_written(gameboy) }
+    }
+}
+
+/// Noise channel (`NR41`-`NR44`, `$FF20`-`$FF23`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct NoiseChannel {
+    /// Initial length timer (`NR41` bits 5-0, write-only)
+    pub length_timer: u8,
+
+    /// Volume envelope (`NR42`)
+    pub envelope: Envelope,
+
+    /// Clock shift (`NR43` bits 7-4)
+    pub clock_shift: u8,
+
+    /// Use a 7-bit LFSR rather than a 15-bit one (`NR43` bit 3)
+    pub short_mode: bool,
+
+    /// Clock divider (`NR43` bits 2-0)
+    pub clock_divider: u8,
+
+    /// Start the channel when written (`NR44` bit 7, write-only)
+    ///
+    /// This is always false when read with [`read_from`](IoRegister::read_from).
+    pub trigger: bool,
+
+    /// Stop the channel when the length timer expires (`NR44` bit 6)
+    pub length_enabled: bool
+}
+
+impl IoRegister for NoiseChannel {
+    const ADDRESS: u16 = 0xFF20;
+    const SIZE: usize = 4;
+
+    fn decode(bytes: &[u8]) -> Self {
+        NoiseChannel {
+            length_timer: bytes[0] & 0x3F,
+            envelope: Envelope::from_byte(bytes[1]),
+            clock_shift: bytes[2] >> 4,
+            short_mode: bit(bytes[2], 3),
+            clock_divider: bytes[2] & 7,
+            trigger: bit(bytes[3], 7),
+            length_enabled: bit(bytes[3], 6)
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = 0xC0 | (self.length_timer & 0x3F);
+        bytes[1] = self.envelope.to_byte();
+        bytes[2] = ((self.clock_shift & 0xF) << 4) | flag(self.short_mode, 3) | (self.clock_divider & 7);
+        bytes[3] = 0x3F | flag(self.trigger, 7) | flag(self.length_enabled, 6);
+    }
+
+    /// Write-only fields are the values last written, and [`trigger`](Self::trigger) is cleared.
+    fn read_from<G: RunnableInstanceFunctions + ?Sized>(gameboy: &G) -> Self {
+        NoiseChannel { trigger: false, ..read_written(gameboy) }
+    }
+}
+
+/// Master sound control (`NR50`-`NR52`, `$FF24`-`$FF26`).
+///
+/// Channel arrays are indexed by channel number minus one.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SoundControl {
+    /// Mix the cartridge's VIN signal into the left output (`NR50` bit 7)
+    pub vin_left: bool,
+
+    /// Left output volume, minus one (`NR50` bits 6-4)
+    pub left_volume: u8,
+
+    /// Mix the cartridge's VIN signal into the right output (`NR50` bit 3)
+    pub vin_right: bool,
+
+    /// Right output volume, minus one (`NR50` bits 2-0)
+    pub right_volume: u8,
+
+    /// Channels panned to the left output (`NR51` bits 7-4)
+    pub left_channels: [bool; 4],
+
+    /// Channels panned to the right output (`NR51` bits 3-0)
+    pub right_channels: [bool; 4],
+
+    /// Audio is powered on (`NR52` bit 7)
+    pub enabled: bool,
+
+    /// Channels that are currently playing (`NR52` bits 3-0, read-only)
+    pub channels_active: [bool; 4]
+}
+
+impl IoRegister for SoundControl {
+    const ADDRESS: u16 = 0xFF24;
+    const SIZE: usize = 3;
+
+    fn decode(bytes: &[u8]) -> Self {
+        SoundControl {
+            vin_left: bit(bytes[0], 7),
+            left_volume: (bytes[0] >> 4) & 7,
+            vin_right: bit(bytes[0], 3),
+            right_volume: bytes[0] & 7,
+            left_channels: core::array::from_fn(|i| bit(bytes[1], 4 + i as u8)),
+            right_channels: core::array::from_fn(|i| bit(bytes[1], i as u8)),
+            enabled: bit(bytes[2], 7),
+            channels_active: core::array::from_fn(|i| bit(bytes[2], i as u8))
+        }
+    }
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes[0] = flag(self.vin_left, 7) | ((self.left_volume & 7) << 4) | flag(self.vin_right, 3) | (self.right_volume & 7);
+        bytes[1] = 0;
+        bytes[2] = 0x70 | flag(self.enabled, 7);
+        for i in 0..4 {
+            bytes[1] |= flag(self.left_channels[i], 4 + i as u8) | flag(self.right_channels[i], i as u8);
+            bytes[2] |= flag(self.channels_active[i], i as u8);
+        }
+    }
+}
@@ -14,6 +14,9 @@ pub use sameboy_sys::GB_VERSION;
 
 extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 pub mod rgb_encoder;
 
 mod instance;
@@ -22,3 +25,61 @@ pub use instance::*;
 
 mod model;
 pub use model::*;
+
+mod movie;
+pub use movie::*;
+
+mod watch;
+pub use watch::{Watch, WatchCondition, WatchHit, WatchId};
+
+mod symbols;
+pub use symbols::*;
+
+mod ram_watch;
+pub use ram_watch::*;
+
+mod achievements;
+pub use achievements::*;
+
+mod autosplitter;
+pub use autosplitter::*;
+
+mod cheat;
+pub use cheat::*;
+
+mod interrupt;
+pub use interrupt::*;
+
+mod io_registers;
+pub use io_registers::*;
+
+mod memory_map;
+pub use memory_map::*;
+
+mod call_stack;
+pub use call_stack::*;
+
+mod profiler;
+pub use profiler::*;
+
+mod cdl;
+pub use cdl::*;
+
+mod tracer;
+pub use tracer::*;
+
+mod disassembler;
+pub use disassembler::*;
+
+#[cfg(feature = "gdb-server")]
+mod gdb_server;
+#[cfg(feature = "gdb-server")]
+pub use gdb_server::*;
+
+#[cfg(feature = "livesplit")]
+mod livesplit;
+#[cfg(feature = "livesplit")]
+pub use livesplit::*;
+
+mod input_scheduler;
+pub use input_scheduler::{AutofireRate, InputMacro, InputMacroStep};
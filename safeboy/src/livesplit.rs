@@ -0,0 +1,68 @@
+use alloc::format;
+use alloc::string::String;
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, TcpStream};
+use std::time::Duration;
+use crate::{AutosplitterEvent, AutosplitterEventKind};
+
+/// Default port of LiveSplit Server.
+pub const LIVESPLIT_SERVER_PORT: u16 = 16834;
+
+/// Sends [`AutosplitterEvent`]s to LiveSplit Server over its TCP text protocol.
+///
+/// LiveSplit's game time is driven by the emulator: it is paused when the run starts and set with
+/// every event, so it only includes emulated time that the autosplitter was not paused for. Real
+/// time is left to LiveSplit.
+///
+/// For example, call [`LiveSplitClient::send_event`] from
+/// [`autosplitter_event`](crate::GameboyCallbacks::autosplitter_event), and
+/// [`LiveSplitClient::set_game_time`] every frame to keep the displayed time up to date.
+pub struct LiveSplitClient {
+    stream: TcpStream
+}
+
+impl LiveSplitClient {
+    /// Connect to LiveSplit Server on localhost.
+    ///
+    /// LiveSplit Server listens on [`LIVESPLIT_SERVER_PORT`] by default.
+    pub fn connect(port: u16) -> io::Result<LiveSplitClient> {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+        stream.set_nodelay(true)?;
+        Ok(LiveSplitClient { stream })
+    }
+
+    /// Send the commands for an event.
+    pub fn send_event(&mut self, event: &AutosplitterEvent) -> io::Result<()> {
+        match event.kind {
+            AutosplitterEventKind::Start => {
+                self.send("starttimer")?;
+                self.send("pausegametime")?;
+                self.set_game_time(event.time)
+            },
+            AutosplitterEventKind::Split(_) => {
+                self.set_game_time(event.time)?;
+                self.send("split")
+            },
+            AutosplitterEventKind::Reset => self.send("reset"),
+            AutosplitterEventKind::Pause | AutosplitterEventKind::Resume => self.set_game_time(event.time)
+        }
+    }
+
+    /// Set LiveSplit's game time (e.g. to [`get_game_time`](crate::Autosplitter::get_game_time)).
+    pub fn set_game_time(&mut self, time: Duration) -> io::Result<()> {
+        self.send(&format!("setgametime {}", format_time(time)))
+    }
+
+    fn send(&mut self, command: &str) -> io::Result<()> {
+        self.stream.write_all(command.as_bytes())?;
+        self.stream.write_all(b"\r\n")
+    }
+}
+
+/// Format a time as `h:mm:ss.fff`.
+fn format_time(time: Duration) -> String {
+    let milliseconds = time.as_millis() as u64;
+    let seconds = milliseconds / 1000;
+    let minutes = seconds / 60;
+    format!("{}:{:02}:{:02}.{:03}", minutes / 60, minutes % 60, seconds % 60, milliseconds % 1000)
+}
@@ -0,0 +1,306 @@
+/// Region of the CPU's address space.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MemoryRegion {
+    /// ROM bank 0 (`$0000`-`$3FFF`; some mappers can switch this bank)
+    Rom0,
+
+    /// Switchable ROM bank (`$4000`-`$7FFF`)
+    RomX,
+
+    /// Video RAM (`$8000`-`$9FFF`; switchable on CGB)
+    Vram,
+
+    /// Cartridge RAM (`$A000`-`$BFFF`)
+    Sram,
+
+    /// Work RAM bank 0 (`$C000`-`$CFFF`)
+    Wram0,
+
+    /// Switchable work RAM bank (`$D000`-`$DFFF`; always bank 1 on DMG)
+    WramX,
+
+    /// Mirror of work RAM (`$E000`-`$FDFF`)
+    Echo,
+
+    /// Object attribute memory (`$FE00`-`$FE9F`)
+    Oam,
+
+    /// Unusable memory after OAM (`$FEA0`-`$FEFF`)
+    Unusable,
+
+    /// I/O registers (`$FF00`-`$FF7F`)
+    Io,
+
+    /// High RAM (`$FF80`-`$FFFE`)
+    Hram,
+
+    /// Interrupt enable register (`$FFFF`)
+    Ie
+}
+
+impl MemoryRegion {
+    /// Get the region containing `address`.
+    pub const fn from_address(address: u16) -> MemoryRegion {
+        match address {
+            0x0000..=0x3FFF => MemoryRegion::Rom0,
+            0x4000..=0x7FFF => MemoryRegion::RomX,
+            0x8000..=0x9FFF => MemoryRegion::Vram,
+            0xA000..=0xBFFF => MemoryRegion::Sram,
+            0xC000..=0xCFFF => MemoryRegion::Wram0,
+            0xD000..=0xDFFF => MemoryRegion::WramX,
+            0xE000..=0xFDFF => MemoryRegion::Echo,
+            0xFE00..=0xFE9F => MemoryRegion::Oam,
+            0xFEA0..=0xFEFF => MemoryRegion::Unusable,
+            0xFF00..=0xFF7F => MemoryRegion::Io,
+            0xFF80..=0xFFFE => MemoryRegion::Hram,
+            0xFFFF => MemoryRegion::Ie
+        }
+    }
+
+    /// Get the first address of the region.
+    pub const fn get_start_address(self) -> u16 {
+        match self {
+            MemoryRegion::Rom0 => 0x0000,
+            MemoryRegion::RomX => 0x4000,
+            MemoryRegion::Vram => 0x8000,
+            MemoryRegion::Sram => 0xA000,
+            MemoryRegion::Wram0 => 0xC000,
+            MemoryRegion::WramX => 0xD000,
+            MemoryRegion::Echo => 0xE000,
+            MemoryRegion::Oam => 0xFE00,
+            MemoryRegion::Unusable => 0xFEA0,
+            MemoryRegion::Io => 0xFF00,
+            MemoryRegion::Hram => 0xFF80,
+            MemoryRegion::Ie => 0xFFFF
+        }
+    }
+
+    /// Get the size of the region in bytes.
+    pub const fn get_size(self) -> u16 {
+        match self {
+            MemoryRegion::Rom0 | MemoryRegion::RomX => 0x4000,
+            MemoryRegion::Vram | MemoryRegion::Sram => 0x2000,
+            MemoryRegion::Wram0 | MemoryRegion::WramX => 0x1000,
+            MemoryRegion::Echo => 0x1E00,
+            MemoryRegion::Oam => 0xA0,
+            MemoryRegion::Unusable => 0x60,
+            MemoryRegion::Io => 0x80,
+            MemoryRegion::Hram => 0x7F,
+            MemoryRegion::Ie => 1
+        }
+    }
+
+    /// Returns true if the region can be mapped to different banks.
+    ///
+    /// Addresses in other regions are always mapped to bank 0, except for the upper part of
+    /// [`MemoryRegion::Echo`], which mirrors [`MemoryRegion::WramX`].
+    pub const fn is_banked(self) -> bool {
+        matches!(self, MemoryRegion::Rom0 | MemoryRegion::RomX | MemoryRegion::Vram | MemoryRegion::Sram | MemoryRegion::WramX)
+    }
+}
+
+/// Address decoded with a [`MemoryMap`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MappedAddress {
+    /// Region containing the address
+    pub region: MemoryRegion,
+
+    /// Bank mapped to the address
+    pub bank: u16,
+
+    /// Offset from the start of the region
+    pub offset: u16
+}
+
+/// Offset in a file loaded into the emulator.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FileOffset {
+    /// Offset in the ROM
+    Rom(usize),
+
+    /// Offset in the save file (cartridge RAM)
+    Save(usize)
+}
+
+impl MappedAddress {
+    /// Get the offset in the ROM or save file of this address.
+    ///
+    /// Returns `None` if the address is not in ROM or cartridge RAM. The offset is not checked
+    /// against the size of the file; mappers mirror banks past the end of the file, so callers may
+    /// want to wrap it.
+    pub const fn get_file_offset(&self) -> Option<FileOffset> {
+        let offset = self.offset as usize;
+        match self.region {
+            MemoryRegion::Rom0 | MemoryRegion::RomX => Some(FileOffset::Rom(self.bank as usize * 0x4000 + offset)),
+            MemoryRegion::Sram => Some(FileOffset::Save(self.bank as usize * 0x2000 + offset)),
+            _ => None
+        }
+    }
+
+    /// Get the region, bank, and offset of an offset in the ROM or save file.
+    ///
+    /// ROM bank 0 is placed in [`MemoryRegion::Rom0`], and all other ROM banks are placed in
+    /// [`MemoryRegion::RomX`]. Returns `None` if the bank does not fit in 16 bits.
+    pub fn from_file_offset(offset: FileOffset) -> Option<MappedAddress> {
+        let (region, bank, offset) = match offset {
+            FileOffset::Rom(offset) => {
+                let bank = offset / 0x4000;
+                let region = if bank == 0 { MemoryRegion::Rom0 } else { MemoryRegion::RomX };
+                (region, bank, offset % 0x4000)
+            },
+            FileOffset::Save(offset) => (MemoryRegion::Sram, offset / 0x2000, offset % 0x2000)
+        };
+        Some(MappedAddress { region, bank: u16::try_from(bank).ok()?, offset: offset as u16 })
+    }
+}
+
+/// Banks mapped to the CPU's address space at a point in time.
+///
+/// Get it with [`get_memory_map`](crate::RunnableInstanceFunctions::get_memory_map). This does
+/// not account for the boot ROM, which is mapped over the start of ROM bank 0 while it runs.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MemoryMap {
+    /// Bank mapped to [`MemoryRegion::Rom0`]
+    pub rom0_bank: u16,
+
+    /// Bank mapped to [`MemoryRegion::RomX`]
+    pub romx_bank: u16,
+
+    /// Bank mapped to [`MemoryRegion::Vram`]
+    pub vram_bank: u16,
+
+    /// Bank mapped to [`MemoryRegion::Sram`]
+    pub sram_bank: u16,
+
+    /// Bank mapped to [`MemoryRegion::WramX`]
+    pub wramx_bank: u16
+}
+
+impl MemoryMap {
+    /// Get the region, bank, and offset of `address`.
+    ///
+    /// Addresses in [`MemoryRegion::Echo`] have the bank of the work RAM they mirror.
+    pub const fn map(&self, address: u16) -> MappedAddress {
+        let region = MemoryRegion::from_address(address);
+        let bank = match region {
+            MemoryRegion::Echo if address >= 0xF000 => self.wramx_bank,
+            _ => self.get_bank(region)
+        };
+        MappedAddress { region, bank, offset: address - region.get_start_address() }
+    }
+
+    /// Get the bank mapped to `region`.
+    ///
+    /// Unbanked regions (including [`MemoryRegion::Echo`]) are always bank 0.
+    pub const fn get_bank(&self, region: MemoryRegion) -> u16 {
+        match region {
+            MemoryRegion::Rom0 => self.rom0_bank,
+            MemoryRegion::RomX => self.romx_bank,
+            MemoryRegion::Vram => self.vram_bank,
+            MemoryRegion::Sram => self.sram_bank,
+            MemoryRegion::WramX => self.wramx_bank,
+            _ => 0
+        }
+    }
+
+    /// Get the CPU address of `address`, if its bank is currently mapped.
+    ///
+    /// Returns `None` if the bank is not mapped or the offset is outside of the region.
+    pub const fn get_address(&self, address: &MappedAddress) -> Option<u16> {
+        if address.offset >= address.region.get_size() {
+            return None
+        }
+        let cpu_address = address.region.get_start_address() + address.offset;
+        if self.map(cpu_address).bank != address.bank {
+            return None
+        }
+        Some(cpu_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: MemoryMap = MemoryMap { rom0_bank: 0, romx_bank: 5, vram_bank: 1, sram_bank: 2, wramx_bank: 3 };
+
+    fn mapped(region: MemoryRegion, bank: u16, offset: u16) -> MappedAddress {
+        MappedAddress { region, bank, offset }
+    }
+
+    #[test]
+    fn regions() {
+        for address in 0..=0xFFFF {
+            let region = MemoryRegion::from_address(address);
+            let start = region.get_start_address();
+            assert!(address >= start && (address - start) < region.get_size(), "{address:04x} in {region:?}");
+        }
+        assert_eq!(MemoryRegion::from_address(0x3FFF), MemoryRegion::Rom0);
+        assert_eq!(MemoryRegion::from_address(0x4000), MemoryRegion::RomX);
+        assert_eq!(MemoryRegion::from_address(0xFDFF), MemoryRegion::Echo);
+        assert_eq!(MemoryRegion::from_address(0xFE00), MemoryRegion::Oam);
+        assert_eq!(MemoryRegion::from_address(0xFFFF), MemoryRegion::Ie);
+    }
+
+    #[test]
+    fn map() {
+        assert_eq!(MAP.map(0x0150), mapped(MemoryRegion::Rom0, 0, 0x0150));
+        assert_eq!(MAP.map(0x4123), mapped(MemoryRegion::RomX, 5, 0x0123));
+        assert_eq!(MAP.map(0x9FFF), mapped(MemoryRegion::Vram, 1, 0x1FFF));
+        assert_eq!(MAP.map(0xA010), mapped(MemoryRegion::Sram, 2, 0x0010));
+        assert_eq!(MAP.map(0xC000), mapped(MemoryRegion::Wram0, 0, 0x0000));
+        assert_eq!(MAP.map(0xD800), mapped(MemoryRegion::WramX, 3, 0x0800));
+        assert_eq!(MAP.map(0xFE10), mapped(MemoryRegion::Oam, 0, 0x0010));
+        assert_eq!(MAP.map(0xFF44), mapped(MemoryRegion::Io, 0, 0x0044));
+        assert_eq!(MAP.map(0xFFFE), mapped(MemoryRegion::Hram, 0, 0x007E));
+        assert_eq!(MAP.map(0xFFFF), mapped(MemoryRegion::Ie, 0, 0x0000));
+
+        // Some mappers can switch ROM bank 0.
+        let multicart = MemoryMap { rom0_bank: 0x20, ..MAP };
+        assert_eq!(multicart.map(0x0150), mapped(MemoryRegion::Rom0, 0x20, 0x0150));
+    }
+
+    #[test]
+    fn echo_banks() {
+        // The lower part mirrors WRAM0, and the upper part mirrors WRAMX.
+        assert_eq!(MAP.map(0xE123), mapped(MemoryRegion::Echo, 0, 0x0123));
+        assert_eq!(MAP.map(0xEFFF), mapped(MemoryRegion::Echo, 0, 0x0FFF));
+        assert_eq!(MAP.map(0xF000), mapped(MemoryRegion::Echo, 3, 0x1000));
+        assert_eq!(MAP.map(0xFDFF), mapped(MemoryRegion::Echo, 3, 0x1DFF));
+        assert_eq!(MAP.get_bank(MemoryRegion::Echo), 0);
+    }
+
+    #[test]
+    fn file_offsets() {
+        assert_eq!(MAP.map(0x0150).get_file_offset(), Some(FileOffset::Rom(0x0150)));
+        assert_eq!(MAP.map(0x4123).get_file_offset(), Some(FileOffset::Rom(5 * 0x4000 + 0x0123)));
+        assert_eq!(MAP.map(0xA010).get_file_offset(), Some(FileOffset::Save(2 * 0x2000 + 0x0010)));
+        assert_eq!(MAP.map(0x8000).get_file_offset(), None);
+        assert_eq!(MAP.map(0xD000).get_file_offset(), None);
+
+        assert_eq!(MappedAddress::from_file_offset(FileOffset::Rom(0x3FFF)), Some(mapped(MemoryRegion::Rom0, 0, 0x3FFF)));
+        assert_eq!(MappedAddress::from_file_offset(FileOffset::Rom(0x4000)), Some(mapped(MemoryRegion::RomX, 1, 0x0000)));
+        assert_eq!(MappedAddress::from_file_offset(FileOffset::Save(0x2001)), Some(mapped(MemoryRegion::Sram, 1, 0x0001)));
+        assert_eq!(MappedAddress::from_file_offset(FileOffset::Rom(0x4000 * 0x10000)), None);
+
+        for offset in [FileOffset::Rom(0), FileOffset::Rom(0x1234), FileOffset::Rom(0x7FFFFF), FileOffset::Save(0), FileOffset::Save(0x1FFFF)] {
+            let address = MappedAddress::from_file_offset(offset).unwrap();
+            assert_eq!(address.get_file_offset(), Some(offset));
+        }
+    }
+
+    #[test]
+    fn get_address() {
+        assert_eq!(MAP.get_address(&mapped(MemoryRegion::RomX, 5, 0x0123)), Some(0x4123));
+        assert_eq!(MAP.get_address(&mapped(MemoryRegion::RomX, 4, 0x0123)), None);
+        assert_eq!(MAP.get_address(&mapped(MemoryRegion::RomX, 5, 0x4000)), None);
+        assert_eq!(MAP.get_address(&mapped(MemoryRegion::WramX, 3, 0x0FFF)), Some(0xDFFF));
+        assert_eq!(MAP.get_address(&mapped(MemoryRegion::Hram, 0, 0x007F)), None);
+        assert_eq!(MAP.get_address(&mapped(MemoryRegion::Echo, 3, 0x1000)), Some(0xF000));
+        assert_eq!(MAP.get_address(&mapped(MemoryRegion::Echo, 0, 0x1000)), None);
+
+        for address in 0..=0xFFFF {
+            assert_eq!(MAP.get_address(&MAP.map(address)), Some(address));
+        }
+    }
+}
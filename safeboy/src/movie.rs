@@ -0,0 +1,487 @@
+use crate::{Model, ReadSaveStateError, RtcMode};
+use alloc::vec::Vec;
+use sameboy_sys::GB_model_t;
+
+const MOVIE_MAGIC: &[u8; 4] = b"SBMV";
+const MOVIE_VERSION: u16 = 1;
+const MOVIE_HEADER_SIZE: usize = 24;
+
+const MOVIE_FLAG_SAVE_STATE: u16 = 1 << 0;
+const FRAME_FLAG_RESET: u8 = 1 << 0;
+const FRAME_FLAG_QUICK_RESET: u8 = 1 << 1;
+
+/// Input movie for deterministic replays.
+///
+/// A movie stores the starting condition of the emulator, the CRC32 of the ROM it was recorded
+/// with, and the input mask for every frame (see
+/// [`set_input_button_mask`](crate::RunnableInstanceFunctions::set_input_button_mask)).
+///
+/// # Format
+///
+/// All integers are little endian.
+///
+/// | Offset | Size | Description                                                        |
+/// |--------|------|--------------------------------------------------------------------|
+/// | 0x00   | 4    | Magic (`SBMV`)                                                     |
+/// | 0x04   | 2    | Format version (currently `1`)                                     |
+/// | 0x06   | 2    | Flags; bit 0 is set if the movie starts from a save state          |
+/// | 0x08   | 4    | CRC32 of the ROM                                                   |
+/// | 0x0C   | 4    | Rerecord count                                                     |
+/// | 0x10   | 4    | Number of frames (`N`)                                             |
+/// | 0x14   | 4    | Size of the start data (`L`)                                       |
+/// | 0x18   | `L`  | Start data                                                         |
+/// | 0x18+L | 2×N  | Frames                                                             |
+///
+/// If the movie starts from a save state, the start data is the save state. Otherwise, it is
+/// five bytes: the model (`u32`, as a `GB_model_t`) followed by the RTC mode (`u8`, as a
+/// `GB_rtc_mode_t`).
+///
+/// Each frame is two bytes: the input mask followed by frame flags. Bit 0 of the frame flags is
+/// set if the emulator was hard reset before the frame, and bit 1 is set if it was partially reset
+/// instead.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Movie {
+    /// CRC32 of the ROM the movie was recorded with.
+    pub rom_crc32: u32,
+
+    /// Number of times a snapshot was loaded while recording.
+    pub rerecord_count: u32,
+
+    /// How the emulator is set up before the first frame.
+    pub start: MovieStart,
+
+    /// Input for every frame.
+    pub frames: Vec<MovieFrame>
+}
+
+impl Movie {
+    /// Read a movie.
+    pub fn from_bytes(data: &[u8]) -> Result<Movie, ReadMovieError> {
+        let header = data.get(..MOVIE_HEADER_SIZE).ok_or(ReadMovieError::Truncated)?;
+        let u16_at = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+        let u32_at = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap());
+
+        if &header[0..4] != MOVIE_MAGIC {
+            return Err(ReadMovieError::BadMagic)
+        }
+
+        let version = u16_at(0x04);
+        if version != MOVIE_VERSION {
+            return Err(ReadMovieError::UnsupportedVersion(version))
+        }
+
+        let flags = u16_at(0x06);
+        let rom_crc32 = u32_at(0x08);
+        let rerecord_count = u32_at(0x0C);
+        let frame_count = u32_at(0x10) as usize;
+        let start_size = u32_at(0x14) as usize;
+
+        let start_data = data[MOVIE_HEADER_SIZE..]
+            .get(..start_size)
+            .ok_or(ReadMovieError::Truncated)?;
+
+        let start = if (flags & MOVIE_FLAG_SAVE_STATE) != 0 {
+            MovieStart::SaveState(start_data.to_vec())
+        }
+        else {
+            let &[m0, m1, m2, m3, rtc_mode] = start_data else {
+                return Err(ReadMovieError::Truncated)
+            };
+            let model = u32::from_le_bytes([m0, m1, m2, m3]);
+            MovieStart::PowerOn {
+                model: Model::try_from(model as GB_model_t).map_err(|_| ReadMovieError::UnknownModel(model))?,
+                rtc_mode: match rtc_mode as u32 {
+                    sameboy_sys::GB_rtc_mode_t_GB_RTC_MODE_SYNC_TO_HOST => RtcMode::SyncToHost,
+                    sameboy_sys::GB_rtc_mode_t_GB_RTC_MODE_ACCURATE => RtcMode::Accurate,
+                    _ => return Err(ReadMovieError::UnknownRtcMode(rtc_mode))
+                }
+            }
+        };
+
+        let frame_data = data[MOVIE_HEADER_SIZE + start_size..]
+            .get(..frame_count.checked_mul(2).ok_or(ReadMovieError::Truncated)?)
+            .ok_or(ReadMovieError::Truncated)?;
+
+        let frames = frame_data
+            .chunks_exact(2)
+            .map(|frame| MovieFrame {
+                input: frame[0],
+                reset: if (frame[1] & FRAME_FLAG_RESET) != 0 {
+                    Some(MovieReset::Hard)
+                }
+                else if (frame[1] & FRAME_FLAG_QUICK_RESET) != 0 {
+                    Some(MovieReset::Quick)
+                }
+                else {
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Movie { rom_crc32, rerecord_count, start, frames })
+    }
+
+    /// Write the movie.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (flags, start_data) = match &self.start {
+            MovieStart::PowerOn { model, rtc_mode } => {
+                let mut start_data = Vec::with_capacity(5);
+                start_data.extend_from_slice(&(*model as u32).to_le_bytes());
+                start_data.push(*rtc_mode as u8);
+                (0, start_data)
+            },
+            MovieStart::SaveState(state) => (MOVIE_FLAG_SAVE_STATE, state.clone())
+        };
+
+        let mut data = Vec::with_capacity(MOVIE_HEADER_SIZE + start_data.len() + self.frames.len() * 2);
+        data.extend_from_slice(MOVIE_MAGIC);
+        data.extend_from_slice(&MOVIE_VERSION.to_le_bytes());
+        data.extend_from_slice(&flags.to_le_bytes());
+        data.extend_from_slice(&self.rom_crc32.to_le_bytes());
+        data.extend_from_slice(&self.rerecord_count.to_le_bytes());
+        data.extend_from_slice(&u32::try_from(self.frames.len()).expect("too many frames").to_le_bytes());
+        data.extend_from_slice(&u32::try_from(start_data.len()).expect("start data too large").to_le_bytes());
+        data.extend_from_slice(&start_data);
+        for frame in &self.frames {
+            data.push(frame.input);
+            data.push(match frame.reset {
+                Some(MovieReset::Hard) => FRAME_FLAG_RESET,
+                Some(MovieReset::Quick) => FRAME_FLAG_QUICK_RESET,
+                None => 0
+            });
+        }
+        data
+    }
+}
+
+/// Describes how the emulator is set up before the first frame of a movie.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MovieStart {
+    /// The emulator is switched to the given model and hard reset.
+    ///
+    /// Battery-backed RAM is not cleared, so it should be loaded (or cleared) beforehand.
+    PowerOn {
+        /// Model to emulate
+        model: Model,
+
+        /// RTC mode to use; this should be [`RtcMode::Accurate`] for movies to be deterministic
+        rtc_mode: RtcMode
+    },
+
+    /// A save state is loaded.
+    SaveState(Vec<u8>)
+}
+
+/// One frame of a movie.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct MovieFrame {
+    /// Input mask for the frame (see [`InputButton`](crate::InputButton))
+    pub input: u8,
+
+    /// The emulator was reset before this frame.
+    pub reset: Option<MovieReset>
+}
+
+/// Describes how the emulator was reset during a movie.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MovieReset {
+    /// Hard reset (see [`reset`](crate::Gameboy::reset))
+    Hard,
+
+    /// Partial reset, which retains some memory (see
+    /// [`partial_reset`](crate::Gameboy::partial_reset))
+    Quick
+}
+
+/// Describes how a movie being played back handles snapshots and reaching the end.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MovieMode {
+    /// The movie is never modified.
+    ///
+    /// Loading a snapshot seeks the playback, and input is returned to the user when the movie
+    /// ends.
+    ReadOnly,
+
+    /// The movie is modified.
+    ///
+    /// Loading a snapshot truncates the movie at the snapshot and resumes recording from there
+    /// (incrementing the rerecord count), and recording continues when the movie ends.
+    ReadWrite
+}
+
+/// Current status of a movie.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MovieStatus {
+    /// Input is being recorded.
+    Recording,
+
+    /// Input is being played back.
+    Playing,
+
+    /// Playback has reached the end of the movie.
+    Finished
+}
+
+/// Save state paired with the position in the movie it was created at.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MovieSnapshot {
+    /// Frame the snapshot was taken on
+    pub frame: u32,
+
+    /// Save state data
+    pub state: Vec<u8>
+}
+
+/// Describes an error from reading a movie.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReadMovieError {
+    /// The data is not a movie.
+    BadMagic,
+
+    /// The movie was created with an unsupported version of the format.
+    UnsupportedVersion(u16),
+
+    /// The movie ended unexpectedly.
+    Truncated,
+
+    /// The movie uses an unknown model.
+    UnknownModel(u32),
+
+    /// The movie uses an unknown RTC mode.
+    UnknownRtcMode(u8)
+}
+
+/// Describes an error from starting or seeking a movie.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MovieError {
+    /// The movie was recorded with a different ROM.
+    RomMismatch {
+        /// CRC32 of the ROM the movie was recorded with
+        expected: u32,
+
+        /// CRC32 of the loaded ROM
+        actual: u32
+    },
+
+    /// The movie's save state (or snapshot) could not be loaded.
+    SaveState(ReadSaveStateError),
+
+    /// The snapshot is past the end of the movie.
+    SnapshotOutOfRange,
+
+    /// No movie is active.
+    NoMovie
+}
+
+pub(crate) struct MovieSession {
+    movie: Movie,
+    mode: MovieMode,
+    frame: usize,
+    recording: bool,
+    applied_input: u8,
+    latched_input: Option<u8>,
+    reset_pending: Option<MovieReset>
+}
+
+impl MovieSession {
+    pub(crate) fn new(movie: Movie, mode: MovieMode, recording: bool) -> Self {
+        Self {
+            movie,
+            mode,
+            frame: 0,
+            recording,
+            applied_input: 0,
+            latched_input: None,
+            reset_pending: None
+        }
+    }
+
+    pub(crate) fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    pub(crate) fn into_movie(self) -> Movie {
+        self.movie
+    }
+
+    pub(crate) fn frame(&self) -> u32 {
+        self.frame as u32
+    }
+
+    pub(crate) fn status(&self) -> MovieStatus {
+        if self.recording {
+            MovieStatus::Recording
+        }
+        else if self.frame < self.movie.frames.len() {
+            MovieStatus::Playing
+        }
+        else {
+            MovieStatus::Finished
+        }
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: MovieMode) {
+        self.mode = mode;
+        if mode == MovieMode::ReadOnly {
+            self.recording = false;
+        }
+        else if self.frame >= self.movie.frames.len() {
+            self.recording = true;
+        }
+    }
+
+    /// Get the reset required before the first frame, if any.
+    pub(crate) fn starts_with_reset(&self) -> Option<MovieReset> {
+        self.movie.frames.first().and_then(|f| f.reset).filter(|_| !self.recording)
+    }
+
+    /// Latch the input for the current frame, returning the mask to apply if it was not yet latched.
+    pub(crate) fn latch_input(&mut self, user_input: u8) -> Option<u8> {
+        if self.latched_input.is_some() {
+            return None
+        }
+
+        let input = match self.status() {
+            MovieStatus::Playing => self.movie.frames[self.frame].input,
+            MovieStatus::Recording | MovieStatus::Finished => user_input
+        };
+        self.latched_input = Some(input);
+        self.applied_input = input;
+        Some(input)
+    }
+
+    /// Called on vblank. Returns the reset required before the next frame, if any.
+    pub(crate) fn end_frame(&mut self) -> Option<MovieReset> {
+        let input = self.latched_input.take().unwrap_or(self.applied_input);
+
+        if self.recording {
+            let reset = self.reset_pending.take();
+            self.movie.frames.push(MovieFrame { input, reset });
+            self.frame += 1;
+            return None
+        }
+
+        if self.frame >= self.movie.frames.len() {
+            return None
+        }
+
+        self.frame += 1;
+        if self.frame == self.movie.frames.len() && self.mode == MovieMode::ReadWrite {
+            self.recording = true;
+        }
+
+        self.movie.frames.get(self.frame).and_then(|f| f.reset).filter(|_| !self.recording)
+    }
+
+    pub(crate) fn on_reset(&mut self, reset: MovieReset) {
+        if self.recording {
+            self.reset_pending = Some(reset);
+        }
+    }
+
+    pub(crate) fn check_snapshot(&self, snapshot: &MovieSnapshot) -> Result<(), MovieError> {
+        if snapshot.frame as usize > self.movie.frames.len() {
+            return Err(MovieError::SnapshotOutOfRange)
+        }
+        Ok(())
+    }
+
+    pub(crate) fn seek(&mut self, frame: u32) {
+        self.frame = frame as usize;
+        self.latched_input = None;
+        self.reset_pending = None;
+
+        match self.mode {
+            MovieMode::ReadOnly => self.recording = false,
+            MovieMode::ReadWrite => {
+                self.movie.frames.truncate(self.frame);
+                self.movie.rerecord_count = self.movie.rerecord_count.saturating_add(1);
+                self.recording = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn test_movie() -> Movie {
+        Movie {
+            rom_crc32: 0x12345678,
+            rerecord_count: 3,
+            start: MovieStart::PowerOn { model: Model::DmgB, rtc_mode: RtcMode::Accurate },
+            frames: vec![
+                MovieFrame { input: 0x00, reset: None },
+                MovieFrame { input: 0x81, reset: Some(MovieReset::Hard) },
+                MovieFrame { input: 0x10, reset: Some(MovieReset::Quick) },
+                MovieFrame { input: 0xFF, reset: None }
+            ]
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let movie = test_movie();
+        assert_eq!(Movie::from_bytes(&movie.to_bytes()), Ok(movie));
+
+        let movie = Movie { start: MovieStart::SaveState(vec![1, 2, 3, 4, 5, 6, 7]), frames: Vec::new(), ..test_movie() };
+        assert_eq!(Movie::from_bytes(&movie.to_bytes()), Ok(movie));
+    }
+
+    #[test]
+    fn layout() {
+        let data = test_movie().to_bytes();
+        assert_eq!(data.len(), MOVIE_HEADER_SIZE + 5 + 4 * 2);
+        assert_eq!(&data[0x00..0x04], b"SBMV");
+        assert_eq!(&data[0x04..0x08], [1, 0, 0, 0]);
+        assert_eq!(&data[0x08..0x0C], [0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(&data[0x0C..0x18], [3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0]);
+        assert_eq!(&data[0x18..0x1D], [2, 0, 0, 0, RtcMode::Accurate as u8]);
+        assert_eq!(&data[0x1D..], [0x00, 0, 0x81, FRAME_FLAG_RESET, 0x10, FRAME_FLAG_QUICK_RESET, 0xFF, 0]);
+    }
+
+    #[test]
+    fn read_errors() {
+        let data = test_movie().to_bytes();
+        let with = |offset: usize, bytes: &[u8]| {
+            let mut data = data.clone();
+            data[offset..offset + bytes.len()].copy_from_slice(bytes);
+            Movie::from_bytes(&data)
+        };
+
+        assert_eq!(with(0x00, b"SBMW"), Err(ReadMovieError::BadMagic));
+        assert_eq!(with(0x04, &[2, 0]), Err(ReadMovieError::UnsupportedVersion(2)));
+        assert_eq!(with(0x18, &[0xFF, 0, 0, 0]), Err(ReadMovieError::UnknownModel(0xFF)));
+        assert_eq!(with(0x1C, &[0xFF]), Err(ReadMovieError::UnknownRtcMode(0xFF)));
+        assert_eq!(with(0x14, &[4]), Err(ReadMovieError::Truncated));
+        assert_eq!(with(0x10, &[5]), Err(ReadMovieError::Truncated));
+        assert_eq!(with(0x10, &[0xFF, 0xFF, 0xFF, 0xFF]), Err(ReadMovieError::Truncated));
+        assert_eq!(Movie::from_bytes(&data[..MOVIE_HEADER_SIZE - 1]), Err(ReadMovieError::Truncated));
+        assert_eq!(Movie::from_bytes(&data[..data.len() - 1]), Err(ReadMovieError::Truncated));
+    }
+
+    #[test]
+    fn session_resets() {
+        let mut session = MovieSession::new(Movie { frames: Vec::new(), ..test_movie() }, MovieMode::ReadWrite, true);
+        for (input, reset) in [(0x00, None), (0x81, Some(MovieReset::Hard)), (0x10, Some(MovieReset::Quick)), (0xFF, None)] {
+            if let Some(reset) = reset {
+                session.on_reset(reset);
+            }
+            assert_eq!(session.latch_input(input), Some(input));
+            assert_eq!(session.end_frame(), None);
+        }
+        assert_eq!(session.movie(), &test_movie());
+
+        // Playback returns each reset at the end of the frame before it.
+        let mut session = MovieSession::new(test_movie(), MovieMode::ReadOnly, false);
+        assert_eq!(session.starts_with_reset(), None);
+        let resets: Vec<_> = (0..4).map(|_| {
+            assert!(session.latch_input(0).is_some());
+            session.end_frame()
+        }).collect();
+        assert_eq!(resets, [Some(MovieReset::Hard), Some(MovieReset::Quick), None, None]);
+        assert_eq!(session.status(), MovieStatus::Finished);
+    }
+}
@@ -0,0 +1,221 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use crate::{CallFrame, CallStack, Instruction, Mnemonic, SymbolTable};
+
+/// Cycles attributed to a function.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FunctionProfile {
+    /// Address of the function, or `None` for code that was not entered through a tracked call
+    /// (such as the main loop)
+    pub address: Option<u16>,
+
+    /// Bank of the function, if it is banked
+    pub bank: Option<u16>,
+
+    /// Name of the function from the symbol table
+    pub name: Option<String>,
+
+    /// Number of times the function was called
+    pub calls: u64,
+
+    /// 8 MiHz ticks spent in the function, including any functions it called
+    pub inclusive_ticks: u64,
+
+    /// 8 MiHz ticks spent in the function itself
+    pub exclusive_ticks: u64
+}
+
+/// Table of cycles attributed to functions, from a [`Profiler`].
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ProfileReport {
+    /// Functions, sorted by exclusive ticks (highest first)
+    pub functions: Vec<FunctionProfile>,
+
+    /// 8 MiHz ticks spent executing instructions
+    pub busy_ticks: u64,
+
+    /// 8 MiHz ticks spent halted
+    pub idle_ticks: u64,
+
+    /// Number of frames covered by the report
+    pub frames: u64
+}
+
+impl ProfileReport {
+    /// Format the report as a table.
+    pub fn to_text(&self) -> String {
+        let mut output = String::new();
+        let total = self.busy_ticks + self.idle_ticks;
+        let percent = |ticks: u64| if total == 0 { 0.0 } else { ticks as f64 * 100.0 / total as f64 };
+
+        // Writing to a String can't fail.
+        let _ = writeln!(output, "{:>12} {:>7} {:>12} {:>7} {:>10}  Function", "Exclusive", "%", "Inclusive", "%", "Calls");
+        for function in &self.functions {
+            let name = match (function.name.as_ref(), function.address) {
+                (Some(name), _) => name.clone(),
+                (None, Some(address)) => format!("${:02X}:${address:04X}", function.bank.unwrap_or(0)),
+                (None, None) => String::from("(root)")
+            };
+            let _ = writeln!(
+                output,
+                "{:>12} {:>6.2}% {:>12} {:>6.2}% {:>10}  {name}",
+                function.exclusive_ticks, percent(function.exclusive_ticks),
+                function.inclusive_ticks, percent(function.inclusive_ticks),
+                function.calls
+            );
+        }
+        let _ = writeln!(output, "{:>12} {:>6.2}%  (halted)", self.idle_ticks, percent(self.idle_ticks));
+        output
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct FunctionKey {
+    address: u16,
+    bank: Option<u16>
+}
+
+impl FunctionKey {
+    fn from_frame(frame: &CallFrame) -> FunctionKey {
+        FunctionKey { address: frame.target, bank: frame.target_bank }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+struct FunctionStats {
+    calls: u64,
+    inclusive_ticks: u64,
+    exclusive_ticks: u64
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+struct Table {
+    functions: BTreeMap<Option<FunctionKey>, FunctionStats>,
+    busy_ticks: u64,
+    idle_ticks: u64,
+    frames: u64
+}
+
+impl Table {
+    fn add_ticks(&mut self, frames: &[CallFrame], ticks: u64) {
+        self.busy_ticks += ticks;
+
+        let innermost = frames.last().map(FunctionKey::from_frame);
+        self.functions.entry(innermost).or_default().exclusive_ticks += ticks;
+
+        // Code outside of any call is always running.
+        self.functions.entry(None).or_default().inclusive_ticks += ticks;
+        for (i, frame) in frames.iter().enumerate() {
+            // Recursive functions are only counted once.
+            let key = FunctionKey::from_frame(frame);
+            if !frames[..i].iter().any(|f| FunctionKey::from_frame(f) == key) {
+                self.functions.entry(Some(key)).or_default().inclusive_ticks += ticks;
+            }
+        }
+    }
+
+    fn report(&self, symbols: Option<&SymbolTable>) -> ProfileReport {
+        let mut functions: Vec<FunctionProfile> = self.functions.iter().map(|(key, stats)| {
+            let name = key.and_then(|k| {
+                let bank = if k.address < 0x4000 { 0 } else { k.bank.unwrap_or(0) };
+                symbols?.name_for_address(bank, k.address).map(String::from)
+            });
+            FunctionProfile {
+                address: key.map(|k| k.address),
+                bank: key.and_then(|k| k.bank),
+                name,
+                calls: stats.calls,
+                inclusive_ticks: stats.inclusive_ticks,
+                exclusive_ticks: stats.exclusive_ticks
+            }
+        }).collect();
+        functions.sort_by(|a, b| b.exclusive_ticks.cmp(&a.exclusive_ticks).then(b.inclusive_ticks.cmp(&a.inclusive_ticks)));
+
+        ProfileReport { functions, busy_ticks: self.busy_ticks, idle_ticks: self.idle_ticks, frames: self.frames }
+    }
+}
+
+/// Attributes emulated time to functions.
+///
+/// Attach this with [`set_profiler`](crate::RunnableInstanceFunctions::set_profiler).
+///
+/// Functions are tracked with a [`CallStack`], and the time between instructions is attributed
+/// to the function that was executing. Time spent halted is reported separately.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Profiler {
+    call_stack: CallStack,
+    aggregate: Table,
+    current_frame: Table,
+    last_frame: Table,
+    last_ticks: Option<u64>,
+    halted: bool
+}
+
+impl Profiler {
+    /// Instantiate a profiler.
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    /// Get a report of everything profiled so far.
+    ///
+    /// Functions are named with `symbols`, if set.
+    pub fn get_aggregate_report(&self, symbols: Option<&SymbolTable>) -> ProfileReport {
+        self.aggregate.report(symbols)
+    }
+
+    /// Get a report of the last complete frame.
+    ///
+    /// Functions are named with `symbols`, if set.
+    pub fn get_frame_report(&self, symbols: Option<&SymbolTable>) -> ProfileReport {
+        self.last_frame.report(symbols)
+    }
+
+    /// Get the call stack used for attributing time.
+    pub fn get_call_stack(&self) -> &CallStack {
+        &self.call_stack
+    }
+
+    /// Clear all results.
+    pub fn reset(&mut self) {
+        self.aggregate = Table::default();
+        self.current_frame = Table::default();
+        self.last_frame = Table::default();
+    }
+
+    /// Attribute the time since the last instruction, then track the instruction about to be
+    /// executed.
+    pub(crate) fn step(&mut self, instruction: Instruction, bank: Option<u16>, sp: u16, stack_top: u16, stack_top_bank: Option<u16>, ticks: u64) {
+        if let Some(last_ticks) = self.last_ticks {
+            let elapsed = ticks.saturating_sub(last_ticks);
+            if self.halted {
+                self.aggregate.idle_ticks += elapsed;
+                self.current_frame.idle_ticks += elapsed;
+            }
+            else {
+                let frames = self.call_stack.get_frames();
+                self.aggregate.add_ticks(frames, elapsed);
+                self.current_frame.add_ticks(frames, elapsed);
+            }
+        }
+        self.last_ticks = Some(ticks);
+        self.halted = instruction.mnemonic == Mnemonic::Halt;
+
+        self.call_stack.step(instruction, bank, sp, stack_top, stack_top_bank);
+        for frame in self.call_stack.get_new_frames() {
+            let key = Some(FunctionKey::from_frame(frame));
+            self.aggregate.functions.entry(key).or_default().calls += 1;
+            self.current_frame.functions.entry(key).or_default().calls += 1;
+        }
+    }
+
+    /// Finish the current frame.
+    pub(crate) fn end_frame(&mut self) {
+        self.aggregate.frames += 1;
+        self.current_frame.frames = 1;
+        self.last_frame = core::mem::take(&mut self.current_frame);
+    }
+}
@@ -0,0 +1,412 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Identifies a RAM watch added with [`RamWatchList::add`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RamWatchId(u32);
+
+/// Named value in memory to display and track.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RamWatch {
+    /// Name of the watch
+    pub name: String,
+
+    /// Address of the value (the first byte, for multi-byte values)
+    pub address: u16,
+
+    /// Only evaluate if this bank is mapped to `address`
+    pub bank: Option<u16>,
+
+    /// How the value is stored
+    pub value_type: RamWatchType,
+
+    /// How the value is displayed
+    pub format: RamWatchFormat
+}
+
+impl RamWatch {
+    /// Instantiate a watch with no bank, displayed in decimal.
+    pub fn new(name: &str, address: u16, value_type: RamWatchType) -> RamWatch {
+        RamWatch { name: String::from(name), address, bank: None, value_type, format: RamWatchFormat::Decimal }
+    }
+
+    /// Only evaluate if `bank` is mapped to the address.
+    pub fn with_bank(mut self, bank: u16) -> RamWatch {
+        self.bank = Some(bank);
+        self
+    }
+
+    /// Display the value with `format`.
+    pub fn with_format(mut self, format: RamWatchFormat) -> RamWatch {
+        self.format = format;
+        self
+    }
+
+    /// Decode the value from the bytes starting at the watch's address.
+    ///
+    /// `bytes` must contain at least [`RamWatchType::get_size`] bytes.
+    pub fn decode(&self, bytes: &[u8]) -> RamWatchValue {
+        self.value_type.decode(bytes)
+    }
+
+    /// Format `value` with the watch's format.
+    pub fn format_value(&self, value: RamWatchValue) -> String {
+        let value = match value {
+            RamWatchValue::Bool(b) => return String::from(if b { "true" } else { "false" }),
+            RamWatchValue::Integer(i) => i
+        };
+
+        let digits = self.value_type.get_size() * 2;
+
+        // BCD is shown as written, regardless of the format.
+        if let RamWatchType::Bcd { .. } = self.value_type {
+            return format!("{value:0digits$}")
+        }
+
+        let raw = match self.value_type {
+            RamWatchType::I8 => value as u8 as u32,
+            _ => value as u32
+        };
+        match self.format {
+            RamWatchFormat::Decimal => format!("{value}"),
+            RamWatchFormat::Hex => format!("${raw:0digits$X}"),
+            RamWatchFormat::Binary => format!("%{raw:0width$b}", width = digits * 4)
+        }
+    }
+}
+
+/// Describes how a watched value is stored.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RamWatchType {
+    /// Unsigned byte
+    U8,
+
+    /// Signed byte
+    I8,
+
+    /// Unsigned 16-bit little endian value
+    U16,
+
+    /// Unsigned 16-bit big endian value
+    U16BigEndian,
+
+    /// Binary-coded decimal, two digits per byte
+    ///
+    /// Digits above 9 are weighted as-is (e.g. `$1A` decodes to 20).
+    Bcd {
+        /// Number of bytes (1 to 4)
+        bytes: u8,
+
+        /// The most significant digits are stored first
+        big_endian: bool
+    },
+
+    /// Single bit (0 to 7) of a byte
+    Bit(u8)
+}
+
+impl RamWatchType {
+    /// Get the number of bytes the value occupies.
+    pub const fn get_size(self) -> usize {
+        match self {
+            RamWatchType::U8 | RamWatchType::I8 | RamWatchType::Bit(_) => 1,
+            RamWatchType::U16 | RamWatchType::U16BigEndian => 2,
+            RamWatchType::Bcd { bytes, .. } => match bytes {
+                0 => 1,
+                1..=4 => bytes as usize,
+                _ => 4
+            }
+        }
+    }
+
+    /// Decode a value from the bytes starting at its address.
+    ///
+    /// `bytes` must contain at least [`RamWatchType::get_size`] bytes.
+    pub fn decode(self, bytes: &[u8]) -> RamWatchValue {
+        let bytes = &bytes[..self.get_size()];
+        match self {
+            RamWatchType::U8 => RamWatchValue::Integer(bytes[0] as i32),
+            RamWatchType::I8 => RamWatchValue::Integer(bytes[0] as i8 as i32),
+            RamWatchType::U16 => RamWatchValue::Integer(u16::from_le_bytes([bytes[0], bytes[1]]) as i32),
+            RamWatchType::U16BigEndian => RamWatchValue::Integer(u16::from_be_bytes([bytes[0], bytes[1]]) as i32),
+            RamWatchType::Bcd { big_endian, .. } => {
+                let mut value = 0i32;
+                let mut add_byte = |byte: &u8| value = value * 100 + (byte >> 4) as i32 * 10 + (byte & 0xF) as i32;
+                if big_endian {
+                    bytes.iter().for_each(&mut add_byte);
+                }
+                else {
+                    bytes.iter().rev().for_each(&mut add_byte);
+                }
+                RamWatchValue::Integer(value)
+            },
+            RamWatchType::Bit(bit) => RamWatchValue::Bool((bytes[0] >> (bit & 7)) & 1 != 0)
+        }
+    }
+}
+
+/// Describes how a watched value is displayed.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RamWatchFormat {
+    /// Decimal (e.g. `42` or `-1`)
+    Decimal,
+
+    /// Hexadecimal (e.g. `$2A`)
+    Hex,
+
+    /// Binary (e.g. `%00101010`)
+    Binary
+}
+
+/// Decoded value of a RAM watch.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RamWatchValue {
+    /// Integer value
+    Integer(i32),
+
+    /// Value of a bit
+    Bool(bool)
+}
+
+/// Describes a watched value that changed.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RamWatchChange {
+    /// Watch whose value changed
+    pub id: RamWatchId,
+
+    /// Previous value, or `None` if it was not evaluated yet or its bank was not mapped
+    pub old_value: Option<RamWatchValue>,
+
+    /// New value, or `None` if its bank is not mapped
+    pub new_value: Option<RamWatchValue>
+}
+
+/// List of RAM watches, evaluated at the end of every frame.
+///
+/// Set it with [`set_ram_watch_list`](crate::RunnableInstanceFunctions::set_ram_watch_list).
+/// Values are read without side effects, and every change (including the first evaluation of a
+/// watch) is passed to [`ram_watch_changed`](crate::GameboyCallbacks::ram_watch_changed).
+#[derive(Clone, Debug, Default)]
+pub struct RamWatchList {
+    watches: Vec<(RamWatchId, RamWatch, Option<RamWatchValue>)>,
+    next_id: u32,
+    changes: Vec<RamWatchChange>
+}
+
+impl RamWatchList {
+    /// Instantiate an empty list.
+    pub fn new() -> RamWatchList {
+        RamWatchList::default()
+    }
+
+    /// Add a watch to the end of the list.
+    pub fn add(&mut self, watch: RamWatch) -> RamWatchId {
+        let id = RamWatchId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.watches.push((id, watch, None));
+        id
+    }
+
+    /// Remove a watch.
+    ///
+    /// Returns `false` if there is no such watch.
+    pub fn remove(&mut self, id: RamWatchId) -> bool {
+        let Some(index) = self.watches.iter().position(|(i, ..)| *i == id) else {
+            return false
+        };
+        self.watches.remove(index);
+        true
+    }
+
+    /// Remove all watches.
+    pub fn clear(&mut self) {
+        self.watches.clear();
+        self.changes.clear();
+    }
+
+    /// Get the number of watches.
+    pub fn len(&self) -> usize {
+        self.watches.len()
+    }
+
+    /// Returns true if there are no watches.
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    /// Get a watch.
+    pub fn get(&self, id: RamWatchId) -> Option<&RamWatch> {
+        self.watches.iter().find(|(i, ..)| *i == id).map(|(_, w, _)| w)
+    }
+
+    /// Get a watch mutably.
+    ///
+    /// Its value is re-evaluated at the end of the next frame.
+    pub fn get_mut(&mut self, id: RamWatchId) -> Option<&mut RamWatch> {
+        self.watches.iter_mut().find(|(i, ..)| *i == id).map(|(_, w, _)| w)
+    }
+
+    /// Get the value of a watch as of the end of the last frame.
+    ///
+    /// Returns `None` if there is no such watch, it was not evaluated yet, or its bank was not
+    /// mapped.
+    pub fn get_value(&self, id: RamWatchId) -> Option<RamWatchValue> {
+        self.watches.iter().find(|(i, ..)| *i == id).and_then(|(_, _, v)| *v)
+    }
+
+    /// Iterate through all watches in order, along with their values as of the end of the last
+    /// frame.
+    pub fn iter(&self) -> impl Iterator<Item = (RamWatchId, &RamWatch, Option<RamWatchValue>)> {
+        self.watches.iter().map(|(i, w, v)| (*i, w, *v))
+    }
+
+    /// Get the changes from the end of the last frame.
+    pub fn get_changes(&self) -> &[RamWatchChange] {
+        &self.changes
+    }
+
+    /// Re-evaluate all watches, returning the number of changes.
+    ///
+    /// `read` fills a buffer with bytes starting at an address, and `get_bank` returns the bank
+    /// mapped to an address.
+    pub(crate) fn update<R: Fn(u16, &mut [u8]), B: Fn(u16) -> Option<u16>>(&mut self, read: R, get_bank: B) -> usize {
+        self.changes.clear();
+        for (id, watch, value) in &mut self.watches {
+            let new_value = if watch.bank.is_none_or(|bank| get_bank(watch.address) == Some(bank)) {
+                let mut bytes = [0u8; 4];
+                read(watch.address, &mut bytes[..watch.value_type.get_size()]);
+                Some(watch.decode(&bytes))
+            }
+            else {
+                None
+            };
+
+            if new_value != *value {
+                self.changes.push(RamWatchChange { id: *id, old_value: *value, new_value });
+                *value = new_value;
+            }
+        }
+        self.changes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bcd(bytes: u8, big_endian: bool) -> RamWatchType {
+        RamWatchType::Bcd { bytes, big_endian }
+    }
+
+    fn format(value_type: RamWatchType, format: RamWatchFormat, bytes: &[u8]) -> String {
+        let watch = RamWatch::new("test", 0xC000, value_type).with_format(format);
+        watch.format_value(watch.decode(bytes))
+    }
+
+    #[test]
+    fn decode() {
+        let bytes = [0x12, 0x34, 0x56, 0x78, 0x9A];
+        assert_eq!(RamWatchType::U8.decode(&bytes), RamWatchValue::Integer(0x12));
+        assert_eq!(RamWatchType::U16.decode(&bytes), RamWatchValue::Integer(0x3412));
+        assert_eq!(RamWatchType::U16BigEndian.decode(&bytes), RamWatchValue::Integer(0x1234));
+        assert_eq!(RamWatchType::U16.decode(&[0xFF, 0xFF]), RamWatchValue::Integer(0xFFFF));
+
+        assert_eq!(RamWatchType::I8.decode(&[0x7F]), RamWatchValue::Integer(127));
+        assert_eq!(RamWatchType::I8.decode(&[0x80]), RamWatchValue::Integer(-128));
+        assert_eq!(RamWatchType::I8.decode(&[0xFF]), RamWatchValue::Integer(-1));
+
+        assert_eq!(RamWatchType::Bit(0).decode(&[0x01]), RamWatchValue::Bool(true));
+        assert_eq!(RamWatchType::Bit(1).decode(&[0x01]), RamWatchValue::Bool(false));
+        assert_eq!(RamWatchType::Bit(7).decode(&[0x80]), RamWatchValue::Bool(true));
+        assert_eq!(RamWatchType::Bit(9).decode(&[0x02]), RamWatchValue::Bool(true));
+    }
+
+    #[test]
+    fn decode_bcd() {
+        let bytes = [0x12, 0x34, 0x56, 0x78];
+        assert_eq!(bcd(1, false).decode(&bytes), RamWatchValue::Integer(12));
+        assert_eq!(bcd(2, false).decode(&bytes), RamWatchValue::Integer(3412));
+        assert_eq!(bcd(2, true).decode(&bytes), RamWatchValue::Integer(1234));
+        assert_eq!(bcd(4, false).decode(&bytes), RamWatchValue::Integer(78563412));
+        assert_eq!(bcd(4, true).decode(&bytes), RamWatchValue::Integer(12345678));
+        assert_eq!(bcd(4, true).decode(&[0x99; 4]), RamWatchValue::Integer(99999999));
+
+        // Digits above 9 are weighted as-is.
+        assert_eq!(bcd(1, true).decode(&[0x1A]), RamWatchValue::Integer(20));
+        assert_eq!(bcd(1, true).decode(&[0xFF]), RamWatchValue::Integer(165));
+
+        // Sizes are clamped to 1-4 bytes.
+        assert_eq!(bcd(0, true).get_size(), 1);
+        assert_eq!(bcd(5, true).get_size(), 4);
+        assert_eq!(bcd(5, true).decode(&[0x12, 0x34, 0x56, 0x78, 0x90]), RamWatchValue::Integer(12345678));
+    }
+
+    #[test]
+    fn format_value() {
+        assert_eq!(format(RamWatchType::U8, RamWatchFormat::Decimal, &[0x2A]), "42");
+        assert_eq!(format(RamWatchType::U8, RamWatchFormat::Hex, &[0x0A]), "$0A");
+        assert_eq!(format(RamWatchType::U8, RamWatchFormat::Binary, &[0x2A]), "%00101010");
+        assert_eq!(format(RamWatchType::U16, RamWatchFormat::Decimal, &[0x34, 0x12]), "4660");
+        assert_eq!(format(RamWatchType::U16, RamWatchFormat::Hex, &[0x2A, 0x00]), "$002A");
+        assert_eq!(format(RamWatchType::U16BigEndian, RamWatchFormat::Binary, &[0x01, 0x02]), "%0000000100000010");
+
+        // Signed values are shown as their raw bits in hexadecimal and binary.
+        assert_eq!(format(RamWatchType::I8, RamWatchFormat::Decimal, &[0xFF]), "-1");
+        assert_eq!(format(RamWatchType::I8, RamWatchFormat::Hex, &[0xFF]), "$FF");
+        assert_eq!(format(RamWatchType::I8, RamWatchFormat::Binary, &[0x80]), "%10000000");
+
+        assert_eq!(format(RamWatchType::Bit(3), RamWatchFormat::Hex, &[0x08]), "true");
+        assert_eq!(format(RamWatchType::Bit(3), RamWatchFormat::Decimal, &[0x00]), "false");
+    }
+
+    #[test]
+    fn format_bcd() {
+        // BCD is always shown in decimal, with every digit.
+        assert_eq!(format(bcd(1, true), RamWatchFormat::Decimal, &[0x05]), "05");
+        assert_eq!(format(bcd(2, true), RamWatchFormat::Hex, &[0x00, 0x42]), "0042");
+        assert_eq!(format(bcd(3, false), RamWatchFormat::Binary, &[0x56, 0x34, 0x12]), "123456");
+    }
+
+    /// Update `list` with all addresses mapped to `bank`.
+    fn update(list: &mut RamWatchList, memory: &[u8], bank: u16) -> usize {
+        let read = |address: u16, buffer: &mut [u8]| {
+            buffer.copy_from_slice(&memory[address as usize..address as usize + buffer.len()])
+        };
+        list.update(read, |_| Some(bank))
+    }
+
+    #[test]
+    fn update_changes() {
+        let mut list = RamWatchList::new();
+        let byte = list.add(RamWatch::new("byte", 0xC000, RamWatchType::U8));
+        let banked = list.add(RamWatch::new("banked", 0xD000, RamWatchType::U16).with_bank(2));
+
+        let mut memory = [0u8; 0x10000];
+        memory[0xC000] = 5;
+        memory[0xD000] = 0x34;
+        memory[0xD001] = 0x12;
+
+        // Every watch changes on its first evaluation.
+        assert_eq!(update(&mut list, &memory, 2), 2);
+        assert_eq!(list.get_changes(), [
+            RamWatchChange { id: byte, old_value: None, new_value: Some(RamWatchValue::Integer(5)) },
+            RamWatchChange { id: banked, old_value: None, new_value: Some(RamWatchValue::Integer(0x1234)) }
+        ]);
+
+        assert_eq!(update(&mut list, &memory, 2), 0);
+
+        // Only changed values and unmapped banks are reported.
+        memory[0xC000] = 6;
+        assert_eq!(update(&mut list, &memory, 1), 2);
+        assert_eq!(list.get_changes(), [
+            RamWatchChange { id: byte, old_value: Some(RamWatchValue::Integer(5)), new_value: Some(RamWatchValue::Integer(6)) },
+            RamWatchChange { id: banked, old_value: Some(RamWatchValue::Integer(0x1234)), new_value: None }
+        ]);
+        assert_eq!(list.get_value(banked), None);
+
+        assert!(list.remove(byte));
+        assert!(!list.remove(byte));
+        assert_eq!(list.len(), 1);
+    }
+}
@@ -0,0 +1,273 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Named address from a symbol file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Symbol {
+    /// Bank of the address
+    pub bank: u16,
+
+    /// Address
+    pub address: u16,
+
+    /// Name (local labels contain a `.`)
+    pub name: String
+}
+
+impl Symbol {
+    /// Returns true if this is a local label (e.g. `Function.loop`).
+    pub fn is_local(&self) -> bool {
+        self.name.contains('.')
+    }
+}
+
+/// Table of symbols, such as those loaded from an RGBDS or no$gmb `.sym` file.
+///
+/// Banks follow the `.sym` convention: ROM0, WRAM0, and unbanked memory use bank 0, and
+/// switchable regions use the bank that is mapped there.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SymbolTable {
+    // Sorted by bank, then address
+    symbols: Vec<Symbol>
+}
+
+/// Symbols further than this from an address are not used to describe it.
+const MAX_DESCRIBE_OFFSET: u16 = 0x1000;
+
+impl SymbolTable {
+    /// Instantiate an empty table.
+    pub const fn new() -> SymbolTable {
+        SymbolTable { symbols: Vec::new() }
+    }
+
+    /// Parse a `.sym` file.
+    ///
+    /// Each line is in the form `BB:AAAA Name`, where `BB` and `AAAA` are the bank and address in
+    /// hexadecimal. Comments (starting with `;`), section headers (e.g. `[labels]`), and lines that
+    /// can't be parsed are ignored.
+    pub fn from_bytes(data: &[u8]) -> SymbolTable {
+        let mut table = SymbolTable::new();
+        for line in data.split(|b| *b == b'\n') {
+            let line = String::from_utf8_lossy(line);
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if let Some(symbol) = parse_line(line) {
+                table.add(symbol);
+            }
+        }
+        table
+    }
+
+    /// Add a symbol.
+    pub fn add(&mut self, symbol: Symbol) {
+        let index = self.symbols.partition_point(|s| (s.bank, s.address) <= (symbol.bank, symbol.address));
+        self.symbols.insert(index, symbol);
+    }
+
+    /// Remove all symbols.
+    pub fn clear(&mut self) {
+        self.symbols.clear();
+    }
+
+    /// Get all symbols, sorted by bank and then address.
+    pub fn get_symbols(&self) -> &[Symbol] {
+        self.symbols.as_slice()
+    }
+
+    /// Get the number of symbols.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns true if there are no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Get the symbol named `name`.
+    pub fn address_for_name(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|s| s.name == name)
+    }
+
+    /// Get the name of the symbol at exactly `bank:address`.
+    ///
+    /// Non-local labels are preferred. If nothing is found in `bank`, bank 0 is also checked.
+    pub fn name_for_address(&self, bank: u16, address: u16) -> Option<&str> {
+        self.exact(bank, address)
+            .or_else(|| if bank != 0 { self.exact(0, address) } else { None })
+            .map(|s| s.name.as_str())
+    }
+
+    /// Describe `bank:address` relative to the nearest preceding non-local label, such as `Main` or
+    /// `Main+$012`.
+    ///
+    /// Returns `None` if no label is within `$1000` bytes before the address in the same memory
+    /// region. If nothing is found in `bank`, bank 0 is also checked.
+    pub fn describe(&self, bank: u16, address: u16) -> Option<String> {
+        let symbol = self.nearest(bank, address)
+            .or_else(|| if bank != 0 { self.nearest(0, address) } else { None })?;
+        let offset = address - symbol.address;
+        if offset == 0 {
+            Some(symbol.name.clone())
+        }
+        else {
+            Some(format!("{}+${offset:03x}", symbol.name))
+        }
+    }
+
+    fn in_bank(&self, bank: u16) -> &[Symbol] {
+        let start = self.symbols.partition_point(|s| s.bank < bank);
+        let end = self.symbols.partition_point(|s| s.bank <= bank);
+        &self.symbols[start..end]
+    }
+
+    fn exact(&self, bank: u16, address: u16) -> Option<&Symbol> {
+        let symbols = self.in_bank(bank);
+        let start = symbols.partition_point(|s| s.address < address);
+        let mut matches = symbols[start..].iter().take_while(|s| s.address == address);
+        let first = matches.clone().next()?;
+        Some(matches.find(|s| !s.is_local()).unwrap_or(first))
+    }
+
+    fn nearest(&self, bank: u16, address: u16) -> Option<&Symbol> {
+        let symbols = self.in_bank(bank);
+        let end = symbols.partition_point(|s| s.address <= address);
+        symbols[..end]
+            .iter()
+            .rev()
+            .take_while(|s| address - s.address <= MAX_DESCRIBE_OFFSET && region(s.address) == region(address))
+            .find(|s| !s.is_local())
+    }
+}
+
+/// Get the memory region of an address, since banks are only meaningful within a region.
+const fn region(address: u16) -> u8 {
+    match address {
+        0x0000..=0x3FFF => 0,
+        0x4000..=0x7FFF => 1,
+        0x8000..=0x9FFF => 2,
+        0xA000..=0xBFFF => 3,
+        0xC000..=0xCFFF => 4,
+        0xD000..=0xDFFF => 5,
+        _ => 6
+    }
+}
+
+fn parse_line(line: &str) -> Option<Symbol> {
+    let (location, name) = line.split_once(char::is_whitespace)?;
+    let (bank, address) = location.split_once(':')?;
+    let name = name.split_whitespace().next()?;
+    Some(Symbol {
+        bank: u16::from_str_radix(bank, 16).ok()?,
+        address: u16::from_str_radix(address, 16).ok()?,
+        name: String::from(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(bank: u16, address: u16, name: &str) -> Symbol {
+        Symbol { bank, address, name: String::from(name) }
+    }
+
+    fn test_table() -> SymbolTable {
+        SymbolTable::from_bytes(b"; File generated by rgblink\n\
+            [labels]\n\
+            01:4000 Bank1Start\n\
+            00:0150 Main\n\
+            00:0150 Main.loop\n\
+            00:0160 Main.end ; comment\n\
+            00:3ff0 HomeEnd\n\
+            00:c000 wBuffer\n\
+            02:4000 Bank2Start\n\
+            02:4100 Bank2Start.local\n")
+    }
+
+    #[test]
+    fn parse() {
+        let table = test_table();
+        assert_eq!(table.get_symbols(), [
+            symbol(0, 0x0150, "Main"),
+            symbol(0, 0x0150, "Main.loop"),
+            symbol(0, 0x0160, "Main.end"),
+            symbol(0, 0x3FF0, "HomeEnd"),
+            symbol(0, 0xC000, "wBuffer"),
+            symbol(1, 0x4000, "Bank1Start"),
+            symbol(2, 0x4000, "Bank2Start"),
+            symbol(2, 0x4100, "Bank2Start.local")
+        ]);
+        assert!(table.get_symbols()[2].is_local());
+        assert_eq!(table.address_for_name("wBuffer"), Some(&symbol(0, 0xC000, "wBuffer")));
+        assert_eq!(table.address_for_name("Missing"), None);
+    }
+
+    #[test]
+    fn parse_ignores_invalid_lines() {
+        let table = SymbolTable::from_bytes(b"\n\
+            00:0150\n\
+            0150 NoBank\n\
+            zz:0150 BadBank\n\
+            00:xyz BadAddress\n\
+            00:10000 TooLarge\n\
+            ;00:0150 Commented\n\
+            \t00:0200   Spaced   extra\r\n\
+            00:0300 Crlf\r\n");
+        assert_eq!(table.get_symbols(), [symbol(0, 0x0200, "Spaced"), symbol(0, 0x0300, "Crlf")]);
+        assert!(SymbolTable::from_bytes(b"").is_empty());
+    }
+
+    #[test]
+    fn name_for_address() {
+        let table = test_table();
+        assert_eq!(table.name_for_address(0, 0x0150), Some("Main"));
+        assert_eq!(table.name_for_address(0, 0x0160), Some("Main.end"));
+        assert_eq!(table.name_for_address(0, 0x0151), None);
+        assert_eq!(table.name_for_address(2, 0x4000), Some("Bank2Start"));
+        assert_eq!(table.name_for_address(3, 0x4000), None);
+
+        // Falls back to bank 0
+        assert_eq!(table.name_for_address(1, 0xC000), Some("wBuffer"));
+        assert_eq!(table.name_for_address(1, 0x0150), Some("Main"));
+    }
+
+    #[test]
+    fn describe() {
+        let table = test_table();
+        assert_eq!(table.describe(0, 0x0150).as_deref(), Some("Main"));
+        assert_eq!(table.describe(0, 0x0163).as_deref(), Some("Main+$013"));
+        assert_eq!(table.describe(2, 0x4105).as_deref(), Some("Bank2Start+$105"));
+        assert_eq!(table.describe(1, 0xC010).as_deref(), Some("wBuffer+$010"));
+        assert_eq!(table.describe(0, 0x0100), None);
+        assert_eq!(table.describe(3, 0x4000), None);
+    }
+
+    #[test]
+    fn describe_limits() {
+        let table = test_table();
+
+        // Within the offset limit
+        assert_eq!(table.describe(0, 0x0150 + MAX_DESCRIBE_OFFSET).as_deref(), Some("Main+$1000"));
+        assert_eq!(table.describe(0, 0x0151 + MAX_DESCRIBE_OFFSET), None);
+
+        // Labels in other regions are not used
+        assert_eq!(table.describe(0, 0x3FFF).as_deref(), Some("HomeEnd+$00f"));
+        assert_eq!(table.describe(0, 0x4000), None);
+        assert_eq!(table.describe(0, 0xD000), None);
+    }
+
+    #[test]
+    fn add_and_clear() {
+        let mut table = SymbolTable::new();
+        table.add(symbol(1, 0x4000, "B"));
+        table.add(symbol(0, 0x0200, "A"));
+        table.add(symbol(0, 0x0100, "C"));
+        let names: Vec<&str> = table.get_symbols().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["C", "A", "B"]);
+        assert_eq!(table.len(), 3);
+
+        table.clear();
+        assert!(table.is_empty());
+    }
+}
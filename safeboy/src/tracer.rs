@@ -0,0 +1,174 @@
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use crate::{decode_instruction, Registers};
+
+/// State of the CPU right before an instruction is executed.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TraceEntry {
+    /// Registers (PC is the address of the instruction)
+    pub registers: Registers,
+
+    /// ROM or RAM bank mapped to PC, if it is banked
+    pub bank: Option<u16>,
+
+    /// Opcode being executed
+    pub opcode: u8,
+
+    /// Bytes at PC, including the opcode
+    pub bytes: [u8; 4],
+
+    /// LY register
+    pub ly: u8,
+
+    /// Number of 8 MiHz ticks emulated before this instruction (see
+    /// [`get_elapsed_ticks`](crate::RunnableInstanceFunctions::get_elapsed_ticks))
+    pub ticks: u64
+}
+
+impl TraceEntry {
+    /// Append this entry to `output` as one line (including the newline) in `format`.
+    pub fn write(&self, format: TraceFormat, output: &mut String) {
+        let r = &self.registers;
+        // Writing to a String can't fail.
+        let _ = match format {
+            TraceFormat::GameboyDoctor => writeln!(
+                output,
+                "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+                r.get_a(), r.get_f(), r.get_b(), r.get_c(), r.get_d(), r.get_e(), r.get_h(), r.get_l(), r.sp, r.pc,
+                self.bytes[0], self.bytes[1], self.bytes[2], self.bytes[3]
+            ),
+            TraceFormat::Bgb => {
+                let flags = r.get_flags();
+                let flag = |set: bool, c: char| if set { c } else { '-' };
+                let instruction = decode_instruction(&self.bytes, r.pc).expect("instructions are at most 3 bytes long");
+
+                let mut bytes = String::new();
+                for byte in &self.bytes[..instruction.length as usize] {
+                    let _ = write!(bytes, "{byte:02X} ");
+                }
+
+                writeln!(
+                    output,
+                    "A:{:02X} F:{}{}{}{} BC:{:04X} DE:{:04X} HL:{:04X} SP:{:04X} PC:{:04X} (cy: {}) LY:{} |[{:02X}]0x{:04X}: {:<9} {}",
+                    r.get_a(), flag(flags.zero, 'Z'), flag(flags.subtract, 'N'), flag(flags.half_carry, 'H'), flag(flags.carry, 'C'),
+                    r.bc, r.de, r.hl, r.sp, r.pc, self.ticks / 2, self.ly, self.bank.unwrap_or(0), r.pc, bytes,
+                    format!("{instruction}").to_lowercase()
+                )
+            }
+        };
+    }
+}
+
+/// Text format of a trace.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TraceFormat {
+    /// Format used by [Gameboy Doctor](https://github.com/robert/gameboy-doctor)
+    ///
+    /// For example, `A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02`.
+    /// Traces must start at PC `$0100` with the LY register stubbed to `$90` to compare them with
+    /// Gameboy Doctor's logs.
+    GameboyDoctor,
+
+    /// Format similar to BGB's trace log, including flags, cycles (in 4 MiHz units), LY, and
+    /// disassembly
+    ///
+    /// For example, `A:01 F:Z-HC BC:0013 DE:00D8 HL:014D SP:FFFE PC:0100 (cy: 0) LY:0 |[00]0x0100: 00        nop`.
+    Bgb
+}
+
+/// Records executed instructions.
+///
+/// Attach this with [`set_tracer`](crate::RunnableInstanceFunctions::set_tracer).
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Tracer {
+    entries: VecDeque<TraceEntry>,
+    capacity: Option<usize>,
+    start_address: Option<u16>,
+    stop_address: Option<u16>,
+    active: bool
+}
+
+impl Tracer {
+    /// Instantiate a tracer that records every instruction.
+    pub fn new() -> Tracer {
+        Tracer { active: true, ..Default::default() }
+    }
+
+    /// Only keep the last `capacity` instructions.
+    pub fn with_ring_buffer(mut self, capacity: usize) -> Tracer {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Start tracing when an instruction at `address` is executed.
+    ///
+    /// Nothing is recorded until then.
+    pub fn with_start_address(mut self, address: u16) -> Tracer {
+        self.start_address = Some(address);
+        self.active = false;
+        self
+    }
+
+    /// Stop tracing when an instruction at `address` is about to be executed.
+    ///
+    /// If a start address is set, tracing resumes the next time it is reached.
+    pub fn with_stop_address(mut self, address: u16) -> Tracer {
+        self.stop_address = Some(address);
+        self
+    }
+
+    /// Returns true if instructions are currently being recorded.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Get all recorded entries, oldest first.
+    pub fn get_entries(&self) -> impl ExactSizeIterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+
+    /// Remove and return all recorded entries, oldest first.
+    pub fn take_entries(&mut self) -> Vec<TraceEntry> {
+        self.entries.drain(..).collect()
+    }
+
+    /// Remove all recorded entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Format all recorded entries in `format`.
+    pub fn to_text(&self, format: TraceFormat) -> String {
+        let mut output = String::new();
+        for entry in &self.entries {
+            entry.write(format, &mut output);
+        }
+        output
+    }
+
+    /// Returns true if an instruction at `pc` should be recorded, updating the start/stop state.
+    pub(crate) fn should_record(&mut self, pc: u16) -> bool {
+        if self.active && self.stop_address == Some(pc) {
+            self.active = false;
+        }
+        else if !self.active && self.start_address == Some(pc) {
+            self.active = true;
+        }
+        self.active
+    }
+
+    pub(crate) fn record(&mut self, entry: TraceEntry) {
+        if let Some(capacity) = self.capacity {
+            if capacity == 0 {
+                return
+            }
+            while self.entries.len() >= capacity {
+                self.entries.pop_front();
+            }
+        }
+        self.entries.push_back(entry);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::MemoryHooks;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Range, RangeInclusive};
+
+/// Identifies a watch added with [`add_watch`](crate::RunnableInstanceFunctions::add_watch).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct WatchId(u32);
+
+/// Breakpoint or watchpoint on a range of addresses.
+///
+/// Watches are checked before any user callbacks are called, and only addresses that are watched
+/// have any further processing done on them.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Watch {
+    /// Accesses to watch for
+    pub accesses: MemoryHooks,
+
+    /// First address to watch
+    pub start: u16,
+
+    /// Last address to watch (inclusive)
+    pub end: u16,
+
+    /// Only trigger if this bank is mapped to the accessed address
+    pub bank: Option<u16>,
+
+    /// Only trigger if the accessed value meets this condition
+    ///
+    /// For reads, this is the value read. For writes, this is the value being written. For
+    /// execution, this is the opcode.
+    pub condition: Option<WatchCondition>
+}
+
+impl Watch {
+    /// Instantiate a breakpoint which triggers when an instruction at `address` is executed.
+    pub const fn execute(address: u16) -> Watch {
+        Watch::new(MemoryHooks::EXECUTE, address, address)
+    }
+
+    /// Instantiate a watchpoint which triggers when any address in `range` is read.
+    pub const fn read(range: RangeInclusive<u16>) -> Watch {
+        Watch::new(MemoryHooks::READ, *range.start(), *range.end())
+    }
+
+    /// Instantiate a watchpoint which triggers when any address in `range` is written.
+    pub const fn write(range: RangeInclusive<u16>) -> Watch {
+        Watch::new(MemoryHooks::WRITE, *range.start(), *range.end())
+    }
+
+    /// Instantiate a watchpoint which triggers when any address in `range` is read or written.
+    pub const fn access(range: RangeInclusive<u16>) -> Watch {
+        Watch::new(MemoryHooks::READ.union(MemoryHooks::WRITE), *range.start(), *range.end())
+    }
+
+    const fn new(accesses: MemoryHooks, start: u16, end: u16) -> Watch {
+        Watch { accesses, start, end, bank: None, condition: None }
+    }
+
+    /// Only trigger if `bank` is mapped to the accessed address.
+    pub const fn with_bank(mut self, bank: u16) -> Watch {
+        self.bank = Some(bank);
+        self
+    }
+
+    /// Only trigger if the accessed value meets `condition`.
+    pub const fn with_condition(mut self, condition: WatchCondition) -> Watch {
+        self.condition = Some(condition);
+        self
+    }
+}
+
+/// Condition on the value of a watched access.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WatchCondition {
+    /// The value is equal to the given value.
+    Equal(u8),
+
+    /// The value is not equal to the given value.
+    NotEqual(u8),
+
+    /// The value is less than the given value.
+    LessThan(u8),
+
+    /// The value is greater than the given value.
+    GreaterThan(u8),
+
+    /// The value, masked with `mask`, is equal to `value`.
+    Masked {
+        /// Bits to compare
+        mask: u8,
+
+        /// Expected value of the bits
+        value: u8
+    },
+
+    /// The value differs from what is currently in memory.
+    ///
+    /// This is only useful for write watches.
+    Changed
+}
+
+impl WatchCondition {
+    const fn test(self, value: u8, old_value: u8) -> bool {
+        match self {
+            WatchCondition::Equal(v) => value == v,
+            WatchCondition::NotEqual(v) => value != v,
+            WatchCondition::LessThan(v) => value < v,
+            WatchCondition::GreaterThan(v) => value > v,
+            WatchCondition::Masked { mask, value: v } => (value & mask) == v,
+            WatchCondition::Changed => value != old_value
+        }
+    }
+}
+
+/// Describes a watch that was triggered.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct WatchHit {
+    /// Watch that was triggered
+    pub id: WatchId,
+
+    /// Access that triggered the watch (one of [`MemoryHooks::READ`], [`MemoryHooks::WRITE`], or
+    /// [`MemoryHooks::EXECUTE`])
+    pub access: MemoryHooks,
+
+    /// Address that was accessed
+    pub address: u16,
+
+    /// Bank mapped to the address, if it is banked
+    pub bank: Option<u16>,
+
+    /// Value that was read or written, or the opcode that was executed
+    pub value: u8
+}
+
+const ACCESS_KINDS: [MemoryHooks; 3] = [MemoryHooks::READ, MemoryHooks::WRITE, MemoryHooks::EXECUTE];
+const BITMAP_SIZE: usize = 0x10000 / 64;
+
+#[derive(Default)]
+pub(crate) struct WatchManager {
+    watches: Vec<(WatchId, Watch)>,
+    next_id: u32,
+    hooks: MemoryHooks,
+    bitmaps: [Vec<u64>; 3],
+    hits: Vec<WatchHit>
+}
+
+impl WatchManager {
+    pub(crate) fn add(&mut self, watch: Watch) -> WatchId {
+        let id = WatchId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.watches.push((id, watch));
+        self.rebuild();
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: WatchId) -> bool {
+        let Some(index) = self.watches.iter().position(|(i, _)| *i == id) else {
+            return false
+        };
+        self.watches.remove(index);
+        self.rebuild();
+        true
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.watches.clear();
+        self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+        self.hooks = MemoryHooks::NONE;
+        for (kind, bitmap) in ACCESS_KINDS.iter().zip(self.bitmaps.iter_mut()) {
+            bitmap.clear();
+
+            let watches = self.watches.iter().filter(|(_, w)| w.accesses.contains(*kind));
+            for (_, watch) in watches {
+                if bitmap.is_empty() {
+                    *bitmap = vec![0; BITMAP_SIZE];
+                    self.hooks |= *kind;
+                }
+                for address in watch.start..=watch.end {
+                    bitmap[address as usize / 64] |= 1 << (address % 64);
+                }
+            }
+        }
+    }
+
+    /// Get the hooks needed to check all watches.
+    pub(crate) fn get_hooks(&self) -> MemoryHooks {
+        self.hooks
+    }
+
+    /// Returns true if any watch covers `address` for the given access.
+    #[inline]
+    pub(crate) fn is_watched(&self, access: MemoryHooks, address: u16) -> bool {
+        let Some(index) = ACCESS_KINDS.iter().position(|k| *k == access) else {
+            return false
+        };
+        self.bitmaps[index]
+            .get(address as usize / 64)
+            .is_some_and(|b| (b & (1 << (address % 64))) != 0)
+    }
+
+    /// Record hits for all watches matching the access, returning the indices of the new hits.
+    pub(crate) fn check(&mut self, access: MemoryHooks, address: u16, bank: Option<u16>, value: u8, old_value: u8) -> Range<usize> {
+        let first_hit = self.hits.len();
+        for (id, watch) in &self.watches {
+            if !watch.accesses.contains(access) || !(watch.start..=watch.end).contains(&address) {
+                continue
+            }
+            if watch.bank.is_some() && watch.bank != bank {
+                continue
+            }
+            if watch.condition.is_some_and(|c| !c.test(value, old_value)) {
+                continue
+            }
+            self.hits.push(WatchHit { id: *id, access, address, bank, value });
+        }
+        first_hit..self.hits.len()
+    }
+
+    pub(crate) fn get_hits(&self) -> &[WatchHit] {
+        self.hits.as_slice()
+    }
+
+    pub(crate) fn clear_hits(&mut self) {
+        self.hits.clear();
+    }
+}
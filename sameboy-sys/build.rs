@@ -12,9 +12,16 @@ const CHEAT_SEARCH_FILES: &[&str] = &[
 ];
 
 fn main() {
-    let disable_debugger = !cfg!(feature = "debugger");
+    // SameBoy's cheat search evaluates filters with the debugger's expression evaluator, so the
+    // debugger is compiled in for either feature.
+    let disable_debugger = !cfg!(feature = "debugger") && !cfg!(feature = "cheat-search");
     let disable_cheat_search = disable_debugger;
 
+    // Listing anything here stops cargo from rerunning this on any change, so list every input.
+    for path in ["build.rs", "SameBoy/Core", "SameBoy/version.mk", "extras", "windows-hacks"] {
+        println!("cargo:rerun-if-changed={path}");
+    }
+
     let core_path = Path::new("SameBoy/Core");
     if !core_path.is_dir() {
         panic!("Missing Core directory in SameBoy; perhaps you forgot to initialize the submodule?")
@@ -64,6 +71,18 @@ fn main() {
         build_system.file(path);
     }
 
+    // Extra functions for accessing internal state (see src/lib.rs)
+    let extras_path = Path::new("extras");
+    build_system.include(core_path);
+    for i in extras_path.read_dir().expect("failed to read extras dir") {
+        let file = i.expect("error when iterating extras");
+        let path = file.path();
+        if path.extension() != Some("c".as_ref()) {
+            continue;
+        }
+        build_system.file(path);
+    }
+
     println!("cargo:rustc-env=GB_VERSION={version_str}");
 
     if disable_debugger {
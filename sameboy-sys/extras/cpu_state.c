@@ -0,0 +1,30 @@
+// Accessors for CPU state that SameBoy does not expose through its public API
+//
+// These are compiled with GB_INTERNAL, so they can read the emulator's internal state directly.
+
+#include "gb.h"
+
+bool safeboy_get_ime(GB_gameboy_t *gb)
+{
+    return gb->ime;
+}
+
+void safeboy_set_ime(GB_gameboy_t *gb, bool ime)
+{
+    gb->ime = ime;
+}
+
+bool safeboy_is_halted(GB_gameboy_t *gb)
+{
+    return gb->halted;
+}
+
+bool safeboy_is_stopped(GB_gameboy_t *gb)
+{
+    return gb->stopped;
+}
+
+bool safeboy_is_double_speed(GB_gameboy_t *gb)
+{
+    return gb->cgb_double_speed;
+}
@@ -0,0 +1,13 @@
+// Symbol management for the debugger
+//
+// SameBoy only loads symbols from a file path, so this exposes its internal function for adding
+// symbols one at a time.
+
+#include "gb.h"
+
+#ifndef GB_DISABLE_DEBUGGER
+void safeboy_debugger_add_symbol(GB_gameboy_t *gb, uint16_t bank, uint16_t address, const char *symbol)
+{
+    GB_debugger_add_symbol(gb, bank, address, symbol);
+}
+#endif
@@ -0,0 +1,13 @@
+// Memory mapping helpers
+//
+// SameBoy does not expose whether the boot ROM is currently mapped over the cartridge ROM.
+
+#include "gb.h"
+
+bool safeboy_is_boot_rom_mapped(GB_gameboy_t *gb, uint16_t addr)
+{
+    if (gb->boot_rom_finished) {
+        return false;
+    }
+    return addr < 0x100 || (addr >= 0x200 && addr < 0x900 && GB_is_cgb(gb));
+}
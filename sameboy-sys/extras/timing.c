@@ -0,0 +1,11 @@
+// Timing helpers
+//
+// SameBoy only reports elapsed time per call to GB_run, so this is needed to keep a running total
+// of emulated time.
+
+#include "gb.h"
+
+unsigned safeboy_get_cycles_since_run(GB_gameboy_t *gb)
+{
+    return gb->cycles_since_run;
+}
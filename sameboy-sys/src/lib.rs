@@ -17,3 +17,33 @@ include!("bindings_pregenerated.rs");
 
 /// The SameBoy core's version
 pub const GB_VERSION: &str = env!("GB_VERSION");
+
+// Extra functions compiled from the extras directory; these are not part of SameBoy's API.
+unsafe extern "C" {
+    /// Get the interrupt master enable flag.
+    pub fn safeboy_get_ime(gb: *mut GB_gameboy_t) -> bool;
+
+    /// Set the interrupt master enable flag.
+    pub fn safeboy_set_ime(gb: *mut GB_gameboy_t, ime: bool);
+
+    /// Returns true if the CPU is halted (via the `HALT` instruction).
+    pub fn safeboy_is_halted(gb: *mut GB_gameboy_t) -> bool;
+
+    /// Returns true if the CPU is stopped (via the `STOP` instruction).
+    pub fn safeboy_is_stopped(gb: *mut GB_gameboy_t) -> bool;
+
+    /// Returns true if the CPU is in CGB double speed mode.
+    pub fn safeboy_is_double_speed(gb: *mut GB_gameboy_t) -> bool;
+
+    /// Get the number of 8 MiHz ticks emulated so far in the current call to `GB_run`.
+    pub fn safeboy_get_cycles_since_run(gb: *mut GB_gameboy_t) -> ::core::ffi::c_uint;
+
+    /// Returns true if `addr` currently reads from the boot ROM rather than the cartridge ROM.
+    pub fn safeboy_is_boot_rom_mapped(gb: *mut GB_gameboy_t, addr: u16) -> bool;
+}
+
+#[cfg(feature = "debugger")]
+unsafe extern "C" {
+    /// Add a symbol to the debugger.
+    pub fn safeboy_debugger_add_symbol(gb: *mut GB_gameboy_t, bank: u16, address: u16, symbol: *const ::core::ffi::c_char);
+}